@@ -37,11 +37,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         evapotranspiration: &data.evapotranspiration,
         catchment,
         calibration_period: ModelPeriod { start, end },
+        warmup_period: None,
         observed_runoff: &data.observed_runoff,
         destination,
         sample_size: Some(50),
         run_off_unit: RunOffUnit::NoConversion,
         generate_comparison_charts: true,
+        csv_precision: None,
+        skip_failures: None,
+        objective: None,
+        objective_fn: None,
+        output_format: None,
+        seed: None,
+        sampling_strategy: None,
+        progress: None,
+        max_threads: None,
+        chart_theme: None,
+        optional_metrics: None,
     };
 
     let mut model = Calibration::new(inputs)?;
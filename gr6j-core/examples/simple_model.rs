@@ -32,13 +32,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             x5: X5::new(0.55)?,
             x6: X6::new(5.3)?,
             store_levels: None,
+            uh_exponent: None,
         }],
         run_period: ModelPeriod::new(start, end)?,
-        warmup_period: None,
         destination: Some(Path::new(r"./gr6j-core/examples/results").to_path_buf()),
         observed_runoff: Some(&data.observed_runoff),
         run_off_unit: RunOffUnit::NoConversion,
-        logging: None,
+        ..Default::default()
     };
     let mut model = GR6JModel::new(inputs)?;
     model.run()?;
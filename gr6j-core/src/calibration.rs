@@ -1,21 +1,40 @@
+#[cfg(feature = "pdf")]
+use crate::chart::{
+    draw_flow_comparison_chart, draw_metric_vs_parameter_chart, render_chart_to_pdf_page, CALIBRATION_CHART_SIZE,
+};
 use crate::chart::{save_flow_comparison_chart, save_metric_vs_parameter_chart};
 use crate::error::{LoadModelError, RunModelError};
-use crate::inputs::{CalibrationCatchmentData, CalibrationInputs, CatchmentData, GR6JModelInputs, RunOffUnit};
-use crate::metric::CalibrationMetric;
-use crate::model::GR6JModel;
+use crate::inputs::{
+    CalibrationCatchmentData, CalibrationInputs, CalibrationOutputFormat, CatchmentData, ChartTheme, GR6JModelInputs,
+    ImageFormat, OptimiserSettings, ProgressFn, RunOffUnit, SamplingStrategy, WarmupStrategy,
+};
+use crate::metric::{
+    CalibrationMetric, CalibrationMetricContext, MetricType, MultiObjective, ObjectiveFn, OptionalMetrics,
+};
+use crate::model::{resolve_warmup_period, GR6JModel};
 use crate::outputs::{
     CalibrationMetricVector, CalibrationOutputs, CalibrationParameterValueVector, CalibrationParameterValues,
+    OptimiserOutputs,
 };
 use crate::parameter::{Parameter, X1, X2, X3, X4, X5, X6};
+#[cfg(feature = "pdf")]
+use crate::pdf::PdfDocument;
+use crate::utils::{format_float, CSV_PRECISION};
 use chrono::{Local, NaiveDate};
 use csv::Writer;
-use egobox_doe::{Lhs, LhsKind, SamplingMethod};
-use log::{debug, info};
+use egobox_doe::{Lhs, LhsKind, Random, SamplingMethod};
+use log::{debug, info, warn};
 use ndarray::{arr2, s, Array2};
+use rand_xoshiro::rand_core::{RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256Plus;
 use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::hash_map::RandomState;
 use std::fs::create_dir;
+use std::hash::{BuildHasher, Hasher};
 use std::mem;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Perform the model calibration to pick the best calibration parameters using comparison charts
 /// for the flow and flow duration curves and calibration metrics (such as Nash-Sutcliffe).
@@ -40,24 +59,65 @@ pub struct Calibration<'a> {
     /// Whether to export the comparison of the observed and simulated run-off time series and
     /// flow duration curves for each model.
     generate_comparison_charts: bool,
+    /// The area of each sub-catchment.
+    areas: Vec<f64>,
+    /// The number of decimal places of the numbers exported to the CSV files.
+    csv_precision: usize,
+    /// Whether to skip the samples that fail to run.
+    skip_failures: bool,
+    /// The weighted combination of metrics used to rank the samples.
+    objective: Option<MultiObjective>,
+    /// The custom objective function used to rank the samples.
+    objective_fn: Option<ObjectiveFn>,
+    /// The time vector of the input data.
+    time: &'a [NaiveDate],
+    /// The observed run-off for the full time vector.
+    observed_runoff: &'a [f64],
+    /// How to export the charts.
+    output_format: CalibrationOutputFormat,
+    /// The colours of the charts.
+    chart_theme: ChartTheme,
+    /// The optional metrics to calculate.
+    optional_metrics: Option<OptionalMetrics>,
+    /// The function called when each model completes its run.
+    progress: Option<ProgressFn>,
+    /// The thread pool used to run the models. When None the global pool is used.
+    thread_pool: Option<ThreadPool>,
 }
 
 /// The data collected by the parallel loop from each GR6J models.
 struct ParData {
+    /// The index of the sample.
+    sample_index: usize,
     /// The time vector
     time: Vec<NaiveDate>,
     /// The data of all hydrological units.
     catchment: Vec<CatchmentData>,
     /// The simulated run-off.
     run_off: Vec<f64>,
-    /// The metrics to use to assess the model performance.
-    metrics: CalibrationMetric,
-    /// The observed run-off
-    observed: Option<Vec<f64>>,
 }
 
 const PARAMETER_HEADER: [&str; 7] = ["Simulation", "X1", "X2", "X3", "X4", "X5", "X6"];
 
+/// Build the thread pool used to run the models.
+///
+/// # Arguments
+///
+/// * `max_threads`: The maximum number of threads. When None the global thread pool is used.
+///
+/// returns: `Result<Option<ThreadPool>, String>`
+fn build_thread_pool(max_threads: Option<usize>) -> Result<Option<ThreadPool>, String> {
+    match max_threads {
+        None => Ok(None),
+        Some(0) => Err("the maximum number of threads must be larger than 0".to_string()),
+        Some(max_threads) => ThreadPoolBuilder::new()
+            .num_threads(max_threads)
+            .build()
+            .map(Some)
+            .map_err(|e| format!("the thread pool cannot be created because {}", e)),
+    }
+}
+
 impl<'a> Calibration<'a> {
     /// Initialise the GR6J models to run for the calibration. This will initialise the inputs of
     /// [`crate::inputs::CalibrationInputs::sample_size`] GR6J models with a different combination
@@ -73,22 +133,62 @@ impl<'a> Calibration<'a> {
                 inputs.destination.to_str().unwrap().to_string(),
             ));
         }
+        if !inputs.destination.is_dir() {
+            return Err(LoadModelError::DestinationNotADirectory(
+                inputs.destination.to_str().unwrap().to_string(),
+            ));
+        }
+
+        if inputs.observed_runoff.len() != inputs.time.len() {
+            return Err(LoadModelError::MismatchedLength("observed run-off".to_string()));
+        }
+
+        // The warm-up period must end before the calibration period. The models extend the warm-up
+        // to the day before the calibration start date when there is a gap
+        if inputs.warmup_period.is_some() && !inputs.time.is_empty() {
+            resolve_warmup_period(
+                inputs.time,
+                inputs.calibration_period,
+                WarmupStrategy::PrecedingData,
+                inputs.warmup_period,
+                false,
+            )?;
+        }
 
         let destination = inputs
             .destination
             .join(Local::now().format("calibration_%Y%m%d_%H%M").to_string());
         let sample_size: usize = inputs.sample_size.unwrap_or(200);
+        if let Some(objective) = &inputs.objective {
+            objective.validate().map_err(LoadModelError::Generic)?;
+        }
+        if inputs.objective.is_some() && inputs.objective_fn.is_some() {
+            return Err(LoadModelError::Generic(
+                "The multi-objective and the custom objective function cannot be used together".to_string(),
+            ));
+        }
+
+        let thread_pool = build_thread_pool(inputs.max_threads).map_err(LoadModelError::Generic)?;
 
         let mut run_inputs: Vec<GR6JModelInputs> = vec![];
 
         // Collect the model inputs
-        info!("Generating {} parameter sub-samples with Latin-Hypercube", sample_size);
+        info!(
+            "Generating {} parameter sub-samples with {}",
+            sample_size,
+            inputs.sampling_strategy.unwrap_or_default()
+        );
 
-        // Generate the samples for each sub-catchment model
+        // Generate the samples for each sub-catchment model. Each unit uses a different seed so
+        // that the samples of units with the same ranges are not identical
         let all_samples: Vec<Array2<f64>> = inputs
             .catchment
             .iter()
-            .map(|data| Self::sample(data, sample_size, None))
+            .enumerate()
+            .map(|(uh_idx, data)| {
+                let seed = inputs.seed.map(|s| s.wrapping_add(uh_idx as u64));
+                Self::sample(data, sample_size, inputs.sampling_strategy, seed)
+            })
             .collect();
 
         for sample_idx in 0..all_samples[0].nrows() {
@@ -105,6 +205,7 @@ impl<'a> Calibration<'a> {
                     x5: X5::new(sample[4])?,
                     x6: X6::new(sample[5])?,
                     store_levels: None,
+                    uh_exponent: None,
                 });
             }
 
@@ -115,11 +216,11 @@ impl<'a> Calibration<'a> {
                 evapotranspiration: inputs.evapotranspiration,
                 catchment,
                 run_period: inputs.calibration_period,
-                warmup_period: None,
-                destination: None,
-                observed_runoff: Some(inputs.observed_runoff),
+                warmup_period: inputs.warmup_period,
+                // the metrics are calculated by the calibration using the same observed data
                 run_off_unit: inputs.run_off_unit.clone(),
                 logging: Some(false),
+                ..Default::default()
             });
         }
 
@@ -130,35 +231,93 @@ impl<'a> Calibration<'a> {
             run_off_unit: inputs.run_off_unit,
             destination,
             generate_comparison_charts: inputs.generate_comparison_charts,
+            areas: inputs.catchment.iter().map(|c| c.area).collect(),
+            csv_precision: inputs.csv_precision.unwrap_or(CSV_PRECISION),
+            skip_failures: inputs.skip_failures.unwrap_or(false),
+            objective: inputs.objective,
+            objective_fn: inputs.objective_fn,
+            time: inputs.time,
+            observed_runoff: inputs.observed_runoff,
+            output_format: inputs.output_format.unwrap_or_default(),
+            chart_theme: inputs.chart_theme.unwrap_or_default(),
+            optional_metrics: inputs.optional_metrics,
+            progress: inputs.progress,
+            thread_pool,
         })
     }
 
     /// Run the calibration. This will run the GR6J models using threads; the parallel loop will
-    /// stop if [`GR6JModel`] throws an error.
+    /// stop if [`GR6JModel`] throws an error, unless [`CalibrationInputs::skip_failures`] is enabled.
     ///
     /// returns: `Result<CalibrationOutputs, RunModelError>`
     pub fn run(&mut self) -> Result<CalibrationOutputs, RunModelError> {
+        match self.thread_pool.take() {
+            Some(thread_pool) => {
+                let outputs = thread_pool.install(|| self.run_models());
+                self.thread_pool = Some(thread_pool);
+                outputs
+            }
+            None => self.run_models(),
+        }
+    }
+
+    /// Run the models and export the results using the current thread pool.
+    ///
+    /// returns: `Result<CalibrationOutputs, RunModelError>`
+    fn run_models(&mut self) -> Result<CalibrationOutputs, RunModelError> {
         let run_inputs = mem::take(&mut self.run_inputs);
+        let total_samples = run_inputs.len();
 
-        let par_data: Result<Vec<_>, _> = run_inputs
-            .into_par_iter()
-            .enumerate()
-            .map(|(model, model_inputs)| {
-                info!("Running model #{}", model + 1);
-                let data = model_inputs.catchment.clone();
-
-                let mut model =
-                    GR6JModel::new(model_inputs).map_err(|e| RunModelError::CalibrationError(model, e.to_string()))?;
-                let results = model.run()?;
-                Ok::<ParData, RunModelError>(ParData {
-                    time: results.time,
-                    catchment: data.to_vec(),
-                    run_off: results.run_off,
-                    metrics: results.metrics.unwrap(),
-                    observed: model.observed,
-                })
+        let run_single_model = |(model_index, model_inputs): (usize, GR6JModelInputs)| {
+            info!("Running model #{}", model_index + 1);
+            let data = model_inputs.catchment.clone();
+
+            let mut model = GR6JModel::new(model_inputs)
+                .map_err(|e| RunModelError::CalibrationError(model_index, e.to_string()))?;
+            // an unstable sample is reported with its sample number
+            let results = model
+                .run()
+                .map_err(|e| RunModelError::CalibrationError(model_index, e.to_string()))?;
+            Ok::<ParData, RunModelError>(ParData {
+                sample_index: model_index,
+                time: results.time,
+                catchment: data.to_vec(),
+                run_off: results.run_off,
             })
-            .collect();
+        };
+
+        // The models complete in any order, therefore the completed runs are counted with an
+        // atomic counter shared by the threads
+        let completed = AtomicUsize::new(0);
+        let progress = &self.progress;
+        let run_model = |item: (usize, GR6JModelInputs)| {
+            let result = run_single_model(item);
+            if let Some(progress) = progress {
+                progress(completed.fetch_add(1, Ordering::SeqCst) + 1, total_samples);
+            }
+            result
+        };
+
+        let mut failed_samples: Vec<(usize, String)> = vec![];
+        let par_data: Result<Vec<_>, _> = if self.skip_failures {
+            let all_results: Vec<_> = run_inputs.into_par_iter().enumerate().map(run_model).collect();
+            let mut par_data = vec![];
+            for (model_index, result) in all_results.into_iter().enumerate() {
+                match result {
+                    Ok(data) => par_data.push(data),
+                    Err(e) => {
+                        warn!("Skipping model #{} because it failed: {}", model_index + 1, e);
+                        failed_samples.push((model_index, e.to_string()));
+                    }
+                }
+            }
+            match par_data.is_empty() {
+                true => Err(RunModelError::AllSamplesFailed(total_samples)),
+                false => Ok(par_data),
+            }
+        } else {
+            run_inputs.into_par_iter().enumerate().map(run_model).collect()
+        };
 
         // Create the destination folder
         if !self.destination.exists() {
@@ -167,9 +326,26 @@ impl<'a> Calibration<'a> {
         }
 
         let mut par_data = par_data?;
-        let observed = par_data[0].observed.clone().unwrap();
         let time: Vec<NaiveDate> = par_data[0].time.to_vec();
 
+        // Calculate the metrics. The quantities of the observed run-off are calculated once and
+        // shared by all samples
+        let start_index =
+            self.time.iter().position(|t| *t == time[0]).ok_or_else(|| {
+                RunModelError::CannotCalculateMetrics("cannot find the calibration start".to_string())
+            })?;
+        let end_index = start_index + time.len();
+        let context = CalibrationMetricContext::new_with_time(
+            &self.time[start_index..end_index],
+            &self.observed_runoff[start_index..end_index],
+            self.optional_metrics.clone(),
+        )?;
+        let metrics: Vec<CalibrationMetric> = par_data
+            .par_iter()
+            .map(|d| context.metrics(&d.run_off))
+            .collect::<Result<_, _>>()?;
+        let observed = context.observed();
+
         // Group the catchment data by sub-catchment
         let first_catchment_data = par_data.first().expect("Cannot find any results").catchment.clone();
         let total_uh = first_catchment_data.len();
@@ -188,16 +364,16 @@ impl<'a> Calibration<'a> {
             wtr.flush()?;
 
             // collect and write CSV lines
-            for (sim_id, c) in par_data.iter().enumerate() {
+            for c in par_data.iter() {
                 let data: CalibrationParameterValues = c.catchment[uh_id].clone().into();
                 wtr.write_record([
-                    format!("#{}", sim_id + 1),
-                    data.x1.to_string(),
-                    data.x2.to_string(),
-                    data.x3.to_string(),
-                    data.x4.to_string(),
-                    data.x5.to_string(),
-                    data.x6.to_string(),
+                    format!("#{}", c.sample_index + 1),
+                    format_float(data.x1, self.csv_precision),
+                    format_float(data.x2, self.csv_precision),
+                    format_float(data.x3, self.csv_precision),
+                    format_float(data.x4, self.csv_precision),
+                    format_float(data.x5, self.csv_precision),
+                    format_float(data.x6, self.csv_precision),
                 ])?;
                 wtr.flush()?;
 
@@ -217,18 +393,63 @@ impl<'a> Calibration<'a> {
         let mut metric_wtr = Writer::from_path(metric_dest)?;
 
         let mut write_headers = true;
-        for (sim_id, results) in par_data.iter().enumerate() {
-            let metrics = &results.metrics;
+        for (results, metrics) in par_data.iter().zip(&metrics) {
             if write_headers {
                 metrics.append_header_to_csv(&mut metric_wtr, Some("Simulation".to_string()))?;
             }
-            metrics.append_row_to_csv(&mut metric_wtr, Some(format!("#{}", sim_id + 1)))?;
+            metrics.append_row_to_csv(
+                &mut metric_wtr,
+                Some(format!("#{}", results.sample_index + 1)),
+                Some(self.csv_precision),
+            )?;
             write_headers = false;
         }
         info!("Exported metric file as '{}'", metric_dest_string);
 
         let run_off: Vec<Vec<f64>> = par_data.iter_mut().map(|d| mem::take(d.run_off.as_mut())).collect();
-        let metrics = CalibrationMetricVector(par_data.iter_mut().map(|d| d.metrics.clone()).collect());
+        let metrics = CalibrationMetricVector(metrics);
+
+        // Rank the samples using the composite score or the custom objective. Samples with a
+        // missing score are never selected
+        let objective_scores: Option<Vec<f64>> = match (&self.objective_fn, &self.objective) {
+            (Some(objective_fn), _) => Some(run_off.par_iter().map(|sim| objective_fn(observed, sim)).collect()),
+            (None, Some(objective)) => Some(metrics.0.iter().map(|m| objective.score(m)).collect()),
+            (None, None) => None,
+        };
+        let best_sample = objective_scores.as_ref().and_then(|scores| {
+            scores
+                .iter()
+                .enumerate()
+                .filter(|(_, score)| !score.is_nan())
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(sim_id, _)| sim_id)
+        });
+        if let (Some(sim_id), Some(scores)) = (best_sample, &objective_scores) {
+            info!(
+                "The best sample is #{} with a score of {}",
+                par_data[sim_id].sample_index + 1,
+                scores[sim_id]
+            );
+        }
+
+        // When all the charts are exported to a single PDF document, each chart is added as a page
+        #[cfg(feature = "pdf")]
+        let pdf_dest = self.destination.join("Charts.pdf");
+        #[cfg(feature = "pdf")]
+        let pdf_dest_string = pdf_dest.to_str().unwrap().to_string();
+        #[cfg(feature = "pdf")]
+        let mut pdf = match self.output_format {
+            CalibrationOutputFormat::SinglePdf => Some(PdfDocument::default()),
+            _ => None,
+        };
+        #[cfg(feature = "pdf")]
+        let save_files = pdf.is_none();
+        #[cfg(not(feature = "pdf"))]
+        let save_files = true;
+        let image_format = match self.output_format {
+            CalibrationOutputFormat::SvgFiles => ImageFormat::Svg,
+            _ => ImageFormat::Png,
+        };
 
         // Generate the parameter vs metric charts
         for (hu_id, parameters) in parameters_by_uh.iter().enumerate() {
@@ -237,13 +458,41 @@ impl<'a> Calibration<'a> {
                 _ => format!("Sub-catchment{}_", hu_id + 1),
             };
             for (p_id, parameter_values) in parameters.to_vec().iter().enumerate() {
-                let dest = self
-                    .destination
-                    .join(format!("{}X{}_vs_metrics.png", file_prefix, p_id + 1));
+                let dest = self.destination.join(format!(
+                    "{}X{}_vs_metrics.{}",
+                    file_prefix,
+                    p_id + 1,
+                    image_format.extension()
+                ));
                 let title = format!("{}Parameter X{}", file_prefix.replace('_', " / "), p_id + 1);
-                save_metric_vs_parameter_chart(parameter_values, &metrics, title, &dest).map_err(|e| {
-                    RunModelError::CannotGenerateChart(dest.to_str().unwrap().to_string(), e.to_string())
-                })?;
+                if save_files {
+                    save_metric_vs_parameter_chart(
+                        parameter_values,
+                        &metrics,
+                        title.clone(),
+                        &dest,
+                        image_format,
+                        &self.chart_theme,
+                    )
+                    .map_err(|e| {
+                        RunModelError::CannotGenerateChart(dest.to_str().unwrap().to_string(), e.to_string())
+                    })?;
+                }
+                #[cfg(feature = "pdf")]
+                if let Some(document) = &mut pdf {
+                    document.add_page(
+                        render_chart_to_pdf_page(CALIBRATION_CHART_SIZE, |root_area| {
+                            draw_metric_vs_parameter_chart(
+                                parameter_values,
+                                &metrics,
+                                title,
+                                &self.chart_theme,
+                                root_area,
+                            )
+                        })
+                        .map_err(|e| RunModelError::CannotGenerateChart(pdf_dest_string.clone(), e.to_string()))?,
+                    );
+                }
                 match parameters_by_uh.len() {
                     1 => info!("Saved chart for parameter X{}", p_id + 1),
                     _ => info!("Saved chart for sub-catchment {} - parameter X{}", hu_id + 1, p_id + 1),
@@ -253,22 +502,63 @@ impl<'a> Calibration<'a> {
 
         // Generate the comparison charts for the simulated vs. observed flow and FDC
         if self.generate_comparison_charts {
-            (0..par_data.len()).into_par_iter().try_for_each(|model_id| {
-                info!("Generating run-off chart for model #{}", model_id + 1);
-                let dest = self.destination.join(format!("Flows_model{}.png", model_id + 1));
-
-                save_flow_comparison_chart(
-                    &time,
-                    &run_off[model_id],
-                    &observed,
-                    format!("Simulated vs. observed - Model #{}", model_id + 1),
-                    &dest,
-                    &self.run_off_unit,
-                )
-                .map_err(|e| RunModelError::CannotGenerateChart(dest.to_str().unwrap().to_string(), e.to_string()))?;
-
-                Ok::<(), RunModelError>(())
-            })?;
+            let chart_title = |sample_index: usize| format!("Simulated vs. observed - Model #{}", sample_index + 1);
+            if save_files {
+                (0..par_data.len()).into_par_iter().try_for_each(|model_id| {
+                    let sample_index = par_data[model_id].sample_index;
+                    info!("Generating run-off chart for model #{}", sample_index + 1);
+                    let dest =
+                        self.destination
+                            .join(format!("Flows_model{}.{}", sample_index + 1, image_format.extension()));
+
+                    save_flow_comparison_chart(
+                        &time,
+                        &run_off[model_id],
+                        Some(observed),
+                        chart_title(sample_index),
+                        &dest,
+                        &self.run_off_unit,
+                        image_format,
+                        &self.chart_theme,
+                    )
+                    .map_err(|e| {
+                        RunModelError::CannotGenerateChart(dest.to_str().unwrap().to_string(), e.to_string())
+                    })?;
+
+                    Ok::<(), RunModelError>(())
+                })?;
+            }
+            #[cfg(feature = "pdf")]
+            if let Some(document) = &mut pdf {
+                let pages = (0..par_data.len())
+                    .into_par_iter()
+                    .map(|model_id| {
+                        let sample_index = par_data[model_id].sample_index;
+                        info!("Generating run-off chart for model #{}", sample_index + 1);
+                        render_chart_to_pdf_page(CALIBRATION_CHART_SIZE, |root_area| {
+                            draw_flow_comparison_chart(
+                                &time,
+                                &run_off[model_id],
+                                Some(observed),
+                                chart_title(sample_index),
+                                &self.run_off_unit,
+                                &self.chart_theme,
+                                root_area,
+                            )
+                        })
+                        .map_err(|e| RunModelError::CannotGenerateChart(pdf_dest_string.clone(), e.to_string()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                pages.into_iter().for_each(|page| document.add_page(page));
+            }
+        }
+
+        #[cfg(feature = "pdf")]
+        if let Some(document) = pdf {
+            document
+                .save(&pdf_dest)
+                .map_err(|e| RunModelError::CannotGenerateChart(pdf_dest_string.clone(), e.to_string()))?;
+            info!("Exported the charts as '{}'", pdf_dest_string);
         }
 
         Ok(CalibrationOutputs {
@@ -276,20 +566,307 @@ impl<'a> Calibration<'a> {
             run_off,
             parameters: parameters_by_uh,
             metrics,
+            areas: self.areas.clone(),
+            failed_samples,
+            objective_scores,
+            best_sample,
         })
     }
 
-    /// Create a sample with combinations of model parameters using the Latin Hypercube sampling.
+    /// Search the parameter set that drives the `objective` metric towards its ideal value using
+    /// the differential-evolution algorithm (DE/rand/1/bin). Unlike [`Calibration::run`], which only
+    /// explores the parameter space, the optimiser evolves a population of parameter sets over
+    /// several generations and converges to an optimum.
+    ///
+    /// The initial population is generated with [`CalibrationInputs::sampling_strategy`] and
+    /// [`CalibrationInputs::seed`]; the same seed always gives the same results. The models of each
+    /// generation run in parallel using [`CalibrationInputs::max_threads`] and
+    /// [`CalibrationInputs::progress`] is called after each model run. No file is exported,
+    /// therefore the destination, the chart and the objective options of the inputs are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs`: The calibration input data.
+    /// * `objective`: The metric to optimise.
+    /// * `settings`: The optimiser settings.
+    ///
+    /// returns: `Result<OptimiserOutputs, RunModelError>`
+    pub fn optimise(
+        inputs: CalibrationInputs,
+        objective: MetricType,
+        settings: OptimiserSettings,
+    ) -> Result<OptimiserOutputs, RunModelError> {
+        settings.validate().map_err(RunModelError::OptimiserError)?;
+        if inputs.catchment.is_empty() {
+            return Err(RunModelError::OptimiserError(
+                "at least one catchment must be provided".to_string(),
+            ));
+        }
+        if inputs.observed_runoff.len() != inputs.time.len() {
+            return Err(RunModelError::OptimiserError(
+                "the observed run-off and the time vector must have the same length".to_string(),
+            ));
+        }
+
+        match build_thread_pool(inputs.max_threads).map_err(RunModelError::OptimiserError)? {
+            Some(thread_pool) => thread_pool.install(|| Self::differential_evolution(&inputs, objective, &settings)),
+            None => Self::differential_evolution(&inputs, objective, &settings),
+        }
+    }
+
+    /// Run the differential-evolution loop for [`Calibration::optimise`].
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs`: The calibration input data.
+    /// * `objective`: The metric to optimise.
+    /// * `settings`: The validated optimiser settings.
+    ///
+    /// returns: `Result<OptimiserOutputs, RunModelError>`
+    fn differential_evolution(
+        inputs: &CalibrationInputs,
+        objective: MetricType,
+        settings: &OptimiserSettings,
+    ) -> Result<OptimiserOutputs, RunModelError> {
+        let population_size = settings.population_size.unwrap_or(30);
+        let generations = settings.generations.unwrap_or(50);
+        let mutation_factor = settings.mutation_factor.unwrap_or(0.8);
+        let crossover_rate = settings.crossover_rate.unwrap_or(0.9);
+
+        // The candidates contain the six parameters of each sub-catchment one after the other
+        let bounds: Vec<(f64, f64)> = inputs
+            .catchment
+            .iter()
+            .flat_map(|c| {
+                [
+                    (c.x1.lower_bound, c.x1.upper_bound),
+                    (c.x2.lower_bound, c.x2.upper_bound),
+                    (c.x3.lower_bound, c.x3.upper_bound),
+                    (c.x4.lower_bound, c.x4.upper_bound),
+                    (c.x5.lower_bound, c.x5.upper_bound),
+                    (c.x6.lower_bound, c.x6.upper_bound),
+                ]
+            })
+            .collect();
+        let total_parameters = bounds.len();
+
+        // The observed quantities are calculated once and shared by all candidates
+        let period = &inputs.calibration_period;
+        let start_index = inputs.time.iter().position(|t| *t == period.start);
+        let end_index = inputs.time.iter().position(|t| *t == period.end);
+        let (start_index, end_index) = match (start_index, end_index) {
+            (Some(start), Some(end)) => (start, end + 1),
+            _ => {
+                return Err(RunModelError::OptimiserError(
+                    "the calibration period is outside the time vector".to_string(),
+                ))
+            }
+        };
+        let context = CalibrationMetricContext::new_with_time(
+            &inputs.time[start_index..end_index],
+            &inputs.observed_runoff[start_index..end_index],
+            inputs.optional_metrics.clone(),
+        )?;
+
+        // A candidate that cannot be run or whose metric is missing is never selected
+        let completed = AtomicUsize::new(0);
+        let total_runs = population_size * (generations + 1);
+        let evaluate = |candidate: &Vec<f64>| {
+            let metrics = Self::run_candidate(inputs, candidate)
+                .and_then(|(_, run_off)| context.metrics(&run_off).map_err(|e| e.to_string()))
+                .ok();
+            if let Some(progress) = &inputs.progress {
+                progress(completed.fetch_add(1, Ordering::SeqCst) + 1, total_runs);
+            }
+            let score = metrics
+                .as_ref()
+                .map(|m| (m.get(objective).value - m.get(objective).ideal_value).abs())
+                .filter(|score| !score.is_nan())
+                .unwrap_or(f64::INFINITY);
+            (score, metrics)
+        };
+
+        // Generate the initial population
+        info!(
+            "Generating the initial population of {} parameter sets",
+            population_size
+        );
+        let samples: Vec<Array2<f64>> = inputs
+            .catchment
+            .iter()
+            .enumerate()
+            .map(|(uh_idx, data)| {
+                let seed = inputs.seed.map(|s| s.wrapping_add(uh_idx as u64));
+                Self::sample(data, population_size, inputs.sampling_strategy, seed)
+            })
+            .collect();
+        let mut population: Vec<Vec<f64>> = (0..population_size)
+            .map(|sample_idx| {
+                samples
+                    .iter()
+                    .flat_map(|sample| sample.row(sample_idx).to_vec())
+                    .collect()
+            })
+            .collect();
+        let mut evaluations: Vec<(f64, Option<CalibrationMetric>)> = population.par_iter().map(evaluate).collect();
+
+        let best_index = |evaluations: &[(f64, Option<CalibrationMetric>)]| {
+            evaluations
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.0.total_cmp(&b.0))
+                .map(|(index, _)| index)
+                .unwrap()
+        };
+        let best_value = |evaluations: &[(f64, Option<CalibrationMetric>)]| {
+            evaluations[best_index(evaluations)]
+                .1
+                .as_ref()
+                .map_or(f64::NAN, |m| m.get(objective).value)
+        };
+        let mut history = vec![best_value(&evaluations)];
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(
+            inputs
+                .seed
+                .unwrap_or_else(|| RandomState::new().build_hasher().finish()),
+        );
+        let mut uniform = move || (rng.next_u64() >> 11) as f64 / (1_u64 << 53) as f64;
+
+        for generation in 0..generations {
+            // Create the trial parameter sets by mutating three random members and crossing the
+            // mutant with the current member
+            let trials: Vec<Vec<f64>> = (0..population_size)
+                .map(|target| {
+                    let mut picked: Vec<usize> = Vec::with_capacity(3);
+                    while picked.len() < 3 {
+                        let index = ((uniform() * population_size as f64) as usize).min(population_size - 1);
+                        if index != target && !picked.contains(&index) {
+                            picked.push(index);
+                        }
+                    }
+                    let forced_index = ((uniform() * total_parameters as f64) as usize).min(total_parameters - 1);
+                    (0..total_parameters)
+                        .map(|p| match p == forced_index || uniform() < crossover_rate {
+                            true => {
+                                let value = population[picked[0]][p]
+                                    + mutation_factor * (population[picked[1]][p] - population[picked[2]][p]);
+                                value.clamp(bounds[p].0, bounds[p].1)
+                            }
+                            false => population[target][p],
+                        })
+                        .collect()
+                })
+                .collect();
+
+            // Keep the trial parameter sets that perform at least as well as the current ones
+            let trial_evaluations: Vec<(f64, Option<CalibrationMetric>)> = trials.par_iter().map(evaluate).collect();
+            for (index, (trial, evaluation)) in trials.into_iter().zip(trial_evaluations).enumerate() {
+                if evaluation.0 <= evaluations[index].0 {
+                    population[index] = trial;
+                    evaluations[index] = evaluation;
+                }
+            }
+
+            history.push(best_value(&evaluations));
+            debug!(
+                "Generation #{}: best {:?} = {}",
+                generation + 1,
+                objective,
+                history.last().unwrap()
+            );
+        }
+
+        let best = best_index(&evaluations);
+        let metrics = evaluations[best].1.take().ok_or_else(|| {
+            RunModelError::OptimiserError(format!(
+                "none of the parameter sets could be run or calculate {:?}",
+                objective
+            ))
+        })?;
+        let (time, run_off) = Self::run_candidate(inputs, &population[best]).map_err(RunModelError::OptimiserError)?;
+        info!(
+            "The best parameter set has {:?} = {}",
+            objective,
+            metrics.get(objective).value
+        );
+
+        Ok(OptimiserOutputs {
+            parameters: population[best]
+                .chunks(6)
+                .map(|p| CalibrationParameterValues {
+                    x1: p[0],
+                    x2: p[1],
+                    x3: p[2],
+                    x4: p[3],
+                    x5: p[4],
+                    x6: p[5],
+                })
+                .collect(),
+            metrics,
+            history,
+            time,
+            run_off,
+        })
+    }
+
+    /// Run the model with a candidate parameter set of the optimiser.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs`: The calibration input data.
+    /// * `candidate`: The six parameters of each sub-catchment one after the other.
+    ///
+    /// returns: `Result<(Vec<NaiveDate>, Vec<f64>), String>`
+    fn run_candidate(inputs: &CalibrationInputs, candidate: &[f64]) -> Result<(Vec<NaiveDate>, Vec<f64>), String> {
+        let mut catchment: Vec<CatchmentData> = vec![];
+        for (data, parameters) in inputs.catchment.iter().zip(candidate.chunks(6)) {
+            catchment.push(CatchmentData {
+                area: data.area,
+                x1: X1::new(parameters[0]).map_err(|e| e.to_string())?,
+                x2: X2::new(parameters[1]).map_err(|e| e.to_string())?,
+                x3: X3::new(parameters[2]).map_err(|e| e.to_string())?,
+                x4: X4::new(parameters[3]).map_err(|e| e.to_string())?,
+                x5: X5::new(parameters[4]).map_err(|e| e.to_string())?,
+                x6: X6::new(parameters[5]).map_err(|e| e.to_string())?,
+                store_levels: None,
+                uh_exponent: None,
+            });
+        }
+
+        let mut model = GR6JModel::new(GR6JModelInputs {
+            time: inputs.time,
+            precipitation: inputs.precipitation,
+            evapotranspiration: inputs.evapotranspiration,
+            catchment,
+            run_period: inputs.calibration_period,
+            warmup_period: inputs.warmup_period,
+            run_off_unit: inputs.run_off_unit.clone(),
+            logging: Some(false),
+            ..Default::default()
+        })
+        .map_err(|e| e.to_string())?;
+        let results = model.run().map_err(|e| e.to_string())?;
+        Ok((results.time, results.run_off))
+    }
+
+    /// Create a sample with combinations of model parameters.
     ///
     /// # Arguments
     ///
     /// * `data`: The data for one catchment.
     /// * `sample_size`: The sample size.
-    /// * `method`: The method to generate the random data.
+    /// * `strategy`: The strategy to generate the random data. When None this defaults to [`SamplingStrategy::LhsClassic`].
+    /// * `seed`: The seed of the random number generator. When None this defaults to a random seed.
     ///
     /// returns: `Array2<f64>`
     /// ```
-    fn sample(data: &CalibrationCatchmentData, sample_size: usize, method: Option<LhsKind>) -> Array2<f64> {
+    fn sample(
+        data: &CalibrationCatchmentData,
+        sample_size: usize,
+        strategy: Option<SamplingStrategy>,
+        seed: Option<u64>,
+    ) -> Array2<f64> {
         debug!("Generating {} samples", sample_size);
         let limits = arr2(&[
             [data.x1.lower_bound, data.x1.upper_bound],
@@ -299,8 +876,343 @@ impl<'a> Calibration<'a> {
             [data.x5.lower_bound, data.x5.upper_bound],
             [data.x6.lower_bound, data.x6.upper_bound],
         ]);
-        Lhs::new(&limits)
-            .kind(method.unwrap_or(LhsKind::Classic))
-            .sample(sample_size)
+        let kind = match strategy.unwrap_or_default() {
+            SamplingStrategy::LhsClassic => LhsKind::Classic,
+            SamplingStrategy::LhsCentered => LhsKind::Centered,
+            SamplingStrategy::LhsMaximin => LhsKind::Maximin,
+            SamplingStrategy::Random => {
+                let random = Random::new(&limits);
+                return match seed {
+                    Some(seed) => random.with_rng(Xoshiro256Plus::seed_from_u64(seed)).sample(sample_size),
+                    None => random.sample(sample_size),
+                };
+            }
+        };
+        let lhs = Lhs::new(&limits).kind(kind);
+        match seed {
+            Some(seed) => lhs.with_rng(Xoshiro256Plus::seed_from_u64(seed)).sample(sample_size),
+            None => lhs.sample(sample_size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calibration::Calibration;
+    use crate::inputs::{
+        CalibrationCatchmentData, CalibrationInputs, ModelPeriod, OptimiserSettings, ProgressFn, RunOffUnit,
+        SamplingStrategy,
+    };
+    use crate::io::{load_forcing_csv, ForcingCsvConfig};
+    use crate::metric::{MetricType, OptionalMetrics};
+    use crate::outputs::CalibrationOutputs;
+    use crate::parameter::{ParameterRange, X1Range, X2Range, X3Range, X4Range, X5Range, X6Range};
+    use chrono::{NaiveDate, TimeDelta};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::{env, fs};
+
+    #[test]
+    fn test_seeded_sample() {
+        let data = CalibrationCatchmentData {
+            area: 1.0,
+            x1: X1Range::default(),
+            x2: X2Range::default(),
+            x3: X3Range::default(),
+            x4: X4Range::default(),
+            x5: X5Range::default(),
+            x6: X6Range::default(),
+        };
+
+        let sample = Calibration::sample(&data, 50, None, Some(42));
+        assert_eq!(sample.dim(), (50, 6));
+        assert_eq!(sample, Calibration::sample(&data, 50, None, Some(42)));
+        assert_ne!(sample, Calibration::sample(&data, 50, None, Some(43)));
+    }
+
+    #[test]
+    fn test_maximin_sample_bounds() {
+        let data = CalibrationCatchmentData {
+            area: 1.0,
+            x1: X1Range::default(),
+            x2: X2Range::default(),
+            x3: X3Range::default(),
+            x4: X4Range::default(),
+            x5: X5Range::default(),
+            x6: X6Range::default(),
+        };
+        let bounds = [
+            (data.x1.lower_bound, data.x1.upper_bound),
+            (data.x2.lower_bound, data.x2.upper_bound),
+            (data.x3.lower_bound, data.x3.upper_bound),
+            (data.x4.lower_bound, data.x4.upper_bound),
+            (data.x5.lower_bound, data.x5.upper_bound),
+            (data.x6.lower_bound, data.x6.upper_bound),
+        ];
+
+        let sample = Calibration::sample(&data, 20, Some(SamplingStrategy::LhsMaximin), Some(1));
+        assert_eq!(sample.dim(), (20, 6));
+        for row in sample.rows() {
+            for (value, (lower, upper)) in row.iter().zip(bounds) {
+                assert!(
+                    *value >= lower && *value <= upper,
+                    "{value} is outside [{lower}, {upper}]"
+                );
+            }
+        }
+    }
+
+    /// Run a calibration with 10 samples on synthetic data.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the temporary folder where to save the results.
+    /// * `progress`: The progress callback.
+    /// * `max_threads`: The maximum number of threads.
+    /// * `optional_metrics`: The optional metrics to calculate.
+    ///
+    /// returns: `CalibrationOutputs`
+    fn run_calibration(
+        name: &str,
+        progress: Option<ProgressFn>,
+        max_threads: Option<usize>,
+        optional_metrics: Option<OptionalMetrics>,
+    ) -> CalibrationOutputs {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..365).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let precipitation: Vec<f64> = (0..time.len()).map(|i| (i % 5) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.0; time.len()];
+        let observed_runoff: Vec<f64> = (0..time.len()).map(|i| 0.5 + (i % 7) as f64 / 10.0).collect();
+        let destination = env::temp_dir().join(name);
+        fs::create_dir_all(&destination).unwrap();
+
+        let inputs = CalibrationInputs {
+            time: &time,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            observed_runoff: &observed_runoff,
+            catchment: vec![CalibrationCatchmentData {
+                area: 1.0,
+                x1: X1Range::default(),
+                x2: X2Range::default(),
+                x3: X3Range::default(),
+                x4: X4Range::default(),
+                x5: X5Range::default(),
+                x6: X6Range::default(),
+            }],
+            calibration_period: ModelPeriod::new(time[0], time[364]).unwrap(),
+            warmup_period: None,
+            destination: destination.clone(),
+            sample_size: Some(10),
+            run_off_unit: RunOffUnit::NoConversion,
+            generate_comparison_charts: false,
+            csv_precision: None,
+            skip_failures: Some(true),
+            objective: None,
+            objective_fn: None,
+            output_format: None,
+            seed: Some(1),
+            sampling_strategy: None,
+            progress,
+            max_threads,
+            chart_theme: None,
+            optional_metrics,
+        };
+        let mut calibration = Calibration::new(inputs).unwrap();
+        let result = calibration.run();
+        fs::remove_dir_all(&destination).unwrap();
+        result.unwrap()
+    }
+
+    #[test]
+    fn test_progress_callback() {
+        let calls: Arc<Mutex<Vec<(usize, usize)>>> = Arc::new(Mutex::new(vec![]));
+        let calls_clone = Arc::clone(&calls);
+        run_calibration(
+            "gr6j_test_progress_callback",
+            Some(Box::new(move |completed, total| {
+                calls_clone.lock().unwrap().push((completed, total))
+            })),
+            None,
+            None,
+        );
+
+        // the callback is called once per model with an increasing count
+        let mut calls = calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 10);
+        calls.sort();
+        assert_eq!(calls.last(), Some(&(10, 10)));
+        assert!(calls.iter().enumerate().all(|(i, c)| *c == (i + 1, 10)));
+    }
+
+    #[test]
+    fn test_max_threads() {
+        let expected = run_calibration("gr6j_test_max_threads_default", None, None, None);
+        let outputs = run_calibration("gr6j_test_max_threads_1", None, Some(1), None);
+        assert_eq!(outputs.run_off, expected.run_off);
+        assert_eq!(outputs.failed_samples, expected.failed_samples);
+    }
+
+    #[test]
+    fn test_optional_metrics() {
+        // the optional metrics are not calculated by default
+        let outputs = run_calibration("gr6j_test_optional_metrics_default", None, None, None);
+        assert!(outputs.metrics.kling_gupta2009().iter().all(|v| v.is_nan()));
+        assert!(outputs.metrics.kling_gupta2012().iter().all(|v| v.is_nan()));
+
+        let optional_metrics = OptionalMetrics {
+            kling_gupta2009: true,
+            ..OptionalMetrics::default()
+        };
+        let outputs = run_calibration("gr6j_test_optional_metrics", None, None, Some(optional_metrics));
+        let kge2009 = outputs.metrics.kling_gupta2009();
+        assert_eq!(kge2009.len(), outputs.metrics.len());
+        assert!(kge2009.iter().any(|v| v.is_finite()));
+        assert!(kge2009.iter().all(|v| *v <= 1.0 || v.is_nan()));
+        assert!(outputs.metrics.kling_gupta2012().iter().all(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn test_optimise() {
+        let dataset = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("test_data")
+            .join("airGR_L0123001_dataset.csv");
+        let forcing = load_forcing_csv(&dataset, ForcingCsvConfig::airgr()).unwrap();
+        let observed_runoff = forcing.observed_runoff.unwrap();
+        let inputs = CalibrationInputs {
+            time: &forcing.time,
+            precipitation: &forcing.precipitation,
+            evapotranspiration: &forcing.evapotranspiration,
+            observed_runoff: &observed_runoff,
+            catchment: vec![CalibrationCatchmentData {
+                area: 1.0,
+                x1: X1Range::default(),
+                x2: X2Range::default(),
+                x3: X3Range::default(),
+                x4: X4Range::default(),
+                x5: X5Range::default(),
+                x6: X6Range::default(),
+            }],
+            calibration_period: ModelPeriod::new(
+                NaiveDate::from_ymd_opt(1990, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(1991, 12, 31).unwrap(),
+            )
+            .unwrap(),
+            warmup_period: None,
+            destination: env::temp_dir(),
+            sample_size: None,
+            run_off_unit: RunOffUnit::NoConversion,
+            generate_comparison_charts: false,
+            csv_precision: None,
+            skip_failures: None,
+            objective: None,
+            objective_fn: None,
+            output_format: None,
+            seed: Some(3),
+            sampling_strategy: None,
+            progress: None,
+            max_threads: None,
+            chart_theme: None,
+            optional_metrics: None,
+        };
+        let settings = OptimiserSettings {
+            population_size: Some(12),
+            generations: Some(15),
+            mutation_factor: None,
+            crossover_rate: None,
+        };
+        let outputs = Calibration::optimise(inputs, MetricType::NashSutcliffe, settings).unwrap();
+
+        // the first value is the best NSE of the random initial population. The best parameter
+        // set is never lost, therefore the NSE can only improve
+        assert_eq!(outputs.history.len(), 16);
+        assert!(outputs.history.windows(2).all(|w| w[1] >= w[0]));
+        let nse = outputs.metrics.nash_sutcliffe.value;
+        assert_eq!(nse, *outputs.history.last().unwrap());
+        assert!(
+            nse > outputs.history[0],
+            "{} does not beat the random baseline {}",
+            nse,
+            outputs.history[0]
+        );
+        assert_eq!(outputs.parameters.len(), 1);
+        assert_eq!(outputs.run_off.len(), 730);
+    }
+
+    #[test]
+    fn test_optimiser_settings() {
+        let settings = OptimiserSettings {
+            population_size: Some(3),
+            ..OptimiserSettings::default()
+        };
+        assert!(settings.validate().is_err());
+        let settings = OptimiserSettings {
+            mutation_factor: Some(0.0),
+            ..OptimiserSettings::default()
+        };
+        assert!(settings.validate().is_err());
+        let settings = OptimiserSettings {
+            crossover_rate: Some(1.5),
+            ..OptimiserSettings::default()
+        };
+        assert!(settings.validate().is_err());
+        assert!(OptimiserSettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_warmup_period() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..730).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let forcing = vec![1.0; time.len()];
+        let inputs = |warmup_period: Option<ModelPeriod>| CalibrationInputs {
+            time: &time,
+            precipitation: &forcing,
+            evapotranspiration: &forcing,
+            observed_runoff: &forcing,
+            catchment: vec![CalibrationCatchmentData {
+                area: 1.0,
+                x1: X1Range::default(),
+                x2: X2Range::default(),
+                x3: X3Range::default(),
+                x4: X4Range::default(),
+                x5: X5Range::default(),
+                x6: X6Range::default(),
+            }],
+            calibration_period: ModelPeriod::new(time[400], time[729]).unwrap(),
+            warmup_period,
+            destination: env::temp_dir(),
+            sample_size: Some(5),
+            run_off_unit: RunOffUnit::NoConversion,
+            generate_comparison_charts: false,
+            csv_precision: None,
+            skip_failures: None,
+            objective: None,
+            objective_fn: None,
+            output_format: None,
+            seed: None,
+            sampling_strategy: None,
+            progress: None,
+            max_threads: None,
+            chart_theme: None,
+            optional_metrics: None,
+        };
+
+        // the period is passed to all the models
+        let warmup_period = ModelPeriod::new(time[100], time[399]).unwrap();
+        let calibration = Calibration::new(inputs(Some(warmup_period))).unwrap();
+        assert_eq!(calibration.run_inputs.len(), 5);
+        assert!(calibration
+            .run_inputs
+            .iter()
+            .all(|i| i.warmup_period == Some(warmup_period)));
+        let calibration = Calibration::new(inputs(None)).unwrap();
+        assert!(calibration.run_inputs.iter().all(|i| i.warmup_period.is_none()));
+
+        // the period can end before the day preceding the calibration period
+        assert!(Calibration::new(inputs(Some(ModelPeriod::new(time[100], time[398]).unwrap()))).is_ok());
+
+        // the period must end before the calibration period
+        assert!(Calibration::new(inputs(Some(ModelPeriod::new(time[100], time[400]).unwrap()))).is_err());
     }
 }
@@ -1,8 +1,11 @@
-use crate::inputs::RunOffUnit;
+use crate::inputs::{ChartTheme, ImageFormat, RunOffUnit};
+use crate::metric::MetricType;
 use crate::model::GR6JModel;
 use crate::outputs::{CalibrationMetricVector, GR6JOutputs};
+#[cfg(feature = "pdf")]
+use crate::pdf::PdfPage;
 use crate::utils::{series_max, series_min, Fdc};
-use chrono::{Datelike, NaiveDate};
+use chrono::{Datelike, Month, NaiveDate};
 use plotters::coord::ranged1d::ValueFormatter;
 use plotters::coord::types::{RangedCoordf64, Yearly};
 use plotters::coord::Shift;
@@ -25,18 +28,101 @@ type TimeSeriesDataOutput<'a, DB> = Result<FlowChartContext<'a, DB>, Box<dyn std
 /// The return type of a chart function
 type ChartResult = Result<(), Box<dyn std::error::Error>>;
 
+/// The size in pixels of the calibration charts.
+pub(crate) const CALIBRATION_CHART_SIZE: (u32, u32) = (1800, 1200);
+
+/// The size in pixels of the cumulative flow chart.
+const CUMULATIVE_CHART_SIZE: (u32, u32) = (1800, 800);
+
+/// The size in pixels of the observed vs. simulated scatter chart.
+const SCATTER_CHART_SIZE: (u32, u32) = (1000, 1000);
+
+/// The size in pixels of the summary chart.
+const SUMMARY_CHART_SIZE: (u32, u32) = (2100 / 2, 2970 / 2);
+
+/// Get the size in pixels of the FDC chart.
+///
+/// # Arguments
+///
+/// * `log_panel`: Whether the chart contains the panel with the logarithmic y-axis.
+///
+/// returns: `(u32, u32)`
+fn fdc_chart_size(log_panel: bool) -> (u32, u32) {
+    (1500 / 2, if log_panel { 1500 / 2 } else { 1500 / 4 })
+}
+
+/// Render a chart as a page of a PDF document instead of a file.
+///
+/// # Arguments
+///
+/// * `size`: The chart width and height in pixels.
+/// * `draw`: The function drawing the chart on the root drawing area.
+///
+/// returns: `Result<PdfPage, Box<dyn Error>>`
+#[cfg(feature = "pdf")]
+pub(crate) fn render_chart_to_pdf_page<F>(size: (u32, u32), draw: F) -> Result<PdfPage, Box<dyn std::error::Error>>
+where
+    F: FnOnce(DrawingArea<BitMapBackend<'_>, Shift>) -> ChartResult,
+{
+    let buffer = render_chart_to_rgb(size, draw)?;
+    Ok(PdfPage::new(size.0, size.1, &buffer)?)
+}
+
+/// Render a chart as a PNG image in memory instead of a file.
+///
+/// # Arguments
+///
+/// * `size`: The chart width and height in pixels.
+/// * `draw`: The function drawing the chart on the root drawing area.
+///
+/// returns: `Result<Vec<u8>, Box<dyn Error>>`
+fn render_chart_to_png<F>(size: (u32, u32), draw: F) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+where
+    F: FnOnce(DrawingArea<BitMapBackend<'_>, Shift>) -> ChartResult,
+{
+    let buffer = render_chart_to_rgb(size, draw)?;
+    let mut png_buffer = vec![];
+    let mut encoder = png::Encoder::new(&mut png_buffer, size.0, size.1);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&buffer)?;
+    writer.finish()?;
+    Ok(png_buffer)
+}
+
+/// Render a chart in memory as RGB pixels.
+///
+/// # Arguments
+///
+/// * `size`: The chart width and height in pixels.
+/// * `draw`: The function drawing the chart on the root drawing area.
+///
+/// returns: `Result<Vec<u8>, Box<dyn Error>>`
+fn render_chart_to_rgb<F>(size: (u32, u32), draw: F) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+where
+    F: FnOnce(DrawingArea<BitMapBackend<'_>, Shift>) -> ChartResult,
+{
+    let mut buffer = vec![0; (size.0 * size.1 * 3) as usize];
+    {
+        let root_area = BitMapBackend::with_buffer(&mut buffer, size).into_drawing_area();
+        draw(root_area)?;
+    }
+    Ok(buffer)
+}
+
 /// The line style for the simulated data.
-fn sim_style() -> ShapeStyle {
+fn sim_style(theme: &ChartTheme) -> ShapeStyle {
     ShapeStyle {
-        color: Palette99::pick(10).to_rgba(),
+        color: theme.simulated.to_rgba(),
         filled: false,
         stroke_width: 1,
     }
 }
 /// The line style for the observed data.
-fn obs_style() -> ShapeStyle {
+fn obs_style(theme: &ChartTheme) -> ShapeStyle {
     ShapeStyle {
-        color: Palette99::pick(12).to_rgba(),
+        color: theme.observed.to_rgba(),
         filled: false,
         stroke_width: 1,
     }
@@ -75,9 +161,59 @@ where
 /// * `model`: The GR6JModel struct.
 /// * `results`: The GR6JOutputs struct.
 /// * `destination`: The folder where to save the chart file.
+/// * `format`: The file format of the chart.
 ///
 /// returns: `ChartResult`
-pub(crate) fn generate_summary_chart(model: &GR6JModel, results: &GR6JOutputs, destination: &Path) -> ChartResult {
+pub(crate) fn generate_summary_chart(
+    model: &GR6JModel,
+    results: &GR6JOutputs,
+    destination: &Path,
+    format: ImageFormat,
+) -> ChartResult {
+    let full_file = destination.join(format!("Summary.{}", format.extension()));
+    let size = SUMMARY_CHART_SIZE;
+    match format {
+        ImageFormat::Png => {
+            draw_summary_chart(model, results, BitMapBackend::new(&full_file, size).into_drawing_area())
+        }
+        ImageFormat::Svg => draw_summary_chart(model, results, SVGBackend::new(&full_file, size).into_drawing_area()),
+    }
+}
+
+/// Render the summary chart (see [`generate_summary_chart`]) as a PNG image in memory.
+///
+/// # Arguments
+///
+/// * `model`: The GR6JModel struct.
+/// * `results`: The GR6JOutputs struct.
+///
+/// returns: `Result<Vec<u8>, Box<dyn Error>>`
+pub(crate) fn summary_chart_png(
+    model: &GR6JModel,
+    results: &GR6JOutputs,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    render_chart_to_png(SUMMARY_CHART_SIZE, |root_area| {
+        draw_summary_chart(model, results, root_area)
+    })
+}
+
+/// Draw the summary chart on a drawing area (see [`generate_summary_chart`]).
+///
+/// # Arguments
+///
+/// * `model`: The GR6JModel struct.
+/// * `results`: The GR6JOutputs struct.
+/// * `root_area`: The drawing area.
+///
+/// returns: `ChartResult`
+fn draw_summary_chart<DB: DrawingBackend>(
+    model: &GR6JModel,
+    results: &GR6JOutputs,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
     let time: Vec<NaiveDate> = results.time.clone();
     let all_series = [
         model.precipitation.clone(),
@@ -89,13 +225,12 @@ pub(crate) fn generate_summary_chart(model: &GR6JModel, results: &GR6JOutputs, d
     let axis_labels = ["Rainfall (mm)", "Evapotranspiration (mm)", &run_off_label];
     let labels = ["Rainfall", "Evapotranspiration", "Simulated"];
 
-    let full_file = destination.join("Summary.png");
-    let root_area = BitMapBackend::new(&full_file, (2100 / 2, 2970 / 2)).into_drawing_area();
     root_area.fill(&WHITE)?;
 
     let root_area = root_area.titled("Inputs & simulated run-off", (FONT, 30))?;
     let panels = root_area.split_evenly((3, 1));
-    let colours = [Palette99::pick(10), Palette99::pick(5), Palette99::pick(4)];
+    let theme = &model.chart_theme;
+    let colours = [theme.rainfall, theme.evapotranspiration, theme.simulated];
 
     let t_range = (*time.first().unwrap()..*time.last().unwrap()).yearly();
     for (idx, panel) in panels.iter().enumerate() {
@@ -122,9 +257,11 @@ pub(crate) fn generate_summary_chart(model: &GR6JModel, results: &GR6JOutputs, d
             colour: colours[idx].to_rgba(),
         })?;
 
-        if idx == 2 && model.observed.is_some() {
-            add_obs_flow_to_context(&mut cc, &time, model.observed.as_ref().unwrap())?;
-            render_legend_box(&mut cc)?;
+        if idx == 2 {
+            if let Some(observed) = &model.observed {
+                add_obs_flow_to_context(&mut cc, &time, observed, theme)?;
+                render_legend_box(&mut cc)?;
+            }
         }
     }
 
@@ -193,22 +330,25 @@ where
 /// * `context`: The chart context.
 /// * `time`: The time vector.
 /// * `observed`: The observed flow vector.
+/// * `theme`: The colours of the chart series.
 ///
 /// returns: `Result<(), Box<dyn Error>>`
 fn add_obs_flow_to_context<DB: DrawingBackend>(
     context: &mut FlowChartContext<DB>,
     time: &[NaiveDate],
     observed: &[f64],
+    theme: &ChartTheme,
 ) -> ChartResult
 where
     DB::ErrorType: 'static,
 {
+    let colour = theme.observed;
     context
         .draw_series(LineSeries::new(
             time.iter().zip(observed).map(|(t, p)| (*t, *p)),
-            obs_style(),
+            obs_style(theme),
         ))?
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK))
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], colour))
         .label("Observed");
 
     Ok(())
@@ -223,6 +363,7 @@ where
 /// * `flow_unit`: The unit to show on the flow axis.
 /// * `observed`: The observed FDC.
 /// * `y_range`: The range to use on the y-axis. Use a log scale to plot the log FDC.
+/// * `theme`: The colours of the chart series.
 ///
 /// returns: `Result<(), Box<dyn Error>>`
 fn render_fdc_panel<DB, Y>(
@@ -231,6 +372,7 @@ fn render_fdc_panel<DB, Y>(
     flow_unit: &RunOffUnit,
     observed: Option<Fdc>,
     y_range: Y,
+    theme: &ChartTheme,
 ) -> ChartResult
 where
     DB: DrawingBackend,
@@ -253,23 +395,24 @@ where
         .label_style(LABEL_STYLE)
         .draw()?;
 
+    let (sim_colour, obs_colour) = (theme.simulated, theme.observed);
     cc.draw_series(LineSeries::new(
         simulated_fdc
             .exceedence
             .iter()
             .zip(simulated_fdc.sorted_run_off)
             .map(|(t, p)| (*t, p)),
-        sim_style(),
+        sim_style(theme),
     ))?
-    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], sim_style().color.to_rgba()))
+    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], sim_colour))
     .label("Simulated");
 
     if let Some(fdc) = observed {
         cc.draw_series(LineSeries::new(
             fdc.exceedence.iter().zip(fdc.sorted_run_off).map(|(t, p)| (*t, p)),
-            obs_style(),
+            obs_style(theme),
         ))?
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], obs_style().color))
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], obs_colour))
         .label("Observed");
         render_legend_box(&mut cc)?;
     }
@@ -277,6 +420,68 @@ where
     Ok(())
 }
 
+/// Render a panel with the residuals of the simulated flow (simulated - observed) around zero.
+///
+/// # Arguments
+///
+/// * `panel`: The panel reference.
+/// * `time`: The time vector.
+/// * `residuals`: The residuals.
+/// * `flow_unit`: The unit to show on the residual axis.
+/// * `theme`: The colours of the chart series.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+fn render_residual_panel<DB: DrawingBackend>(
+    panel: &DrawingArea<DB, Shift>,
+    time: &[NaiveDate],
+    residuals: &[f64],
+    flow_unit: &RunOffUnit,
+    theme: &ChartTheme,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    // centre the y-axis on zero. Missing residuals are not drawn
+    let y_max = residuals
+        .iter()
+        .filter(|r| r.is_finite())
+        .fold(0.0_f64, |max, r| max.max(r.abs()));
+    let y_max = if y_max > 0.0 { y_max } else { 1.0 };
+
+    let t_range = (*time.first().unwrap()..*time.last().unwrap()).yearly();
+    let mut cc = ChartBuilder::on(panel)
+        .x_label_area_size(X_LABEL_AREA_SIZE)
+        .y_label_area_size(Y_LABEL_AREA_SIZE)
+        .set_label_area_size(LabelAreaPosition::Left, 90)
+        .margin_top(5)
+        .margin_left(20)
+        .margin_right(30)
+        .build_cartesian_2d(t_range, -y_max..y_max)?;
+
+    cc.configure_mesh()
+        .y_desc(format!("Residual ({})", flow_unit.unit_label()))
+        .axis_desc_style(AXIS_STYLE)
+        .label_style(LABEL_STYLE)
+        .x_label_formatter(&|v| v.year().to_string())
+        .draw()?;
+
+    cc.draw_series(LineSeries::new(
+        [(*time.first().unwrap(), 0.0), (*time.last().unwrap(), 0.0)],
+        BLACK,
+    ))?;
+    let points: Vec<(NaiveDate, f64)> = time.iter().copied().zip(residuals.iter().copied()).collect();
+    for segment in points.split(|(_, r)| !r.is_finite()) {
+        cc.draw_series(LineSeries::new(segment.iter().copied(), sim_style(theme)))?;
+    }
+    let colour = theme.simulated;
+    cc.draw_series(std::iter::empty::<PathElement<(NaiveDate, f64)>>())?
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], colour))
+        .label("Simulated - observed");
+    render_legend_box(&mut cc)?;
+
+    Ok(())
+}
+
 /// Generate a chart with two panels; the top panel containing the simulated and observed FDCs
 /// using a normal scale for the y-axis, the second panel with the same FDCs but the y-axis is
 /// logarithmic.
@@ -286,21 +491,85 @@ where
 /// * `model`: The GR6JModel struct.
 /// * `simulated`: The FDC struct for the FDC of the simulated run-off.
 /// * `observed`: The FDC struct for the FDC of the observed run-off.
+/// * `log_panel`: Whether to draw the second panel with the logarithmic y-axis.
 /// * `destination`: The folder where to save the chart file.
+/// * `format`: The file format of the chart.
 ///
 /// returns: `ChartResult`
 pub(crate) fn save_fdc_chart(
     model: &GR6JModel,
     simulated: Fdc,
     observed: Option<Fdc>,
+    log_panel: bool,
     destination: &Path,
+    format: ImageFormat,
 ) -> ChartResult {
-    let full_file = destination.join("FDC.png");
-    let root_area = BitMapBackend::new(&full_file, (1500 / 2, 1500 / 2)).into_drawing_area();
+    let full_file = destination.join(format!("FDC.{}", format.extension()));
+    let size = fdc_chart_size(log_panel);
+    match format {
+        ImageFormat::Png => draw_fdc_chart(
+            model,
+            simulated,
+            observed,
+            log_panel,
+            BitMapBackend::new(&full_file, size).into_drawing_area(),
+        ),
+        ImageFormat::Svg => draw_fdc_chart(
+            model,
+            simulated,
+            observed,
+            log_panel,
+            SVGBackend::new(&full_file, size).into_drawing_area(),
+        ),
+    }
+}
+
+/// Render the FDC chart (see [`save_fdc_chart`]) as a PNG image in memory.
+///
+/// # Arguments
+///
+/// * `model`: The GR6JModel struct.
+/// * `simulated`: The FDC struct for the FDC of the simulated run-off.
+/// * `observed`: The FDC struct for the FDC of the observed run-off.
+/// * `log_panel`: Whether to draw the second panel with the logarithmic y-axis.
+///
+/// returns: `Result<Vec<u8>, Box<dyn Error>>`
+pub(crate) fn fdc_chart_png(
+    model: &GR6JModel,
+    simulated: Fdc,
+    observed: Option<Fdc>,
+    log_panel: bool,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    render_chart_to_png(fdc_chart_size(log_panel), |root_area| {
+        draw_fdc_chart(model, simulated, observed, log_panel, root_area)
+    })
+}
+
+/// Draw the FDC chart on a drawing area (see [`save_fdc_chart`]).
+///
+/// # Arguments
+///
+/// * `model`: The GR6JModel struct.
+/// * `simulated`: The FDC struct for the FDC of the simulated run-off.
+/// * `observed`: The FDC struct for the FDC of the observed run-off.
+/// * `log_panel`: Whether to draw the second panel with the logarithmic y-axis.
+/// * `root_area`: The drawing area.
+///
+/// returns: `ChartResult`
+fn draw_fdc_chart<DB: DrawingBackend>(
+    model: &GR6JModel,
+    simulated: Fdc,
+    observed: Option<Fdc>,
+    log_panel: bool,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
     root_area.fill(&WHITE)?;
 
     let root_area = root_area.titled("Flow duration curve", (FONT, 30))?;
-    let panels = root_area.split_evenly((2, 1));
+    let panels = root_area.split_evenly((if log_panel { 2 } else { 1 }, 1));
 
     let has_observed = observed.as_ref().is_some();
     let mut q_max = series_max(&simulated.sorted_run_off);
@@ -312,21 +581,25 @@ pub(crate) fn save_fdc_chart(
         q_max = q_max.ceil();
     };
 
-    render_fdc_panel::<BitMapBackend<'_>, RangedCoordf64>(
+    render_fdc_panel::<DB, RangedCoordf64>(
         &panels[0],
         simulated.clone(),
         &model.run_off_unit,
         observed.clone(),
         (0.0..q_max).into(),
+        &model.chart_theme,
     )?;
 
-    render_fdc_panel::<BitMapBackend<'_>, LogCoord<f64>>(
-        &panels[1],
-        simulated,
-        &model.run_off_unit,
-        observed,
-        (0.0..q_max).log_scale().into(),
-    )?;
+    if log_panel {
+        render_fdc_panel::<DB, LogCoord<f64>>(
+            &panels[1],
+            simulated,
+            &model.run_off_unit,
+            observed,
+            (0.0..q_max).log_scale().into(),
+            &model.chart_theme,
+        )?;
+    }
 
     Ok(())
 }
@@ -340,6 +613,7 @@ pub(crate) fn save_fdc_chart(
 /// * `panel`: The drawing area reference.
 /// * `metric_name`: The name of the metric.
 /// * `metric_ideal_value`: The ideal value the metric should reach.
+/// * `theme`: The colours of the chart series.
 ///
 /// returns: `Result<(), Box<dyn Error>>`
 fn render_metric_vs_parameter_panel<DB: DrawingBackend>(
@@ -348,6 +622,7 @@ fn render_metric_vs_parameter_panel<DB: DrawingBackend>(
     panel: &DrawingArea<DB, Shift>,
     metric_name: &str,
     metric_ideal_value: f64,
+    theme: &ChartTheme,
 ) -> ChartResult
 where
     DB::ErrorType: 'static,
@@ -381,7 +656,7 @@ where
         6,
         6,
         ShapeStyle {
-            color: Palette99::pick(10).to_rgba(),
+            color: theme.ideal_value.to_rgba(),
             filled: false,
             stroke_width: 2,
         },
@@ -390,6 +665,35 @@ where
     Ok(())
 }
 
+/// Get the metric values to plot in each panel of the metric vs parameter chart. The 2009 and
+/// 2012 Kling-Gupta efficiencies are only plotted when they were calculated.
+///
+/// # Arguments
+///
+/// * `metrics`: The vector with the metric values.
+///
+/// returns: `Vec<(Vec<f64>, MetricType)>`
+fn metric_panels(metrics: &CalibrationMetricVector) -> Vec<(Vec<f64>, MetricType)> {
+    let mut panels = vec![
+        (metrics.nash_sutcliffe(), MetricType::NashSutcliffe),
+        (metrics.log_nash_sutcliffe(), MetricType::LogNashSutcliffe),
+        (
+            metrics.non_parametric_kling_gupta(),
+            MetricType::NonParametricKlingGupta,
+        ),
+        (metrics.rmse(), MetricType::Rmse),
+        (metrics.volume_error(), MetricType::VolumeError),
+    ];
+    let first_metrics = metrics.0.first().unwrap();
+    if first_metrics.is_calculated(MetricType::KlingGupta2009) {
+        panels.push((metrics.kling_gupta2009(), MetricType::KlingGupta2009));
+    }
+    if first_metrics.is_calculated(MetricType::KlingGupta2012) {
+        panels.push((metrics.kling_gupta2012(), MetricType::KlingGupta2012));
+    }
+    panels
+}
+
 /// Plot a chart for a parameter catchment (or sub-catchment) to compare the parameter values
 /// against all calculated metrics.
 ///
@@ -398,7 +702,9 @@ where
 /// * `x`: The vector with the parameter values.
 /// * `metrics`: The vector with the metric values.
 /// * `title`: The chart title.
-/// * `destination`: The folder where to save the chart file.
+/// * `destination`: The path of the chart file.
+/// * `format`: The file format of the chart.
+/// * `theme`: The colours of the chart series.
 ///
 /// returns: `Result<(), Box<dyn Error>>`
 pub(crate) fn save_metric_vs_parameter_chart(
@@ -406,91 +712,147 @@ pub(crate) fn save_metric_vs_parameter_chart(
     metrics: &CalibrationMetricVector,
     title: String,
     destination: &PathBuf,
+    format: ImageFormat,
+    theme: &ChartTheme,
 ) -> ChartResult {
-    let first_metrics = &metrics.0.first().unwrap();
+    match format {
+        ImageFormat::Png => draw_metric_vs_parameter_chart(
+            x,
+            metrics,
+            title,
+            theme,
+            BitMapBackend::new(destination, CALIBRATION_CHART_SIZE).into_drawing_area(),
+        ),
+        ImageFormat::Svg => draw_metric_vs_parameter_chart(
+            x,
+            metrics,
+            title,
+            theme,
+            SVGBackend::new(destination, CALIBRATION_CHART_SIZE).into_drawing_area(),
+        ),
+    }
+}
+
+/// Draw the metric vs parameter chart on a drawing area (see [`save_metric_vs_parameter_chart`]).
+///
+/// # Arguments
+///
+/// * `x`: The vector with the parameter values.
+/// * `metrics`: The vector with the metric values.
+/// * `title`: The chart title.
+/// * `theme`: The colours of the chart series.
+/// * `root_area`: The drawing area.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub(crate) fn draw_metric_vs_parameter_chart<DB: DrawingBackend>(
+    x: &[f64],
+    metrics: &CalibrationMetricVector,
+    title: String,
+    theme: &ChartTheme,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    let first_metrics = metrics.0.first().unwrap();
+    let panels_data = metric_panels(metrics);
 
-    let root_area = BitMapBackend::new(destination, (1800, 1200)).into_drawing_area();
     root_area.fill(&WHITE)?;
     let root_area = root_area.titled(&title, (FONT, 30))?;
-    let panels = root_area.split_evenly((2, 3));
-
-    // First panel
-    render_metric_vs_parameter_panel(
-        x,
-        &metrics.nash_sutcliffe(),
-        &panels[0],
-        &first_metrics.nash_sutcliffe.name,
-        first_metrics.nash_sutcliffe.ideal_value,
-    )?;
+    let panels = root_area.split_evenly((panels_data.len().div_ceil(3), 3));
 
-    // Second panel
-    render_metric_vs_parameter_panel(
-        x,
-        &metrics.log_nash_sutcliffe(),
-        &panels[1],
-        &first_metrics.log_nash_sutcliffe.name,
-        first_metrics.log_nash_sutcliffe.ideal_value,
-    )?;
-
-    // Third panel
-    render_metric_vs_parameter_panel(
-        x,
-        &metrics.non_parametric_kling_gupta(),
-        &panels[2],
-        &first_metrics.non_parametric_kling_gupta.name,
-        first_metrics.non_parametric_kling_gupta.ideal_value,
-    )?;
-
-    // Fourth panel
-    render_metric_vs_parameter_panel(
-        x,
-        &metrics.rmse(),
-        &panels[3],
-        &first_metrics.rmse.name,
-        first_metrics.rmse.ideal_value,
-    )?;
-
-    // Fifth panel
-    render_metric_vs_parameter_panel(
-        x,
-        &metrics.volume_error(),
-        &panels[4],
-        &first_metrics.volume_error.name,
-        first_metrics.volume_error.ideal_value,
-    )?;
+    for ((values, metric_type), panel) in panels_data.iter().zip(panels.iter()) {
+        let metric = first_metrics.get(*metric_type);
+        render_metric_vs_parameter_panel(x, values, panel, &metric.name, metric.ideal_value, theme)?;
+    }
 
     Ok(())
 }
 
 /// Plot a chart to compare the observed vs simulated flow and flow duration curve for one model.
+/// When the observed flow is available, a third panel shows the residuals of the simulated flow.
 ///
 /// # Arguments
 ///
 /// * `time`: The time vector.
 /// * `simulated`: The simulated flow time-series.
-/// * `observed`: The observed flow time-series.
+/// * `observed`: The observed flow time-series. When None only the simulated flow is drawn.
 /// * `title`: The chart title.
-/// * `destination`: The folder where to save the chart file.
+/// * `destination`: The path of the chart file.
 /// * `flow_unit`: The unit of measurement for the flow.
+/// * `format`: The file format of the chart.
+/// * `theme`: The colours of the chart series.
 ///
 /// returns: `Result<(), Box<dyn Error>>`
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn save_flow_comparison_chart(
     time: &[NaiveDate],
     simulated: &[f64],
-    observed: &[f64],
+    observed: Option<&[f64]>,
     title: String,
     destination: &PathBuf,
     flow_unit: &RunOffUnit,
+    format: ImageFormat,
+    theme: &ChartTheme,
 ) -> ChartResult {
-    let root_area = BitMapBackend::new(destination, (1800, 1200)).into_drawing_area();
+    match format {
+        ImageFormat::Png => draw_flow_comparison_chart(
+            time,
+            simulated,
+            observed,
+            title,
+            flow_unit,
+            theme,
+            BitMapBackend::new(destination, CALIBRATION_CHART_SIZE).into_drawing_area(),
+        ),
+        ImageFormat::Svg => draw_flow_comparison_chart(
+            time,
+            simulated,
+            observed,
+            title,
+            flow_unit,
+            theme,
+            SVGBackend::new(destination, CALIBRATION_CHART_SIZE).into_drawing_area(),
+        ),
+    }
+}
+
+/// Draw the comparison of the observed vs simulated flow on a drawing area (see
+/// [`save_flow_comparison_chart`]).
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `simulated`: The simulated flow time-series.
+/// * `observed`: The observed flow time-series. When None only the simulated flow is drawn.
+/// * `title`: The chart title.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `theme`: The colours of the chart series.
+/// * `root_area`: The drawing area.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub(crate) fn draw_flow_comparison_chart<DB: DrawingBackend>(
+    time: &[NaiveDate],
+    simulated: &[f64],
+    observed: Option<&[f64]>,
+    title: String,
+    flow_unit: &RunOffUnit,
+    theme: &ChartTheme,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
     root_area.fill(&WHITE)?;
     let root_area = root_area.titled(&title, (FONT, 30))?;
-    let panels = root_area.split_evenly((2, 1));
+    let panels = root_area.split_evenly((if observed.is_some() { 3 } else { 2 }, 1));
 
     // Flow panel
     let t_range = (*time.first().unwrap()..*time.last().unwrap()).yearly();
     let mut y_max = series_max(simulated);
-    y_max = y_max.max(series_max(observed));
+    if let Some(observed) = observed {
+        y_max = y_max.max(series_max(observed));
+    }
 
     let axis_label = format!("Run-off ({})", flow_unit.unit_label());
     let mut cc = render_time_series_panel(TimeSeriesData {
@@ -501,19 +863,810 @@ pub(crate) fn save_flow_comparison_chart(
         y_max,
         axis_label: &axis_label,
         series_name: "Simulated",
-        colour: sim_style().color.to_rgba(),
+        colour: theme.simulated.to_rgba(),
     })?;
-    add_obs_flow_to_context(&mut cc, time, observed)?;
+    if let Some(observed) = observed {
+        add_obs_flow_to_context(&mut cc, time, observed, theme)?;
+    }
     render_legend_box(&mut cc)?;
 
     // Panel with log FDC
-    render_fdc_panel::<BitMapBackend<'_>, LogCoord<f64>>(
+    render_fdc_panel::<DB, LogCoord<f64>>(
         &panels[1],
         Fdc::new(simulated),
         flow_unit,
-        Some(Fdc::new(observed)),
+        observed.map(Fdc::new),
         (0.0..y_max).log_scale().into(),
+        theme,
     )?;
 
+    // Panel with the residuals
+    if let Some(observed) = observed {
+        let residuals: Vec<f64> = simulated.iter().zip(observed).map(|(s, o)| s - o).collect();
+        render_residual_panel(&panels[2], time, &residuals, flow_unit, theme)?;
+    }
+
     Ok(())
 }
+
+/// Plot a chart comparing the cumulative simulated and observed run-off (double-mass chart).
+/// A systematic divergence of the two lines highlights a bias in the simulated volume, whereas
+/// a temporary one points to a timing error. The time steps where either of the two flows is
+/// missing do not add to the cumulative values.
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `simulated`: The simulated flow time-series.
+/// * `observed`: The observed flow time-series.
+/// * `title`: The chart title.
+/// * `destination`: The path of the chart file.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `format`: The file format of the chart.
+/// * `theme`: The colours of the chart series.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn save_cumulative_flow_chart(
+    time: &[NaiveDate],
+    simulated: &[f64],
+    observed: &[f64],
+    title: String,
+    destination: &PathBuf,
+    flow_unit: &RunOffUnit,
+    format: ImageFormat,
+    theme: &ChartTheme,
+) -> ChartResult {
+    match format {
+        ImageFormat::Png => draw_cumulative_flow_chart(
+            time,
+            simulated,
+            observed,
+            title,
+            flow_unit,
+            theme,
+            BitMapBackend::new(destination, CUMULATIVE_CHART_SIZE).into_drawing_area(),
+        ),
+        ImageFormat::Svg => draw_cumulative_flow_chart(
+            time,
+            simulated,
+            observed,
+            title,
+            flow_unit,
+            theme,
+            SVGBackend::new(destination, CUMULATIVE_CHART_SIZE).into_drawing_area(),
+        ),
+    }
+}
+
+/// Draw the cumulative simulated and observed run-off on a drawing area (see
+/// [`save_cumulative_flow_chart`]).
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `simulated`: The simulated flow time-series.
+/// * `observed`: The observed flow time-series.
+/// * `title`: The chart title.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `theme`: The colours of the chart series.
+/// * `root_area`: The drawing area.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+fn draw_cumulative_flow_chart<DB: DrawingBackend>(
+    time: &[NaiveDate],
+    simulated: &[f64],
+    observed: &[f64],
+    title: String,
+    flow_unit: &RunOffUnit,
+    theme: &ChartTheme,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    let mut cumulative_sim = Vec::with_capacity(simulated.len());
+    let mut cumulative_obs = Vec::with_capacity(observed.len());
+    let (mut total_sim, mut total_obs) = (0.0, 0.0);
+    for (sim, obs) in simulated.iter().zip(observed) {
+        if sim.is_finite() && obs.is_finite() {
+            total_sim += sim;
+            total_obs += obs;
+        }
+        cumulative_sim.push(total_sim);
+        cumulative_obs.push(total_obs);
+    }
+
+    root_area.fill(&WHITE)?;
+    let root_area = root_area.titled(&title, (FONT, 30))?;
+
+    let t_range = (*time.first().unwrap()..*time.last().unwrap()).yearly();
+    let y_max = total_sim.max(total_obs);
+    let axis_label = format!("Cumulative run-off ({})", flow_unit.unit_label());
+    let mut cc = render_time_series_panel(TimeSeriesData {
+        panel: &root_area,
+        time,
+        series: &cumulative_sim,
+        t_range: &t_range,
+        y_max,
+        axis_label: &axis_label,
+        series_name: "Simulated",
+        colour: theme.simulated.to_rgba(),
+    })?;
+    add_obs_flow_to_context(&mut cc, time, &cumulative_obs, theme)?;
+    render_legend_box(&mut cc)?;
+
+    Ok(())
+}
+
+/// Plot the simulated run-off against the observed run-off with a dashed 1:1 reference line. The
+/// closer the points are to the line, the better the simulation. The pairs where either of the
+/// two flows is missing are not plotted.
+///
+/// # Arguments
+///
+/// * `observed`: The observed flow time-series.
+/// * `simulated`: The simulated flow time-series.
+/// * `title`: The chart title.
+/// * `destination`: The path of the chart file.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `format`: The file format of the chart.
+/// * `theme`: The colours of the chart series.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub(crate) fn save_scatter_chart(
+    observed: &[f64],
+    simulated: &[f64],
+    title: String,
+    destination: &PathBuf,
+    flow_unit: &RunOffUnit,
+    format: ImageFormat,
+    theme: &ChartTheme,
+) -> ChartResult {
+    match format {
+        ImageFormat::Png => draw_scatter_chart(
+            observed,
+            simulated,
+            title,
+            flow_unit,
+            theme,
+            BitMapBackend::new(destination, SCATTER_CHART_SIZE).into_drawing_area(),
+        ),
+        ImageFormat::Svg => draw_scatter_chart(
+            observed,
+            simulated,
+            title,
+            flow_unit,
+            theme,
+            SVGBackend::new(destination, SCATTER_CHART_SIZE).into_drawing_area(),
+        ),
+    }
+}
+
+/// Draw the observed vs. simulated scatter chart on a drawing area (see [`save_scatter_chart`]).
+///
+/// # Arguments
+///
+/// * `observed`: The observed flow time-series.
+/// * `simulated`: The simulated flow time-series.
+/// * `title`: The chart title.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `theme`: The colours of the chart series.
+/// * `root_area`: The drawing area.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+fn draw_scatter_chart<DB: DrawingBackend>(
+    observed: &[f64],
+    simulated: &[f64],
+    title: String,
+    flow_unit: &RunOffUnit,
+    theme: &ChartTheme,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    let points: Vec<(f64, f64)> = observed
+        .iter()
+        .zip(simulated)
+        .filter(|(obs, sim)| obs.is_finite() && sim.is_finite())
+        .map(|(obs, sim)| (*obs, *sim))
+        .collect();
+
+    // use the same range on both axes so that the 1:1 line is the diagonal
+    let (mut q_min, mut q_max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (obs, sim)| {
+            (lo.min(obs.min(*sim)), hi.max(obs.max(*sim)))
+        });
+    if points.is_empty() {
+        (q_min, q_max) = (0.0, 1.0);
+    }
+    q_min = q_min.min(0.0).floor();
+    q_max = q_max.ceil().max(q_min + 1.0);
+
+    root_area.fill(&WHITE)?;
+    let root_area = root_area.titled(&title, (FONT, 30))?;
+
+    let unit = flow_unit.unit_label();
+    let mut cc = ChartBuilder::on(&root_area)
+        .x_label_area_size(X_LABEL_AREA_SIZE)
+        .y_label_area_size(Y_LABEL_AREA_SIZE)
+        .margin(10)
+        .build_cartesian_2d(q_min..q_max, q_min..q_max)?;
+
+    cc.configure_mesh()
+        .x_desc(format!("Observed run-off ({})", unit))
+        .y_desc(format!("Simulated run-off ({})", unit))
+        .axis_desc_style(AXIS_STYLE)
+        .label_style(LABEL_STYLE)
+        .draw()?;
+
+    cc.draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
+        points,
+        3,
+        theme.simulated.filled(),
+    ))?;
+
+    cc.draw_series(DashedLineSeries::new(
+        [(q_min, q_min), (q_max, q_max)],
+        6,
+        6,
+        ShapeStyle {
+            color: theme.ideal_value.to_rgba(),
+            filled: false,
+            stroke_width: 2,
+        },
+    ))?;
+
+    root_area.present()?;
+    Ok(())
+}
+
+/// The run-off statistics of a calendar month.
+struct MonthlyStatistics {
+    /// The month number (1 for January).
+    month: u32,
+    /// The minimum run-off.
+    min: f64,
+    /// The mean run-off.
+    mean: f64,
+    /// The maximum run-off.
+    max: f64,
+}
+
+/// Group the run-off by calendar month and calculate the minimum, mean and maximum value of each
+/// month. The missing values are ignored and the months without any value are not returned.
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `run_off`: The run-off time-series.
+///
+/// returns: `Vec<MonthlyStatistics>`
+fn monthly_statistics(time: &[NaiveDate], run_off: &[f64]) -> Vec<MonthlyStatistics> {
+    let mut groups: Vec<Vec<f64>> = vec![vec![]; 12];
+    for (t, q) in time.iter().zip(run_off) {
+        if q.is_finite() {
+            groups[t.month0() as usize].push(*q);
+        }
+    }
+
+    groups
+        .iter()
+        .enumerate()
+        .filter(|(_, values)| !values.is_empty())
+        .map(|(month0, values)| MonthlyStatistics {
+            month: month0 as u32 + 1,
+            min: series_min(values),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            max: series_max(values),
+        })
+        .collect()
+}
+
+/// Plot a chart with the hydrological regime of the run-off. For each calendar month, the chart
+/// shows the range between the minimum and maximum run-off as bar and the mean run-off as marker.
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `run_off`: The run-off time-series.
+/// * `destination`: The path of the chart file.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `format`: The file format of the chart.
+/// * `theme`: The colours of the chart series.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub(crate) fn save_monthly_regime_chart(
+    time: &[NaiveDate],
+    run_off: &[f64],
+    destination: &PathBuf,
+    flow_unit: &RunOffUnit,
+    format: ImageFormat,
+    theme: &ChartTheme,
+) -> ChartResult {
+    let statistics = monthly_statistics(time, run_off);
+    let size = (1200, 800);
+    match format {
+        ImageFormat::Png => draw_monthly_regime_chart(
+            &statistics,
+            flow_unit,
+            theme,
+            BitMapBackend::new(destination, size).into_drawing_area(),
+        ),
+        ImageFormat::Svg => draw_monthly_regime_chart(
+            &statistics,
+            flow_unit,
+            theme,
+            SVGBackend::new(destination, size).into_drawing_area(),
+        ),
+    }
+}
+
+/// Draw the hydrological regime on a drawing area (see [`save_monthly_regime_chart`]).
+///
+/// # Arguments
+///
+/// * `statistics`: The run-off statistics of each month.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `theme`: The colours of the chart series.
+/// * `root_area`: The drawing area.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+fn draw_monthly_regime_chart<DB: DrawingBackend>(
+    statistics: &[MonthlyStatistics],
+    flow_unit: &RunOffUnit,
+    theme: &ChartTheme,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    root_area.fill(&WHITE)?;
+    let root_area = root_area.titled("Monthly run-off regime", (FONT, 30))?;
+
+    let mut y_max = statistics.iter().fold(0.0_f64, |max, s| max.max(s.max));
+    if y_max > 1.0 {
+        y_max = y_max.ceil();
+    } else if y_max <= 0.0 {
+        y_max = 1.0;
+    };
+
+    let mut cc = ChartBuilder::on(&root_area)
+        .x_label_area_size(X_LABEL_AREA_SIZE)
+        .y_label_area_size(Y_LABEL_AREA_SIZE)
+        .set_label_area_size(LabelAreaPosition::Left, 90)
+        .margin_top(5)
+        .margin_left(20)
+        .margin_right(30)
+        .build_cartesian_2d((1_u32..12_u32).into_segmented(), 0.0..y_max)?;
+
+    cc.configure_mesh()
+        .disable_x_mesh()
+        .y_desc(format!("Run-off ({})", flow_unit.unit_label()))
+        .axis_desc_style(AXIS_STYLE)
+        .label_style(LABEL_STYLE)
+        .x_label_formatter(&|v| match v {
+            SegmentValue::CenterOf(month) => Month::try_from(*month as u8)
+                .map(|m| m.name()[0..3].to_string())
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .draw()?;
+
+    let colour = theme.simulated;
+    cc.draw_series(statistics.iter().map(|s| {
+        let mut bar = Rectangle::new(
+            [
+                (SegmentValue::Exact(s.month), s.min),
+                (SegmentValue::Exact(s.month + 1), s.max),
+            ],
+            colour.mix(0.4).filled(),
+        );
+        bar.set_margin(0, 0, 15, 15);
+        bar
+    }))?
+    .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], colour.mix(0.4).filled()))
+    .label("Min - max");
+
+    cc.draw_series(
+        statistics
+            .iter()
+            .map(|s| Circle::new((SegmentValue::CenterOf(s.month), s.mean), 5, colour.filled())),
+    )?
+    .legend(move |(x, y)| Circle::new((x + 10, y), 5, colour.filled()))
+    .label("Mean");
+
+    cc.configure_series_labels()
+        .border_style(GREY_A400)
+        .background_style(WHITE)
+        .label_font((FONT, 20))
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()?;
+
+    Ok(())
+}
+
+/// Plot a chart with the contribution of each hydrological unit to the total run-off. The
+/// contributions are stacked on top of each other and the total run-off is drawn as line.
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `per_catchment_run_off`: The run-off of each hydrological unit.
+/// * `total`: The total run-off.
+/// * `destination`: The path of the chart file.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `format`: The file format of the chart.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub(crate) fn save_catchment_contribution_chart(
+    time: &[NaiveDate],
+    per_catchment_run_off: &[Vec<f64>],
+    total: &[f64],
+    destination: &PathBuf,
+    flow_unit: &RunOffUnit,
+    format: ImageFormat,
+) -> ChartResult {
+    let size = (1800, 800);
+    match format {
+        ImageFormat::Png => draw_catchment_contribution_chart(
+            time,
+            per_catchment_run_off,
+            total,
+            flow_unit,
+            BitMapBackend::new(destination, size).into_drawing_area(),
+        ),
+        ImageFormat::Svg => draw_catchment_contribution_chart(
+            time,
+            per_catchment_run_off,
+            total,
+            flow_unit,
+            SVGBackend::new(destination, size).into_drawing_area(),
+        ),
+    }
+}
+
+/// Draw the contribution of each hydrological unit on a drawing area (see
+/// [`save_catchment_contribution_chart`]).
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `per_catchment_run_off`: The run-off of each hydrological unit.
+/// * `total`: The total run-off.
+/// * `flow_unit`: The unit of measurement for the flow.
+/// * `root_area`: The drawing area.
+///
+/// returns: `Result<(), Box<dyn Error>>`
+fn draw_catchment_contribution_chart<DB: DrawingBackend>(
+    time: &[NaiveDate],
+    per_catchment_run_off: &[Vec<f64>],
+    total: &[f64],
+    flow_unit: &RunOffUnit,
+    root_area: DrawingArea<DB, Shift>,
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    root_area.fill(&WHITE)?;
+    let root_area = root_area.titled("Run-off by hydrological unit", (FONT, 30))?;
+
+    // cumulative run-off to stack the contributions
+    let mut stacked: Vec<Vec<f64>> = vec![];
+    for run_off in per_catchment_run_off.iter() {
+        let previous = stacked.last().cloned().unwrap_or_else(|| vec![0.0; run_off.len()]);
+        stacked.push(previous.iter().zip(run_off).map(|(a, b)| a + b).collect());
+    }
+
+    let t_range = (*time.first().unwrap()..*time.last().unwrap()).yearly();
+    let mut y_max = series_max(total);
+    if let Some(top) = stacked.last() {
+        y_max = y_max.max(series_max(top));
+    }
+    if y_max > 1.0 {
+        y_max = y_max.ceil();
+    };
+
+    let mut cc = ChartBuilder::on(&root_area)
+        .x_label_area_size(X_LABEL_AREA_SIZE)
+        .y_label_area_size(Y_LABEL_AREA_SIZE)
+        .set_label_area_size(LabelAreaPosition::Left, 90)
+        .margin_top(5)
+        .margin_left(20)
+        .margin_right(30)
+        .build_cartesian_2d(t_range, 0.0..y_max)?;
+
+    cc.configure_mesh()
+        .y_desc(format!("Run-off ({})", flow_unit.unit_label()))
+        .axis_desc_style(AXIS_STYLE)
+        .label_style(LABEL_STYLE)
+        .x_label_formatter(&|v| v.year().to_string())
+        .draw()?;
+
+    // draw the top layer first so that each unit area covers the one below
+    for (index, series) in stacked.iter().enumerate().rev() {
+        let colour = Palette99::pick(index);
+        cc.draw_series(AreaSeries::new(
+            time.iter().zip(series).map(|(t, q)| (*t, *q)),
+            0.0,
+            colour.mix(0.6),
+        ))?
+        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], colour.filled()))
+        .label(format!("Unit #{}", index + 1));
+    }
+
+    cc.draw_series(LineSeries::new(
+        time.iter().zip(total).map(|(t, q)| (*t, *q)),
+        ShapeStyle {
+            color: BLACK.to_rgba(),
+            filled: false,
+            stroke_width: 1,
+        },
+    ))?
+    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK))
+    .label("Total");
+    render_legend_box(&mut cc)?;
+
+    root_area.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chart::{
+        draw_flow_comparison_chart, draw_metric_vs_parameter_chart, metric_panels, monthly_statistics,
+        save_cumulative_flow_chart, save_flow_comparison_chart, save_monthly_regime_chart, save_scatter_chart,
+        CALIBRATION_CHART_SIZE,
+    };
+    use crate::inputs::{ChartTheme, ImageFormat, RunOffUnit};
+    use crate::metric::{CalibrationMetric, MetricType, OptionalMetrics};
+    use crate::outputs::CalibrationMetricVector;
+    use chrono::{Datelike, NaiveDate, TimeDelta};
+    use float_cmp::approx_eq;
+    use plotters::prelude::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_svg_chart() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..730).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let simulated: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 30) as f64 / 10.0).collect();
+        let observed: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 20) as f64 / 10.0).collect();
+
+        let destination = env::temp_dir().join("gr6j_test_svg_chart.svg");
+        save_flow_comparison_chart(
+            &time,
+            &simulated,
+            Some(&observed),
+            "Simulated vs. observed".to_string(),
+            &destination,
+            &RunOffUnit::NoConversion,
+            ImageFormat::Svg,
+            &ChartTheme::default(),
+        )
+        .unwrap();
+        let content = fs::read_to_string(&destination).unwrap();
+        fs::remove_file(&destination).unwrap();
+
+        assert!(!content.is_empty());
+        assert!(content.trim_start().starts_with("<svg"));
+    }
+
+    #[test]
+    fn test_scatter_chart() {
+        let simulated: Vec<f64> = (0..365).map(|i| 1.0 + (i % 30) as f64 / 10.0).collect();
+        let mut observed: Vec<f64> = (0..365).map(|i| 1.0 + (i % 20) as f64 / 10.0).collect();
+        observed[10] = f64::NAN;
+
+        let destination = env::temp_dir().join("gr6j_test_scatter_chart.png");
+        save_scatter_chart(
+            &observed,
+            &simulated,
+            "Observed vs. simulated".to_string(),
+            &destination,
+            &RunOffUnit::NoConversion,
+            ImageFormat::Png,
+            &ChartTheme::default(),
+        )
+        .unwrap();
+        let content = fs::read(&destination).unwrap();
+        fs::remove_file(&destination).unwrap();
+
+        assert!(!content.is_empty());
+        assert!(png::Decoder::new(content.as_slice()).read_info().is_ok());
+    }
+
+    #[test]
+    fn test_cumulative_flow_chart() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..730).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let simulated: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 30) as f64 / 10.0).collect();
+        let mut observed: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 20) as f64 / 10.0).collect();
+        observed[100] = f64::NAN;
+
+        let destination = env::temp_dir().join("gr6j_test_cumulative_flow_chart.png");
+        save_cumulative_flow_chart(
+            &time,
+            &simulated,
+            &observed,
+            "Cumulative run-off".to_string(),
+            &destination,
+            &RunOffUnit::NoConversion,
+            ImageFormat::Png,
+            &ChartTheme::default(),
+        )
+        .unwrap();
+        let content = fs::read(&destination).unwrap();
+        fs::remove_file(&destination).unwrap();
+
+        assert!(!content.is_empty());
+        assert!(png::Decoder::new(content.as_slice()).read_info().is_ok());
+    }
+
+    #[test]
+    fn test_flow_comparison_chart_with_residuals() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..730).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let simulated: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 30) as f64 / 10.0).collect();
+        let mut observed: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 20) as f64 / 10.0).collect();
+        observed[50] = f64::NAN;
+
+        let size = CALIBRATION_CHART_SIZE;
+        let mut with_observed = vec![0; (size.0 * size.1 * 3) as usize];
+        let mut without_observed = with_observed.clone();
+        for (buffer, observed) in [
+            (&mut with_observed, Some(observed.as_slice())),
+            (&mut without_observed, None),
+        ] {
+            let root_area = BitMapBackend::with_buffer(buffer, size).into_drawing_area();
+            draw_flow_comparison_chart(
+                &time,
+                &simulated,
+                observed,
+                "Simulated vs. observed".to_string(),
+                &RunOffUnit::NoConversion,
+                &ChartTheme::default(),
+                root_area,
+            )
+            .unwrap();
+        }
+
+        // the residual panel is drawn in the bottom third of the chart only
+        let bottom_third = (size.0 * (size.1 * 2 / 3) * 3) as usize;
+        assert!(with_observed[bottom_third..].iter().any(|p| *p != 255));
+        assert_ne!(with_observed, without_observed);
+    }
+
+    #[test]
+    fn test_custom_theme() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..365).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let simulated: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 30) as f64 / 10.0).collect();
+        let observed: Vec<f64> = (0..time.len()).map(|i| 1.0 + (i % 20) as f64 / 10.0).collect();
+
+        let theme = ChartTheme {
+            simulated: RGBColor(1, 2, 3),
+            observed: RGBColor(4, 5, 6),
+            ..ChartTheme::default()
+        };
+        let render = |theme: &ChartTheme| {
+            let size = CALIBRATION_CHART_SIZE;
+            let mut buffer = vec![0; (size.0 * size.1 * 3) as usize];
+            {
+                let root_area = BitMapBackend::with_buffer(&mut buffer, size).into_drawing_area();
+                draw_flow_comparison_chart(
+                    &time,
+                    &simulated,
+                    Some(&observed),
+                    "Simulated vs. observed".to_string(),
+                    &RunOffUnit::NoConversion,
+                    theme,
+                    root_area,
+                )
+                .unwrap();
+            }
+            buffer
+        };
+        let has_colour =
+            |buffer: &[u8], colour: RGBColor| buffer.chunks(3).any(|p| p == [colour.0, colour.1, colour.2]);
+
+        let custom = render(&theme);
+        assert!(has_colour(&custom, theme.simulated));
+        assert!(has_colour(&custom, theme.observed));
+
+        let default = render(&ChartTheme::default());
+        assert!(!has_colour(&default, theme.simulated));
+        assert!(!has_colour(&default, theme.observed));
+    }
+
+    #[test]
+    fn test_monthly_regime_chart() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..731).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let mut run_off: Vec<f64> = time.iter().map(|t| t.month() as f64 + t.day() as f64 / 100.0).collect();
+        run_off[0] = f64::NAN;
+
+        // one group is plotted for each month
+        let statistics = monthly_statistics(&time, &run_off);
+        assert_eq!(statistics.len(), 12);
+        assert_eq!(
+            statistics.iter().map(|s| s.month).collect::<Vec<u32>>(),
+            (1..=12).collect::<Vec<u32>>()
+        );
+        // January has 30 values in 2000 (the first day is missing) and 31 in 2001
+        assert_eq!(statistics[0].month, 1);
+        assert!(approx_eq!(f64, statistics[0].min, 1.01, epsilon = 1e-12));
+        assert!(approx_eq!(
+            f64,
+            statistics[0].mean,
+            1.0 + 991.0 / 6100.0,
+            epsilon = 1e-12
+        ));
+        assert!(approx_eq!(f64, statistics[0].max, 1.31, epsilon = 1e-12));
+        assert!(statistics.iter().all(|s| s.min <= s.mean && s.mean <= s.max));
+
+        let destination = env::temp_dir().join("gr6j_test_monthly_regime_chart.png");
+        save_monthly_regime_chart(
+            &time,
+            &run_off,
+            &destination,
+            &RunOffUnit::NoConversion,
+            ImageFormat::Png,
+            &ChartTheme::default(),
+        )
+        .unwrap();
+        let content = fs::read(&destination).unwrap();
+        fs::remove_file(&destination).unwrap();
+        assert!(png::Decoder::new(content.as_slice()).read_info().is_ok());
+    }
+
+    #[test]
+    fn test_metric_vs_parameter_chart_optional_metrics() {
+        let observed: Vec<f64> = (0..100).map(|i| 1.0 + (i % 10) as f64 / 10.0).collect();
+        let build_metrics = |optional_metrics: Option<OptionalMetrics>| {
+            CalibrationMetricVector(
+                (0..5)
+                    .map(|m| {
+                        let simulated: Vec<f64> = observed.iter().map(|q| q * (1.0 + m as f64 / 10.0)).collect();
+                        CalibrationMetric::new(&observed, &simulated, optional_metrics.clone()).unwrap()
+                    })
+                    .collect(),
+            )
+        };
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+
+        assert_eq!(metric_panels(&build_metrics(None)).len(), 5);
+
+        let optional_metrics = OptionalMetrics {
+            kling_gupta2009: true,
+            kling_gupta2012: true,
+            ..OptionalMetrics::default()
+        };
+        let metrics = build_metrics(Some(optional_metrics));
+        let panels = metric_panels(&metrics);
+        assert_eq!(panels.len(), 7);
+        assert_eq!(panels[5].1, MetricType::KlingGupta2009);
+        assert_eq!(panels[6].1, MetricType::KlingGupta2012);
+        assert!(panels.iter().all(|(values, _)| values.iter().all(|v| v.is_finite())));
+
+        let size = CALIBRATION_CHART_SIZE;
+        let mut buffer = vec![0; (size.0 * size.1 * 3) as usize];
+        {
+            let root_area = BitMapBackend::with_buffer(&mut buffer, size).into_drawing_area();
+            draw_metric_vs_parameter_chart(
+                &x,
+                &metrics,
+                "Parameter X1".to_string(),
+                &ChartTheme::default(),
+                root_area,
+            )
+            .unwrap();
+        }
+        // the seventh panel is drawn in the bottom row
+        let bottom_row = (size.0 * (size.1 * 2 / 3) * 3) as usize;
+        assert!(buffer[bottom_row..].iter().any(|p| *p != 255));
+    }
+}
@@ -0,0 +1,312 @@
+use crate::error::LoadModelError;
+use crate::inputs::{CatchmentData, GR6JModelInputs, ModelPeriod, RunOffUnit, WarmupStrategy};
+use crate::parameter::{Parameter, X1, X2, X3, X4, X5, X6};
+use chrono::NaiveDate;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::fs;
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
+/// The area and parameters of one hydrological unit in a [`ModelConfig`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CatchmentConfig {
+    /// The catchment os sub-catchment area (km2).
+    pub area: f64,
+    /// Maximum capacity of the production store (mm/day).
+    pub x1: f64,
+    /// Inter-catchment (or groundwater) exchange coefficient (mm/day).
+    pub x2: f64,
+    /// One-day-ahead maximum capacity of the routing store (mm/day).
+    pub x3: f64,
+    /// Time base of unit hydrograph `UH1` (days).
+    pub x4: f64,
+    /// Inter-catchment exchange threshold.
+    pub x5: f64,
+    /// Time constant of exponential store (mm)
+    pub x6: f64,
+}
+
+/// The full set-up of a model run. With the `serde` feature, this can be stored in a TOML file,
+/// for example:
+///
+/// ```toml
+/// forcing_file = "forcing.csv"
+/// date_format = "%d/%m/%Y"
+/// run_off_unit = "NoConversion"
+///
+/// [run_period]
+/// start = "1994-01-01"
+/// end = "1998-12-31"
+///
+/// [[catchment]]
+/// area = 1.0
+/// x1 = 31.0
+/// x2 = 3.47
+/// x3 = 32.0
+/// x4 = 2.1
+/// x5 = 0.55
+/// x6 = 5.3
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelConfig {
+    /// The CSV file with the forcing data. The columns must contain the date, the total
+    /// precipitation (mm/day), the potential evapotranspiration (mm/day) and, optionally, the
+    /// observed run-off. The first row is the header. A relative path is resolved against the
+    /// folder of the configuration file.
+    pub forcing_file: PathBuf,
+    /// The format of the dates in the forcing file. When None this defaults to `%Y-%m-%d`.
+    pub date_format: Option<String>,
+    /// Area and GR6J parameters for each hydrological unit.
+    pub catchment: Vec<CatchmentConfig>,
+    /// The start and end date of the model.
+    pub run_period: ModelPeriod,
+    /// The start and end date of the warm-up period.
+    pub warmup_period: Option<ModelPeriod>,
+    /// How to initialise the model stores before the run period.
+    pub warmup_strategy: Option<WarmupStrategy>,
+    /// Convert the run-off to the desired unit of measurement.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub run_off_unit: RunOffUnit,
+    /// The folder where to export the charts and the diagnostic files.
+    pub destination: Option<PathBuf>,
+    /// The multiplicative factor to apply to the precipitation series.
+    pub precip_correction: Option<f64>,
+    /// The multiplicative factor to apply to the potential evapotranspiration series.
+    pub pet_correction: Option<f64>,
+}
+
+/// The forcing data loaded from [`ModelConfig::forcing_file`] or with
+/// [`crate::io::load_forcing_csv`].
+#[derive(Debug, Clone)]
+pub struct ForcingData {
+    /// Vector of time.
+    pub time: Vec<NaiveDate>,
+    /// Input vector of total precipitation (mm/day).
+    pub precipitation: Vec<f64>,
+    /// Input vector of potential evapotranspiration (PE) (mm/day).
+    pub evapotranspiration: Vec<f64>,
+    /// The observed run-off, if the file contains a fourth column.
+    pub observed_runoff: Option<Vec<f64>>,
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for ModelConfig {
+    type Err = LoadModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|e| LoadModelError::InvalidConfig(e.to_string()))
+    }
+}
+
+impl ModelConfig {
+    /// Load the configuration from a TOML file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: The path to the TOML file.
+    ///
+    /// returns: `Result<ModelConfig, LoadModelError>`
+    #[cfg(feature = "serde")]
+    pub fn from_toml(path: &Path) -> Result<Self, LoadModelError> {
+        let content = fs::read_to_string(path).map_err(|e| LoadModelError::InvalidConfig(e.to_string()))?;
+        let mut config = ModelConfig::from_str(&content)?;
+
+        if config.forcing_file.is_relative() {
+            if let Some(parent) = path.parent() {
+                config.forcing_file = parent.join(&config.forcing_file);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Convert the configuration to a TOML string.
+    ///
+    /// returns: `Result<String, LoadModelError>`
+    #[cfg(feature = "serde")]
+    pub fn to_toml(&self) -> Result<String, LoadModelError> {
+        toml::to_string(self).map_err(|e| LoadModelError::InvalidConfig(e.to_string()))
+    }
+
+    /// Load the forcing data from [`ModelConfig::forcing_file`].
+    ///
+    /// returns: `Result<ForcingData, LoadModelError>`
+    pub fn load_forcing(&self) -> Result<ForcingData, LoadModelError> {
+        let date_format = self.date_format.as_deref().unwrap_or("%Y-%m-%d");
+        let file = File::open(&self.forcing_file).map_err(|e| {
+            LoadModelError::InvalidConfig(format!(
+                "the forcing file '{}' cannot be opened ({})",
+                self.forcing_file.display(),
+                e
+            ))
+        })?;
+        let mut rdr = csv::Reader::from_reader(file);
+
+        let mut time: Vec<NaiveDate> = vec![];
+        let mut precipitation: Vec<f64> = vec![];
+        let mut evapotranspiration: Vec<f64> = vec![];
+        let mut observed_runoff: Vec<f64> = vec![];
+        for (row, result) in rdr.records().enumerate() {
+            let record = result.map_err(|e| LoadModelError::InvalidConfig(e.to_string()))?;
+            let invalid = |column: &str| {
+                LoadModelError::InvalidConfig(format!(
+                    "the {} on row #{} of the forcing file is not valid",
+                    column,
+                    row + 1
+                ))
+            };
+
+            let date = record.get(0).ok_or_else(|| invalid("date"))?;
+            time.push(NaiveDate::parse_from_str(date, date_format).map_err(|_| invalid("date"))?);
+            precipitation.push(parse_value(record.get(1)).ok_or_else(|| invalid("precipitation"))?);
+            evapotranspiration.push(parse_value(record.get(2)).ok_or_else(|| invalid("evapotranspiration"))?);
+            if record.get(3).is_some() {
+                observed_runoff.push(parse_value(record.get(3)).ok_or_else(|| invalid("observed run-off"))?);
+            }
+        }
+
+        let observed_runoff = match observed_runoff.is_empty() {
+            true => None,
+            false => Some(observed_runoff),
+        };
+        Ok(ForcingData {
+            time,
+            precipitation,
+            evapotranspiration,
+            observed_runoff,
+        })
+    }
+
+    /// Build the model inputs from the configuration and the forcing data.
+    ///
+    /// # Arguments
+    ///
+    /// * `forcing`: The forcing data.
+    ///
+    /// returns: `Result<GR6JModelInputs, LoadModelError>`
+    pub fn build_inputs<'a>(&self, forcing: &'a ForcingData) -> Result<GR6JModelInputs<'a>, LoadModelError> {
+        let mut catchment: Vec<CatchmentData> = vec![];
+        for data in self.catchment.iter() {
+            catchment.push(CatchmentData {
+                area: data.area,
+                x1: X1::new(data.x1)?,
+                x2: X2::new(data.x2)?,
+                x3: X3::new(data.x3)?,
+                x4: X4::new(data.x4)?,
+                x5: X5::new(data.x5)?,
+                x6: X6::new(data.x6)?,
+                store_levels: None,
+                uh_exponent: None,
+            });
+        }
+
+        // the periods are deserialised without validation
+        let validate_period = |p: &ModelPeriod| {
+            ModelPeriod::new(p.start, p.end).map_err(|e| LoadModelError::InvalidConfig(e.to_string()))
+        };
+        let run_period = validate_period(&self.run_period)?;
+        let warmup_period = match &self.warmup_period {
+            None => None,
+            Some(p) => Some(validate_period(p)?),
+        };
+
+        Ok(GR6JModelInputs {
+            time: &forcing.time,
+            precipitation: &forcing.precipitation,
+            evapotranspiration: &forcing.evapotranspiration,
+            catchment,
+            run_period,
+            warmup_period,
+            warmup_strategy: self.warmup_strategy,
+            destination: self.destination.clone(),
+            observed_runoff: forcing.observed_runoff.as_deref(),
+            run_off_unit: self.run_off_unit.clone(),
+            precip_correction: self.precip_correction,
+            pet_correction: self.pet_correction,
+            ..Default::default()
+        })
+    }
+}
+
+/// Parse a number from a CSV cell. `NA` is converted to NaN.
+fn parse_value(value: Option<&str>) -> Option<f64> {
+    match value?.trim() {
+        "NA" => Some(f64::NAN),
+        v => v.parse::<f64>().ok(),
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::config::{CatchmentConfig, ModelConfig};
+    use crate::inputs::{ModelPeriod, RunOffUnit, WarmupStrategy};
+    use crate::model::GR6JModel;
+    use chrono::NaiveDate;
+    use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+
+    fn sample_config_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples")
+            .join("model_config.toml")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = ModelConfig {
+            forcing_file: PathBuf::from("forcing.csv"),
+            date_format: Some("%d/%m/%Y".to_string()),
+            catchment: vec![CatchmentConfig {
+                area: 2.5,
+                x1: 31.0,
+                x2: 3.47,
+                x3: 32.0,
+                x4: 2.1,
+                x5: 0.55,
+                x6: 5.3,
+            }],
+            run_period: ModelPeriod::new(
+                NaiveDate::from_ymd_opt(1994, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(1998, 12, 31).unwrap(),
+            )
+            .unwrap(),
+            warmup_period: None,
+            warmup_strategy: Some(WarmupStrategy::Climatology),
+            run_off_unit: RunOffUnit::CubicMetrePerSecond,
+            destination: None,
+            precip_correction: Some(1.1),
+            pet_correction: None,
+        };
+
+        let toml = config.to_toml().unwrap();
+        assert_eq!(ModelConfig::from_str(&toml).unwrap(), config);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(ModelConfig::from_str("forcing_file = 1").is_err());
+    }
+
+    #[test]
+    fn test_sample_config() {
+        let config = ModelConfig::from_toml(&sample_config_path()).unwrap();
+        assert_eq!(config.catchment.len(), 1);
+        assert!(config.forcing_file.exists());
+
+        let forcing = config.load_forcing().unwrap();
+        assert_eq!(forcing.time.len(), forcing.precipitation.len());
+        assert!(forcing.observed_runoff.is_some());
+
+        let mut inputs = config.build_inputs(&forcing).unwrap();
+        inputs.logging = Some(false);
+        let results = GR6JModel::new(inputs).unwrap().run().unwrap();
+        assert_eq!(results.time[0], config.run_period.start);
+    }
+}
@@ -7,6 +7,8 @@ use thiserror::Error;
 pub enum ModelPeriodError {
     #[error("The end {0} date must be smaller than the start date {1}")]
     DateTooSmall(NaiveDate, NaiveDate),
+    #[error("The split fraction must be a number between 0 and 1, but {0} was given")]
+    InvalidSplitFraction(f64),
 }
 
 #[derive(Error, Debug)]
@@ -35,16 +37,42 @@ pub enum LoadModelError {
     TooFarWarmUpPeriod(String, String),
     #[error("The destination folder {0} does not exist")]
     DestinationNotFound(String),
+    #[error("The destination {0} is not a folder")]
+    DestinationNotADirectory(String),
     #[error(
         "The {0} series contains at least one NA value at the following indices: {1:?}. Missing values are not allowed"
     )]
     NanData(String, Vec<String>),
+    #[error(
+        "The {0} series contains at least one negative value at the following indices: {1:?}. Negative values are not allowed"
+    )]
+    NegativeData(String, Vec<String>),
+    #[error("The model configuration is not valid: {0}")]
+    InvalidConfig(String),
+    #[error("The forcing file is not valid: {0}")]
+    InvalidForcingFile(String),
     #[error("{0}")]
     Generic(String),
 }
 
+#[derive(Error, Debug, PartialEq)]
+pub enum MetricError {
+    #[error(
+        "The vector must have the same length. Observed has {observed} values and simulated has {simulated} values"
+    )]
+    LengthMismatch { observed: usize, simulated: usize },
+    #[error("The time vector has {time} values but the observed series has {observed} values")]
+    TimeLengthMismatch { time: usize, observed: usize },
+    #[error("The mask has {mask} values but the series have {series} values")]
+    MaskLengthMismatch { mask: usize, series: usize },
+    #[error("The mask does not select any time step")]
+    EmptyMask(),
+}
+
 #[derive(Error, Debug)]
 pub enum RunModelError {
+    #[error("The model cannot be loaded because {0}")]
+    CannotLoadModel(String),
     #[error("The destination folder {0} cannot be created")]
     DestinationNotWritable(String),
     #[error("The run-off conversion factor must be larger than 0")]
@@ -57,8 +85,16 @@ pub enum RunModelError {
     CannotExportCsv(String),
     #[error("The {0} chart file cannot be generated because {1}")]
     CannotGenerateChart(String, String),
+    #[error("The run metadata file cannot be exported because {0}")]
+    CannotExportMetadata(String),
+    #[error("The simulation became unstable at step #{0} because {1}")]
+    Unstable(usize, String),
     #[error("Cannot load the calibration model #{0} because: {1}")]
     CalibrationError(usize, String),
+    #[error("All the {0} calibration samples failed")]
+    AllSamplesFailed(usize),
+    #[error("The optimiser cannot run because {0}")]
+    OptimiserError(String),
 }
 
 impl From<csv::Error> for RunModelError {
@@ -67,6 +103,18 @@ impl From<csv::Error> for RunModelError {
     }
 }
 
+impl From<LoadModelError> for RunModelError {
+    fn from(value: LoadModelError) -> Self {
+        RunModelError::CannotLoadModel(value.to_string())
+    }
+}
+
+impl From<MetricError> for RunModelError {
+    fn from(value: MetricError) -> Self {
+        RunModelError::CannotCalculateMetrics(value.to_string())
+    }
+}
+
 impl From<io::Error> for RunModelError {
     fn from(value: io::Error) -> Self {
         RunModelError::CannotExportCsv(value.to_string())
@@ -1,12 +1,17 @@
 use crate::error::ModelPeriodError;
+use crate::metric::{MultiObjective, ObjectiveFn, OptionalMetrics};
 use crate::parameter::{X1Range, X2Range, X3Range, X4Range, X5Range, X6Range, X1, X2, X3, X4, X5, X6};
-use chrono::NaiveDate;
+use chrono::{NaiveDate, TimeDelta};
+use plotters::style::{Palette, Palette99, RGBColor};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 use std::path::PathBuf;
 
 /// Struct to define the store levels
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StoreLevels {
     /// The production store level (mm)
     pub production_store: f64,
@@ -17,6 +22,8 @@ pub struct StoreLevels {
 }
 
 impl Default for StoreLevels {
+    /// The default initial levels. The production and routing store levels are fractions of X1
+    /// and X3 (see [`InitialStoreLevels::Fraction`]).
     fn default() -> Self {
         StoreLevels {
             production_store: 0.3,
@@ -26,12 +33,89 @@ impl Default for StoreLevels {
     }
 }
 
+impl StoreLevels {
+    /// Define the initial store levels as fraction of the store capacities. See
+    /// [`InitialStoreLevels::Fraction`].
+    ///
+    /// # Arguments
+    ///
+    /// * `production_store`: The production store level as fraction of X1 (0-1).
+    /// * `routing_store`: The routing store level as fraction of X3 (0-1).
+    /// * `exponential_store`: The exponential store level (mm).
+    ///
+    /// returns: InitialStoreLevels
+    pub fn fraction(production_store: f64, routing_store: f64, exponential_store: f64) -> InitialStoreLevels {
+        InitialStoreLevels::Fraction(StoreLevels {
+            production_store,
+            routing_store,
+            exponential_store,
+        })
+    }
+
+    /// Define the initial store levels in mm. See [`InitialStoreLevels::Absolute`].
+    ///
+    /// # Arguments
+    ///
+    /// * `production_store`: The production store level (mm).
+    /// * `routing_store`: The routing store level (mm).
+    /// * `exponential_store`: The exponential store level (mm).
+    ///
+    /// returns: InitialStoreLevels
+    pub fn absolute(production_store: f64, routing_store: f64, exponential_store: f64) -> InitialStoreLevels {
+        InitialStoreLevels::Absolute(StoreLevels {
+            production_store,
+            routing_store,
+            exponential_store,
+        })
+    }
+}
+
+/// The store levels at the beginning of the simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub enum InitialStoreLevels {
+    /// The production and routing store levels are fractions (0-1) of their capacities X1 and X3;
+    /// for example a production store level of 0.3 means 0.3·X1 mm. The exponential store has no
+    /// capacity and its level is always in mm.
+    Fraction(StoreLevels),
+    /// All the store levels are in mm and are used as they are.
+    Absolute(StoreLevels),
+}
+
+impl Default for InitialStoreLevels {
+    fn default() -> Self {
+        InitialStoreLevels::Fraction(StoreLevels::default())
+    }
+}
+
+impl InitialStoreLevels {
+    /// Get the store levels in mm.
+    ///
+    /// # Arguments
+    ///
+    /// * `x1`: The maximum capacity of the production store (mm).
+    /// * `x3`: The maximum capacity of the routing store (mm).
+    ///
+    /// returns: StoreLevels
+    pub fn to_absolute(&self, x1: f64, x3: f64) -> StoreLevels {
+        match self {
+            InitialStoreLevels::Fraction(levels) => StoreLevels {
+                production_store: levels.production_store * x1,
+                routing_store: levels.routing_store * x3,
+                exponential_store: levels.exponential_store,
+            },
+            InitialStoreLevels::Absolute(levels) => *levels,
+        }
+    }
+}
+
 /// Struct to define a model time range
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ModelPeriod {
     /// The period start date
     pub start: NaiveDate,
-    /// The period end date
+    /// The period end date. This date is included in the period
     pub end: NaiveDate,
 }
 
@@ -42,6 +126,31 @@ impl ModelPeriod {
         }
         Ok(ModelPeriod { start, end })
     }
+
+    /// Split the period in two adjacent and non-overlapping periods, for example to calibrate the
+    /// model on the first period and validate it on the second one. The first period ends on the
+    /// day at the given fraction of the period length and the second period starts on the
+    /// following day.
+    ///
+    /// # Arguments
+    ///
+    /// * `fraction`: The fraction of the period length to assign to the first period (for
+    ///   example 0.7 to calibrate on the first 70% and validate on the last 30%).
+    ///
+    /// returns: `Result<(ModelPeriod, ModelPeriod), ModelPeriodError>`
+    pub fn split(&self, fraction: f64) -> Result<(ModelPeriod, ModelPeriod), ModelPeriodError> {
+        if !fraction.is_finite() || fraction <= 0.0 || fraction >= 1.0 {
+            return Err(ModelPeriodError::InvalidSplitFraction(fraction));
+        }
+        let total_days = (self.end - self.start).num_days();
+        let split_days = (total_days as f64 * fraction).round() as i64;
+        let split_date = self.start + TimeDelta::try_days(split_days).unwrap();
+
+        // both periods must contain at least two days
+        let first = ModelPeriod::new(self.start, split_date)?;
+        let second = ModelPeriod::new(split_date + TimeDelta::try_days(1).unwrap(), self.end)?;
+        Ok((first, second))
+    }
 }
 
 impl Debug for ModelPeriod {
@@ -50,8 +159,22 @@ impl Debug for ModelPeriod {
     }
 }
 
-/// The data for the catchment or hydrological unit.
+impl fmt::Display for ModelPeriod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} to {}", self.start, self.end)
+    }
+}
+
+/// The data for the catchment or hydrological unit. With the `serde` feature, this can be loaded
+/// from a configuration file, for example in JSON:
+///
+/// ```json
+/// {"area": 1.0, "x1": 31.0, "x2": 3.47, "x3": 32.0, "x4": 2.1, "x5": 0.55, "x6": 5.3}
+/// ```
+///
+/// The parameters are validated and the optional fields can be omitted.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
 pub struct CatchmentData {
     /// The catchment os sub-catchment area (km2).
     pub area: f64,
@@ -71,12 +194,28 @@ pub struct CatchmentData {
     pub x5: Box<X5>,
     /// Time constant of exponential store (mm)
     pub x6: Box<X6>,
-    /// The store levels
-    pub store_levels: Option<StoreLevels>,
+    /// The store levels at the beginning of the simulation, either as fraction of the store
+    /// capacities ([`StoreLevels::fraction`]) or in mm ([`StoreLevels::absolute`]). When None this
+    /// defaults to [`CatchmentData::default_store_levels`].
+    pub store_levels: Option<InitialStoreLevels>,
+    /// The exponent of the S-curves of the two unit hydrographs. This must be larger than 0. When
+    /// None this defaults to [`crate::model::UH_EXPONENT`] as in the original GR6J model.
+    pub uh_exponent: Option<f64>,
+}
+
+impl CatchmentData {
+    /// The store levels used when [`CatchmentData::store_levels`] is not given: the production
+    /// and routing stores are 30% and 50% full and the exponential store is empty.
+    ///
+    /// returns: InitialStoreLevels
+    pub fn default_store_levels() -> InitialStoreLevels {
+        InitialStoreLevels::default()
+    }
 }
 
 /// Convert the run-off to the desired unit of measurement
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RunOffUnit {
     #[default]
     /// Keep the run-off in mm*km2/d
@@ -87,6 +226,8 @@ pub enum RunOffUnit {
     MlPerDay,
     /// Convert the run-off to m³/s
     CubicMetrePerSecond,
+    /// Convert the run-off to l/s
+    LitrePerSecond,
 }
 
 impl RunOffUnit {
@@ -94,8 +235,11 @@ impl RunOffUnit {
     pub fn conv_factor(&self) -> f64 {
         match self {
             RunOffUnit::NoConversion => 1.0,
-            RunOffUnit::CubicMetrePerDay => 1.0 / 1000.0,
-            RunOffUnit::CubicMetrePerSecond => 86400.0 / 1000.0,
+            // 1 mm*km2 is 1000 m³
+            RunOffUnit::CubicMetrePerDay => 1000.0,
+            RunOffUnit::CubicMetrePerSecond => 1000.0 / 86400.0,
+            // 1 mm*km2 is 1e6 litres
+            RunOffUnit::LitrePerSecond => 1_000_000.0 / 86400.0,
             RunOffUnit::MlPerDay => 1.0,
         }
     }
@@ -106,12 +250,44 @@ impl RunOffUnit {
             RunOffUnit::NoConversion => "-",
             RunOffUnit::CubicMetrePerDay => "m³/d",
             RunOffUnit::CubicMetrePerSecond => "m³/s",
+            RunOffUnit::LitrePerSecond => "L/s",
             RunOffUnit::MlPerDay => "Ml/d",
         }
     }
 }
 
-/// Inputs to the GR6J model.
+/// The strategy used to initialise the model stores before the run period.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WarmupStrategy {
+    #[default]
+    /// Run the model over the data preceding the run period (see [`GR6JModelInputs::warmup_period`]).
+    PrecedingData,
+    /// Run the model over a synthetic one-year forcing built from the day-of-year mean of the
+    /// available precipitation and evapotranspiration. This does not require any data before the
+    /// run period and gives a spin-up that is less specific to a single year.
+    Climatology,
+    /// Do not warm up the model. The stores are initialised with [`CatchmentData::store_levels`].
+    NoWarmUp,
+}
+
+/// The numeric precision used to store the model results.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OutputPrecision {
+    #[default]
+    /// Store all the results in double precision.
+    F64,
+    /// Store the run-off and the store levels in single precision to halve the memory used by the
+    /// results. The per-step fluxes are not kept. The model runs and the metrics are calculated in
+    /// double precision before the results are down-cast, therefore the metrics are not affected;
+    /// the stored values have about 7 significant digits.
+    F32,
+}
+
+/// Inputs to the GR6J model. The optional fields can be omitted with the struct update syntax
+/// and [`GR6JModelInputs::default`], for example:
+/// `GR6JModelInputs { time: &time, ..., run_period, ..Default::default() }`.
 #[derive(Debug)]
 pub struct GR6JModelInputs<'a> {
     /// Vector of time.
@@ -129,8 +305,14 @@ pub struct GR6JModelInputs<'a> {
     /// compared to `time`.
     pub run_period: ModelPeriod,
     /// The start and end date of the warm-up period. If `None` and [`ModelPeriod::start`] allows,
-    /// the one-year period preceding the [`ModelPeriod::start`] is used.
+    /// the one-year period preceding the [`ModelPeriod::start`] is used. The warm-up period must
+    /// end before the run period starts; when there is a gap, the warm-up is extended to the day
+    /// before the run start date.
     pub warmup_period: Option<ModelPeriod>,
+    /// How to initialise the model stores before the run period. The warm-up period cannot be set
+    /// with [`WarmupStrategy::Climatology`], as the one-year period preceding the run start date is
+    /// always used. When None this defaults to [`WarmupStrategy::PrecedingData`].
+    pub warmup_strategy: Option<WarmupStrategy>,
     /// Whether to export charts, the simulated run-off and other diagnostic file into a sub-folder
     /// inside the given destination folder. The sub-folder will be named with the run timestamp.
     pub destination: Option<PathBuf>,
@@ -143,6 +325,168 @@ pub struct GR6JModelInputs<'a> {
     /// still need to be exposed via environment variables or another crate (such as env_logger).
     /// When None this defaults to true.
     pub logging: Option<bool>,
+    /// The maximum value of the net rainfall or evapotranspiration scaled by `X1` used when the
+    /// production store is updated. This prevents the exponential function from overflowing. When
+    /// None this defaults to [`crate::model::SCALED_RAINFALL_CAP`].
+    pub scaled_rainfall_cap: Option<f64>,
+    /// Run the models of the hydrological units in parallel. The run-off of each unit is combined
+    /// only after all units have completed, therefore the results are identical to a serial run.
+    /// When None this defaults to false.
+    pub parallel_catchments: Option<bool>,
+    /// The precision of the stored results. When None this defaults to [`OutputPrecision::F64`].
+    pub output_precision: Option<OutputPrecision>,
+    /// Whether to draw the panel with the log-scale y-axis in the flow duration curve chart. The
+    /// panel is always skipped when the run-off contains zero or negative values. When None this
+    /// defaults to true.
+    pub fdc_log_panel: Option<bool>,
+    /// The number of decimal places of the numbers exported to the CSV files. When None this
+    /// defaults to [`crate::utils::CSV_PRECISION`].
+    pub csv_precision: Option<usize>,
+    /// The multiplicative factor to apply to the precipitation series to correct the bias of the
+    /// precipitation product. When None this defaults to 1.0.
+    pub precip_correction: Option<f64>,
+    /// The multiplicative factor to apply to the potential evapotranspiration series to correct
+    /// the bias of the evapotranspiration product. When None this defaults to 1.0.
+    pub pet_correction: Option<f64>,
+    /// Whether to write the `run_metadata.json` file with the crate version, the run timestamp and
+    /// the model configuration (see [`crate::outputs::RunMetadata`]) to the destination folder.
+    /// The file is only written with the `serde` feature. When None this defaults to true.
+    pub write_metadata: Option<bool>,
+    /// The fraction of the effective rainfall routed through the first unit hydrograph (UH1). The
+    /// remaining fraction goes through the second unit hydrograph (UH2). This must be between 0
+    /// and 1. When None this defaults to [`crate::model::UH_SPLIT`].
+    pub uh_split: Option<f64>,
+    /// The fraction of the UH1 output that is sent to the exponential store. The remaining
+    /// fraction goes to the routing store. This must be between 0 and 1. When None this defaults
+    /// to [`crate::model::ROUTING_SPLIT`].
+    pub routing_split: Option<f64>,
+    /// Insert the dates missing from [`GR6JModelInputs::time`] instead of returning an error. The
+    /// precipitation, evapotranspiration and observed run-off of the inserted days are set to NaN
+    /// and the model skips these days: the stores are not updated and the run-off is NaN. The
+    /// run-off on the inserted days is therefore undefined and is excluded from the metrics. When
+    /// None this defaults to false and the time vector must have continuous dates.
+    pub fill_gaps: Option<bool>,
+    /// The file format of the charts exported to [`GR6JModelInputs::destination`]. When None this
+    /// defaults to [`ImageFormat::Png`].
+    pub image_format: Option<ImageFormat>,
+    /// The colours of the charts exported to [`GR6JModelInputs::destination`]. When None this
+    /// defaults to [`ChartTheme::default`].
+    pub chart_theme: Option<ChartTheme>,
+    /// The additional diagnostic charts to export to [`GR6JModelInputs::destination`]. When None
+    /// only the summary and flow duration curve charts are exported.
+    pub diagnostic_charts: Option<Vec<DiagnosticChart>>,
+    /// The optional metrics to calculate when [`GR6JModelInputs::observed_runoff`] is given, such
+    /// as the 2009 and 2012 Kling-Gupta efficiencies. When None this defaults to
+    /// [`OptionalMetrics::default()`].
+    pub optional_metrics: Option<OptionalMetrics>,
+}
+
+impl Default for GR6JModelInputs<'_> {
+    /// Inputs with all the optional fields set to None. The time series, the catchment and the
+    /// run period are empty and must always be set, otherwise [`crate::model::GR6JModel::new`]
+    /// returns an error.
+    fn default() -> Self {
+        Self {
+            time: &[],
+            precipitation: &[],
+            evapotranspiration: &[],
+            catchment: vec![],
+            run_period: ModelPeriod {
+                start: NaiveDate::default(),
+                end: NaiveDate::default(),
+            },
+            warmup_period: None,
+            warmup_strategy: None,
+            destination: None,
+            observed_runoff: None,
+            run_off_unit: RunOffUnit::default(),
+            logging: None,
+            scaled_rainfall_cap: None,
+            parallel_catchments: None,
+            output_precision: None,
+            fdc_log_panel: None,
+            csv_precision: None,
+            precip_correction: None,
+            pet_correction: None,
+            write_metadata: None,
+            uh_split: None,
+            routing_split: None,
+            fill_gaps: None,
+            image_format: None,
+            chart_theme: None,
+            diagnostic_charts: None,
+            optional_metrics: None,
+        }
+    }
+}
+
+/// The file format of the exported charts.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    #[default]
+    /// Raster image in the PNG format.
+    Png,
+    /// Vector image in the SVG format. This is suitable for publication-quality figures.
+    Svg,
+}
+
+impl ImageFormat {
+    /// The extension of the chart files.
+    ///
+    /// returns: `&str`
+    pub fn extension(&self) -> &str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// The diagnostic charts that can be exported in addition to the summary and flow duration curve
+/// charts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticChart {
+    /// The cumulative simulated and observed run-off (double-mass chart). This is only exported
+    /// when the observed run-off is given.
+    CumulativeFlow,
+    /// The simulated against the observed run-off with a 1:1 line. This is only exported when the
+    /// observed run-off is given.
+    Scatter,
+    /// The minimum, mean and maximum run-off of each calendar month.
+    MonthlyRegime,
+}
+
+/// The colours used to draw the series in the charts. Custom colours can be used, for example, to
+/// generate colour-blind-friendly figures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartTheme {
+    /// The colour of the simulated run-off.
+    pub simulated: RGBColor,
+    /// The colour of the observed run-off.
+    pub observed: RGBColor,
+    /// The colour of the rainfall.
+    pub rainfall: RGBColor,
+    /// The colour of the potential evapotranspiration.
+    pub evapotranspiration: RGBColor,
+    /// The colour of the line marking the ideal value of a metric.
+    pub ideal_value: RGBColor,
+}
+
+impl Default for ChartTheme {
+    /// The default colours picked from the 99-colour palette of `plotters`.
+    fn default() -> Self {
+        let pick = |index: usize| {
+            let (r, g, b) = Palette99::COLORS[index];
+            RGBColor(r, g, b)
+        };
+        Self {
+            simulated: pick(10),
+            observed: pick(12),
+            rainfall: pick(10),
+            evapotranspiration: pick(5),
+            ideal_value: pick(10),
+        }
+    }
 }
 
 /// The data for the catchment or hydrological unit to calibrate.
@@ -164,6 +508,11 @@ pub struct CalibrationCatchmentData {
     pub x6: Box<X6Range>,
 }
 
+/// A callback to track the progress of the calibration. This receives the number of completed
+/// models and the total number of models (in this order). The function is called from the
+/// parallel loop and must therefore be `Send` and `Sync`.
+pub type ProgressFn = Box<dyn Fn(usize, usize) + Send + Sync>;
+
 /// The calibration inputs.
 pub struct CalibrationInputs<'a> {
     /// Vector of time.
@@ -181,6 +530,11 @@ pub struct CalibrationInputs<'a> {
     /// The start and end date of the model run. The model can be run on a shorter time period
     /// compared to [`CalibrationInputs::time`].
     pub calibration_period: ModelPeriod,
+    /// The start and end date of the warm-up period used by all the models. This must end before
+    /// [`CalibrationInputs::calibration_period`] starts; when there is a gap, the warm-up is
+    /// extended to the day before the calibration start date. When None this defaults to the
+    /// one-year period preceding the calibration period, if the input data allow.
+    pub warmup_period: Option<ModelPeriod>,
     /// The path where to export (1) the comparison charts for the observed vs. simulated flow, (2)
     /// the flow duration curves, (3) the scatter charts of the calibration metrics to select
     /// the best calibration parameters and (4) a CSV file with metric values. The files are
@@ -197,4 +551,241 @@ pub struct CalibrationInputs<'a> {
     /// flow duration curves for each model. If `true`, the tool will generate as many as
     /// [`CalibrationInputs::sample_size`] figures.
     pub generate_comparison_charts: bool,
+    /// The number of decimal places of the numbers exported to the CSV files. When None this
+    /// defaults to [`crate::utils::CSV_PRECISION`].
+    pub csv_precision: Option<usize>,
+    /// Whether to skip the samples whose model fails to run (for example because the simulation
+    /// becomes unstable) and continue the calibration with the remaining samples. The failed
+    /// samples are reported in [`crate::outputs::CalibrationOutputs::failed_samples`]. When None
+    /// this defaults to false and the first failure stops the calibration.
+    pub skip_failures: Option<bool>,
+    /// A weighted combination of metrics used to rank the samples and select the best parameter
+    /// set. When None this defaults to no composite score and the samples are not ranked.
+    pub objective: Option<MultiObjective>,
+    /// A custom objective function used to rank the samples and select the best parameter set
+    /// instead of a built-in metric. The standard metrics are still calculated. This cannot be
+    /// used together with [`CalibrationInputs::objective`]. When None this defaults to no custom
+    /// objective.
+    pub objective_fn: Option<ObjectiveFn>,
+    /// How to export the calibration charts. When None this defaults to
+    /// [`CalibrationOutputFormat::PngFiles`].
+    pub output_format: Option<CalibrationOutputFormat>,
+    /// The seed of the random number generator used by the Latin Hypercube sampling. The same seed
+    /// always generates the same parameter sets, so that a calibration can be reproduced. When
+    /// None this defaults to a random seed.
+    pub seed: Option<u64>,
+    /// The strategy used to sample the parameter sets. When None this defaults to
+    /// [`SamplingStrategy::LhsClassic`].
+    pub sampling_strategy: Option<SamplingStrategy>,
+    /// A function called every time a model completes its run (successfully or not), for example
+    /// to draw a progress bar. When None this defaults to no callback.
+    pub progress: Option<ProgressFn>,
+    /// The maximum number of threads used to run the models and export the charts. The models run
+    /// in a dedicated thread pool, so that the global thread pool used by the rest of the program is
+    /// not affected. When None this defaults to the global thread pool with one thread per CPU.
+    pub max_threads: Option<usize>,
+    /// The colours of the exported charts. When None this defaults to [`ChartTheme::default`].
+    pub chart_theme: Option<ChartTheme>,
+    /// The optional metrics to calculate for each model, such as the 2009 and 2012 Kling-Gupta
+    /// efficiencies. The enabled metrics are exported and plotted in the metric vs. parameter
+    /// charts. When None this defaults to [`OptionalMetrics::default()`].
+    pub optional_metrics: Option<OptionalMetrics>,
+}
+
+/// The format of the charts exported by the calibration.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CalibrationOutputFormat {
+    #[default]
+    /// Save each chart as a separate PNG file.
+    PngFiles,
+    /// Save each chart as a separate SVG file.
+    SvgFiles,
+    /// Save all the charts in a single multi-page PDF document named `Charts.pdf`. Each chart is a
+    /// page of the document. Only available with the `pdf` feature.
+    #[cfg(feature = "pdf")]
+    SinglePdf,
+}
+
+/// The settings of the differential-evolution optimiser used by
+/// [`crate::calibration::Calibration::optimise`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct OptimiserSettings {
+    /// The number of parameter sets in each generation. This must be at least 4. When None this
+    /// defaults to 30.
+    pub population_size: Option<usize>,
+    /// The number of generations to evolve the population. When None this defaults to 50.
+    pub generations: Option<usize>,
+    /// The factor (0-2) used to scale the difference between two parameter sets when a new
+    /// parameter set is generated. When None this defaults to 0.8.
+    pub mutation_factor: Option<f64>,
+    /// The probability (0-1) that a parameter of a new parameter set is taken from the mutated
+    /// parameter set instead of the current one. When None this defaults to 0.9.
+    pub crossover_rate: Option<f64>,
+}
+
+impl OptimiserSettings {
+    /// Check that the settings are within their valid ranges.
+    ///
+    /// returns: `Result<(), String>`
+    pub fn validate(&self) -> Result<(), String> {
+        if self.population_size.is_some_and(|size| size < 4) {
+            return Err("the population size must be at least 4".to_string());
+        }
+        if self.generations == Some(0) {
+            return Err("the number of generations must be larger than 0".to_string());
+        }
+        if let Some(factor) = self.mutation_factor.filter(|f| !(*f > 0.0 && *f <= 2.0)) {
+            return Err(format!(
+                "the mutation factor must be between 0 and 2, but {} was given",
+                factor
+            ));
+        }
+        if let Some(rate) = self.crossover_rate.filter(|r| !(0.0..=1.0).contains(r)) {
+            return Err(format!(
+                "the crossover rate must be between 0 and 1, but {} was given",
+                rate
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The strategy used to generate the parameter sets to try during the calibration.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SamplingStrategy {
+    #[default]
+    /// Latin Hypercube sampling with points randomly placed within each interval.
+    LhsClassic,
+    /// Latin Hypercube sampling with points placed at the centre of each interval.
+    LhsCentered,
+    /// Latin Hypercube sampling maximising the minimum distance between the points. This gives a
+    /// better coverage of the parameter space when the sample size is small.
+    LhsMaximin,
+    /// Uniform random sampling without any stratification.
+    Random,
+}
+
+impl fmt::Display for SamplingStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SamplingStrategy::LhsClassic => "classic Latin-Hypercube",
+            SamplingStrategy::LhsCentered => "centred Latin-Hypercube",
+            SamplingStrategy::LhsMaximin => "maximin Latin-Hypercube",
+            SamplingStrategy::Random => "uniform random sampling",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    use crate::inputs::{CatchmentData, InitialStoreLevels, StoreLevels};
+    use crate::inputs::{ModelPeriod, RunOffUnit, SamplingStrategy};
+    #[cfg(feature = "serde")]
+    use crate::parameter::Parameter;
+    use chrono::NaiveDate;
+    use float_cmp::assert_approx_eq;
+
+    #[test]
+    fn test_sampling_strategy_display() {
+        assert_eq!(SamplingStrategy::default().to_string(), "classic Latin-Hypercube");
+        assert_eq!(SamplingStrategy::Random.to_string(), "uniform random sampling");
+    }
+
+    #[test]
+    fn test_run_off_unit_conversion() {
+        // 2.5 mm over 1 km2 in one day is 2500 m³/d
+        let run_off = 2.5;
+        assert_eq!(run_off * RunOffUnit::NoConversion.conv_factor(), 2.5);
+        assert_eq!(run_off * RunOffUnit::CubicMetrePerDay.conv_factor(), 2500.0);
+        assert_eq!(run_off * RunOffUnit::MlPerDay.conv_factor(), 2.5);
+        assert_approx_eq!(
+            f64,
+            run_off * RunOffUnit::CubicMetrePerSecond.conv_factor(),
+            0.028935185185185185,
+            ulps = 2
+        );
+        // 2500 m³/d is 2.5e6 l/d
+        assert_approx_eq!(
+            f64,
+            run_off * RunOffUnit::LitrePerSecond.conv_factor(),
+            28.935185185185187,
+            ulps = 2
+        );
+        assert_eq!(RunOffUnit::LitrePerSecond.unit_label(), "L/s");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialize_catchment_data() {
+        let data: CatchmentData = serde_json::from_str(
+            r#"{
+                "area": 2.5,
+                "x1": 31.0,
+                "x2": 3.47,
+                "x3": 32.0,
+                "x4": 2.1,
+                "x5": 0.55,
+                "x6": 5.3,
+                "store_levels": {"Absolute": {"production_store": 10.0, "routing_store": 5.0, "exponential_store": 0.0}}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(data.area, 2.5);
+        assert_eq!(data.x1.value(), 31.0);
+        assert_eq!(data.x2.value(), 3.47);
+        assert_eq!(data.x6.value(), 5.3);
+        assert_eq!(data.store_levels, Some(StoreLevels::absolute(10.0, 5.0, 0.0)));
+        assert!(data.uh_exponent.is_none());
+
+        let levels: InitialStoreLevels = serde_json::from_str(
+            r#"{"Fraction": {"production_store": 0.3, "routing_store": 0.5, "exponential_store": 0.0}}"#,
+        )
+        .unwrap();
+        assert_eq!(levels, InitialStoreLevels::default());
+
+        // out-of-range parameter
+        let error = serde_json::from_str::<CatchmentData>(
+            r#"{"area": 1.0, "x1": 0.0, "x2": 3.47, "x3": 32.0, "x4": 2.1, "x5": 0.55, "x6": 5.3}"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("The production store capacity (X1) must be larger than its minimum threshold (0.01)"));
+    }
+
+    #[test]
+    fn test_model_period_split() {
+        let period = ModelPeriod::new(
+            NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2000, 1, 11).unwrap(),
+        )
+        .unwrap();
+
+        let (first, second) = period.split(0.7).unwrap();
+        assert_eq!(first.start, period.start);
+        assert_eq!(first.end, NaiveDate::from_ymd_opt(2000, 1, 8).unwrap());
+        assert_eq!(second.start, NaiveDate::from_ymd_opt(2000, 1, 9).unwrap());
+        assert_eq!(second.end, period.end);
+
+        // invalid fractions
+        assert!(period.split(0.0).is_err());
+        assert!(period.split(1.0).is_err());
+        assert!(period.split(f64::NAN).is_err());
+
+        // the second period would be a single day
+        assert!(period.split(0.95).is_err());
+    }
+
+    #[test]
+    fn test_model_period_display() {
+        let period = ModelPeriod::new(
+            NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2000, 12, 31).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(period.to_string(), "2000-01-01 to 2000-12-31");
+        assert_eq!(format!("{:?}", period), "2000-01-01-2000-12-31");
+    }
 }
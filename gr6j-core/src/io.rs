@@ -0,0 +1,186 @@
+use crate::config::ForcingData;
+use crate::error::LoadModelError;
+use chrono::NaiveDate;
+use std::fs::File;
+use std::path::Path;
+
+/// The layout of a CSV file with the forcing data. The columns are identified by the names in
+/// the header row and their order does not matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForcingCsvConfig {
+    /// The name of the column with the dates. When None this defaults to `Date`.
+    pub date_column: Option<String>,
+    /// The format of the dates. When None this defaults to `%Y-%m-%d`.
+    pub date_format: Option<String>,
+    /// The name of the column with the total precipitation (mm/day). When None this defaults to
+    /// `P`.
+    pub precipitation_column: Option<String>,
+    /// The name of the column with the potential evapotranspiration (mm/day). When None this
+    /// defaults to `E`.
+    pub evapotranspiration_column: Option<String>,
+    /// The name of the column with the observed run-off. When None the observed run-off is not
+    /// loaded.
+    pub observed_runoff_column: Option<String>,
+    /// The value used in the file for missing data. Missing values are converted to NaN. When
+    /// None this defaults to `NA`.
+    pub na_value: Option<String>,
+}
+
+impl ForcingCsvConfig {
+    /// The layout of the forcing files exported from the airGR R package, such as the dataset
+    /// of the L0123001 catchment bundled with the crate tests.
+    ///
+    /// returns: ForcingCsvConfig
+    pub fn airgr() -> Self {
+        ForcingCsvConfig {
+            date_column: Some("DatesR".to_string()),
+            date_format: Some("%d/%m/%Y".to_string()),
+            precipitation_column: Some("P".to_string()),
+            evapotranspiration_column: Some("E".to_string()),
+            observed_runoff_column: Some("Qmm".to_string()),
+            na_value: Some("NA".to_string()),
+        }
+    }
+}
+
+/// Load the forcing data from a CSV file. The first row must be the header with the column names.
+///
+/// # Arguments
+///
+/// * `path`: The path to the CSV file.
+/// * `config`: The layout of the file.
+///
+/// returns: `Result<ForcingData, LoadModelError>`
+pub fn load_forcing_csv(path: &Path, config: ForcingCsvConfig) -> Result<ForcingData, LoadModelError> {
+    let date_column = config.date_column.as_deref().unwrap_or("Date");
+    let date_format = config.date_format.as_deref().unwrap_or("%Y-%m-%d");
+    let precipitation_column = config.precipitation_column.as_deref().unwrap_or("P");
+    let evapotranspiration_column = config.evapotranspiration_column.as_deref().unwrap_or("E");
+    let na_value = config.na_value.as_deref().unwrap_or("NA");
+
+    let file = File::open(path).map_err(|e| {
+        LoadModelError::InvalidForcingFile(format!("the file '{}' cannot be opened ({})", path.display(), e))
+    })?;
+    let mut rdr = csv::Reader::from_reader(file);
+
+    // find the column indexes
+    let headers = rdr
+        .headers()
+        .map_err(|e| LoadModelError::InvalidForcingFile(e.to_string()))?
+        .clone();
+    let column_index = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim() == name)
+            .ok_or_else(|| LoadModelError::InvalidForcingFile(format!("the column '{}' does not exist", name)))
+    };
+    let date_index = column_index(date_column)?;
+    let precipitation_index = column_index(precipitation_column)?;
+    let evapotranspiration_index = column_index(evapotranspiration_column)?;
+    let observed_runoff_index = match config.observed_runoff_column.as_deref() {
+        None => None,
+        Some(name) => Some(column_index(name)?),
+    };
+
+    let mut time: Vec<NaiveDate> = vec![];
+    let mut precipitation: Vec<f64> = vec![];
+    let mut evapotranspiration: Vec<f64> = vec![];
+    let mut observed_runoff: Vec<f64> = vec![];
+    for (row, result) in rdr.records().enumerate() {
+        let record = result.map_err(|e| LoadModelError::InvalidForcingFile(e.to_string()))?;
+        let invalid = |column: &str| {
+            LoadModelError::InvalidForcingFile(format!("the value of '{}' on row #{} is not valid", column, row + 1))
+        };
+        let value = |index: usize, column: &str| match record.get(index).map(|v| v.trim()) {
+            Some(v) if v == na_value => Ok(f64::NAN),
+            Some(v) => v.parse::<f64>().map_err(|_| invalid(column)),
+            None => Err(invalid(column)),
+        };
+
+        let date = record.get(date_index).ok_or_else(|| invalid(date_column))?;
+        time.push(NaiveDate::parse_from_str(date.trim(), date_format).map_err(|_| invalid(date_column))?);
+        precipitation.push(value(precipitation_index, precipitation_column)?);
+        evapotranspiration.push(value(evapotranspiration_index, evapotranspiration_column)?);
+        if let (Some(index), Some(name)) = (observed_runoff_index, config.observed_runoff_column.as_deref()) {
+            observed_runoff.push(value(index, name)?);
+        }
+    }
+
+    Ok(ForcingData {
+        time,
+        precipitation,
+        evapotranspiration,
+        observed_runoff: observed_runoff_index.map(|_| observed_runoff),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{load_forcing_csv, ForcingCsvConfig};
+    use chrono::NaiveDate;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn dataset_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("test_data")
+            .join("airGR_L0123001_dataset.csv")
+    }
+
+    #[test]
+    fn test_load_airgr_dataset() {
+        let forcing = load_forcing_csv(&dataset_path(), ForcingCsvConfig::airgr()).unwrap();
+        assert_eq!(forcing.time[0], NaiveDate::from_ymd_opt(1984, 1, 1).unwrap());
+        assert_eq!(forcing.precipitation[0], 4.1);
+        assert_eq!(forcing.evapotranspiration[1], 0.2);
+        assert_eq!(forcing.precipitation.len(), forcing.time.len());
+        assert_eq!(forcing.evapotranspiration.len(), forcing.time.len());
+
+        // the missing observed values are NaN
+        let observed = forcing.observed_runoff.unwrap();
+        assert_eq!(observed.len(), forcing.time.len());
+        assert_eq!(observed[0], 0.6336);
+        assert!(observed.iter().any(|q| q.is_nan()));
+
+        // the observed run-off is optional
+        let config = ForcingCsvConfig {
+            observed_runoff_column: None,
+            ..ForcingCsvConfig::airgr()
+        };
+        assert!(load_forcing_csv(&dataset_path(), config)
+            .unwrap()
+            .observed_runoff
+            .is_none());
+    }
+
+    #[test]
+    fn test_invalid_forcing_file() {
+        let config = ForcingCsvConfig {
+            precipitation_column: Some("Precipitation".to_string()),
+            ..ForcingCsvConfig::airgr()
+        };
+        assert!(load_forcing_csv(&dataset_path(), config).is_err());
+        assert!(load_forcing_csv(&dataset_path().with_extension("txt"), ForcingCsvConfig::airgr()).is_err());
+
+        // default layout with a custom missing value and a wrong date
+        let file = env::temp_dir().join("gr6j_test_invalid_forcing_file.csv");
+        fs::write(&file, "Date,P,E\n2000-01-01,1.0,-999\n2000-01-02,2.0,1.0\n").unwrap();
+        let config = ForcingCsvConfig {
+            date_column: None,
+            date_format: None,
+            precipitation_column: None,
+            evapotranspiration_column: None,
+            observed_runoff_column: None,
+            na_value: Some("-999".to_string()),
+        };
+        let forcing = load_forcing_csv(&file, config.clone()).unwrap();
+        assert!(forcing.evapotranspiration[0].is_nan());
+        assert_eq!(forcing.precipitation, [1.0, 2.0]);
+
+        fs::write(&file, "Date,P,E\n01/01/2000,1.0,1.0\n").unwrap();
+        assert!(load_forcing_csv(&file, config).is_err());
+        fs::remove_file(&file).unwrap();
+    }
+}
@@ -1,10 +1,15 @@
 pub mod calibration;
 mod chart;
+pub mod config;
 pub mod error;
 pub mod inputs;
+pub mod io;
 pub mod metric;
 pub mod model;
 pub mod outputs;
 pub mod parameter;
+#[cfg(feature = "pdf")]
+mod pdf;
+pub mod signatures;
 pub mod unit_hydrograph;
 pub mod utils;
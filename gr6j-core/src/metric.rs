@@ -1,17 +1,147 @@
-use crate::utils::{Fdc, NaNVec};
+use crate::error::MetricError;
+use crate::utils::{day_of_year_mean, format_float, Fdc, NaNVec, CSV_PRECISION};
+use chrono::{Datelike, NaiveDate};
 use csv::Writer;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::cmp::Ordering;
 use std::fs::File;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// The method to use to calculate the Kling-Gupta coefficient
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum KlingGuptaMethod {
     Y2009,
     Y2012,
     NonParametric,
 }
 
+/// The components of the Kling-Gupta efficiency. These show whether a poor efficiency is caused by
+/// the correlation, the variability bias or the mean bias.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct KgeComponents {
+    /// The Spearman's rank correlation coefficient between the observed and simulated flow.
+    pub r: f64,
+    /// The variability bias. The definition depends on the [`KlingGuptaMethod`].
+    pub alpha: f64,
+    /// The mean bias as ratio between the simulated and observed mean flow.
+    pub beta: f64,
+    /// The Kling-Gupta efficiency calculated from the three components.
+    pub kge: f64,
+}
+
+/// The transformation to apply to the observed and simulated flow before calculating a metric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowTransform {
+    /// Subtract the day-of-year mean of the observed flow (the climatology) from both series. The
+    /// metric then only rewards the model for reproducing the flow anomalies and not for merely
+    /// getting the seasonal cycle right.
+    DeseasonalisedAnomaly,
+}
+
+impl FlowTransform {
+    /// Apply the transformation to the observed and simulated flow.
+    ///
+    /// # Arguments
+    ///
+    /// * `time`: The time vector.
+    /// * `observed`: The observed flow series.
+    /// * `simulated`: The simulated flow series.
+    ///
+    /// returns: `(Vec<f64>, Vec<f64>)`
+    pub fn apply(&self, time: &[NaiveDate], observed: &[f64], simulated: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        match self {
+            FlowTransform::DeseasonalisedAnomaly => {
+                let climatology = day_of_year_mean(time, observed, time);
+                let anomaly = |series: &[f64]| series.iter().zip(&climatology).map(|(q, c)| q - c).collect();
+                (anomaly(observed), anomaly(simulated))
+            }
+        }
+    }
+}
+
+/// The benchmark series the model is compared against in
+/// [`CalibrationMetric::benchmark_efficiency`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Benchmark {
+    /// The mean of the observed flow. The efficiency is then the Nash-Sutcliffe efficiency.
+    Mean,
+    /// The day-of-year mean of the observed flow (the climatology). This is a harder baseline for
+    /// catchments with a strong seasonal cycle.
+    DayOfYearMean,
+}
+
+/// The metrics that can be used as objective to rank the calibration samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricType {
+    NashSutcliffe,
+    LogNashSutcliffe,
+    KlingGupta2009,
+    KlingGupta2012,
+    NonParametricKlingGupta,
+    Rmse,
+    VolumeError,
+    AnomalyNashSutcliffe,
+    Pearson,
+    RSquared,
+    PercentBias,
+}
+
+/// A user-defined objective function. This receives the observed and simulated run-off (in this
+/// order) and returns a score to minimise (for example `1 - NSE`). The function is called from
+/// the parallel loop and must therefore be `Send` and `Sync`.
+pub type ObjectiveFn = Arc<dyn Fn(&[f64], &[f64]) -> f64 + Send + Sync>;
+
+/// A weighted combination of metrics to use as a single calibration objective (for example
+/// 0.5·NSE + 0.5·log-NSE to balance the fit of high and low flows).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiObjective {
+    /// The metrics and their weights.
+    pub weights: Vec<(MetricType, f64)>,
+}
+
+impl MultiObjective {
+    /// Check that at least one metric is given and that the weights are finite and not negative.
+    ///
+    /// returns: `Result<(), String>`
+    pub fn validate(&self) -> Result<(), String> {
+        if self.weights.is_empty() {
+            return Err("the multi-objective must contain at least one metric".to_string());
+        }
+        if let Some((metric_type, weight)) = self.weights.iter().find(|(_, w)| !w.is_finite() || *w < 0.0) {
+            return Err(format!(
+                "the weight of {:?} must be a positive number, but {} was given",
+                metric_type, weight
+            ));
+        }
+        Ok(())
+    }
+
+    /// Calculate the composite score as the weighted sum of the distance of each metric from its
+    /// ideal value. A score of 0 means that all metrics are at their ideal value; the smaller the
+    /// score, the better the simulation. The score is NaN when one of the metrics is not available.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics`: The metrics of a simulation.
+    ///
+    /// returns: `f64`
+    pub fn score(&self, metrics: &CalibrationMetric) -> f64 {
+        self.weights
+            .iter()
+            .map(|(metric_type, weight)| {
+                let metric = metrics.get(*metric_type);
+                weight * (metric.value - metric.ideal_value).abs()
+            })
+            .sum()
+    }
+}
+
 //. A metric data
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Metric {
     /// The metric name
     pub name: String,
@@ -23,6 +153,7 @@ pub struct Metric {
 
 /// The type of calibration metric to calculate.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CalibrationMetric {
     /// The Nash-Sutcliffe efficiency. An efficiency of 1 gives a perfect match of simulated to
     /// observed data. An efficiency of 0 indicates that the model predictions are as accurate as
@@ -54,7 +185,35 @@ pub struct CalibrationMetric {
     /// indicates that the simulation generates less volume than the observed. A positive value
     /// indicates instead that the simulation generates more volume than the observed.
     pub volume_error: Metric,
+    /// The percent bias (PBIAS) as the sum of the differences between the simulated and observed
+    /// flow divided by the total observed flow. A value of 0.0 suggests a good simulated flow. A
+    /// positive value indicates that the model overestimates the flow, a negative value that it
+    /// underestimates it.
+    pub percent_bias: Metric,
+    /// The fraction of days (excluding days with missing values) when the simulated run-off
+    /// is larger than, equal to or smaller than the observed run-off. This helps to understand
+    /// whether the volume error is caused by a few large errors or by a consistent bias.
+    pub error_sign_fractions: (f64, f64, f64),
+    /// The Nash-Sutcliffe efficiency calculated on the flow anomalies (see
+    /// [`FlowTransform::DeseasonalisedAnomaly`]). This is stricter than
+    /// [`CalibrationMetric::nash_sutcliffe`] for catchments with a strong seasonal cycle. This is only
+    /// calculated with [`CalibrationMetric::new_with_time`] and is NaN otherwise.
+    pub anomaly_nash_sutcliffe: Metric,
+    /// The Pearson correlation coefficient between the observed and simulated flow. A value of 1
+    /// indicates a perfect positive linear relationship.
+    pub pearson: Metric,
+    /// The coefficient of determination, calculated as the square of the Pearson correlation
+    /// coefficient. A value of 1 indicates that the simulated flow explains all the variance of
+    /// the observed flow.
+    pub r_squared: Metric,
+    /// The components of [`CalibrationMetric::kling_gupta2009`].
+    kling_gupta2009_components: Option<KgeComponents>,
+    /// The components of [`CalibrationMetric::kling_gupta2012`].
+    kling_gupta2012_components: Option<KgeComponents>,
+    /// The components of [`CalibrationMetric::non_parametric_kling_gupta`].
+    non_parametric_kling_gupta_components: Option<KgeComponents>,
     /// A structure controlling whether to calculate additional metrics.
+    #[cfg_attr(feature = "serde", serde(skip))]
     optional_metrics: OptionalMetrics,
 }
 
@@ -62,9 +221,35 @@ pub struct CalibrationMetric {
 #[derive(Default, Debug, Clone)]
 pub struct OptionalMetrics {
     /// Set this to `true` to calculate the 2009 Kling-Gupta efficiency.
-    kling_gupta2009: bool,
+    pub kling_gupta2009: bool,
     /// Set this to `true` to calculate the 2012 Kling-Gupta efficiency.
-    kling_gupta2012: bool,
+    pub kling_gupta2012: bool,
+    /// This is `true` when the time vector is available to calculate the anomaly Nash-Sutcliffe.
+    pub(crate) anomaly_nash_sutcliffe: bool,
+    /// The value to add to the flow before applying the logarithm in the Nash-Sutcliffe with log
+    /// flows, so that the days with zero flow are not excluded. A common choice is one hundredth
+    /// of the mean observed flow. When None this defaults to no offset and the days with zero or
+    /// negative flow are excluded.
+    pub epsilon: Option<f64>,
+}
+
+impl OptionalMetrics {
+    /// Apply the logarithm used by the Nash-Sutcliffe with log flows.
+    ///
+    /// # Arguments
+    ///
+    /// * `values`: The flow series.
+    ///
+    /// returns: `Vec<f64>`
+    fn log(&self, values: &[f64]) -> Vec<f64> {
+        match self.epsilon {
+            None => NaNVec(values).log(),
+            Some(epsilon) => {
+                let shifted: Vec<f64> = values.iter().map(|x| x + epsilon).collect();
+                NaNVec(shifted.as_slice()).log()
+            }
+        }
+    }
 }
 
 impl<'a> CalibrationMetric {
@@ -78,30 +263,98 @@ impl<'a> CalibrationMetric {
     /// [`OptionalMetrics::default()`]. Some metrics are similar and are not calculated by default
     /// and must be enabled by the user.
     ///
-    /// returns: `Result<CalibrationMetric, String>`
+    /// returns: `Result<CalibrationMetric, MetricError>`
     pub fn new(
         observed: &'a [f64],
         simulated: &'a [f64],
         optional_metrics: Option<OptionalMetrics>,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, MetricError> {
+        CalibrationMetricContext::new(observed, optional_metrics).metrics(simulated)
+    }
+
+    /// Calculate the efficiency metrics between two flow time series only using the time steps
+    /// selected by a mask, for example to only include the validation period or the summer months.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The observed flow series.
+    /// * `simulated`: The simulated flow series.
+    /// * `mask`: Whether to include each time step. This must have the same length as the series.
+    /// * `optional_metrics`: The optional metric to calculate. If None this defaults to
+    ///   [`OptionalMetrics::default()`].
+    ///
+    /// returns: `Result<CalibrationMetric, MetricError>`
+    pub fn new_masked(
+        observed: &'a [f64],
+        simulated: &'a [f64],
+        mask: &'a [bool],
+        optional_metrics: Option<OptionalMetrics>,
+    ) -> Result<Self, MetricError> {
         if observed.len() != simulated.len() {
-            return Err(format!(
-                "The vector must have the same length. Observed has {} values and simulated has {} values",
-                observed.len(),
-                simulated.len()
-            ));
+            return Err(MetricError::LengthMismatch {
+                observed: observed.len(),
+                simulated: simulated.len(),
+            });
         }
-        let optional_metrics = optional_metrics.unwrap_or_default();
-        let mut metrics = Self {
+        if observed.len() != mask.len() {
+            return Err(MetricError::MaskLengthMismatch {
+                mask: mask.len(),
+                series: observed.len(),
+            });
+        }
+        let (observed, simulated): (Vec<f64>, Vec<f64>) = observed
+            .iter()
+            .zip(simulated)
+            .zip(mask)
+            .filter(|(_, include)| **include)
+            .map(|((obs, sim), _)| (*obs, *sim))
+            .unzip();
+        if observed.is_empty() {
+            return Err(MetricError::EmptyMask());
+        }
+
+        Self::new(&observed, &simulated, optional_metrics)
+    }
+
+    /// Calculate the efficiency metrics between two flow time series, including the metrics that
+    /// require the dates of the series, such as [`CalibrationMetric::anomaly_nash_sutcliffe`].
+    ///
+    /// # Arguments
+    ///
+    /// * `time`: The time vector.
+    /// * `observed`: The observed flow series.
+    /// * `simulated`: The simulated flow series.
+    /// * `optional_metrics`: The optional metric to calculate. If None this defaults to
+    ///   [`OptionalMetrics::default()`].
+    ///
+    /// returns: `Result<CalibrationMetric, MetricError>`
+    pub fn new_with_time(
+        time: &'a [NaiveDate],
+        observed: &'a [f64],
+        simulated: &'a [f64],
+        optional_metrics: Option<OptionalMetrics>,
+    ) -> Result<Self, MetricError> {
+        CalibrationMetricContext::new_with_time(time, observed, optional_metrics)?.metrics(simulated)
+    }
+
+    /// Create the metrics with all values set to NaN.
+    ///
+    /// # Arguments
+    ///
+    /// * `optional_metrics`: The optional metric to calculate.
+    ///
+    /// returns: `CalibrationMetric`
+    fn unavailable(optional_metrics: OptionalMetrics) -> Self {
+        CalibrationMetric {
             nash_sutcliffe: Metric {
                 name: "Nash-Sutcliffe".to_string(),
                 ideal_value: 1.0,
-                value: Self::nse(observed, simulated),
+                value: f64::NAN,
             },
             log_nash_sutcliffe: Metric {
                 name: "Nash-Sutcliffe with log flows".to_string(),
                 ideal_value: 1.0,
-                value: Self::nse(NaNVec(observed).log().as_slice(), NaNVec(simulated).log().as_slice()),
+                value: f64::NAN,
             },
             kling_gupta2009: Metric {
                 name: "Kling-Gupta (2009)".to_string(),
@@ -116,28 +369,138 @@ impl<'a> CalibrationMetric {
             non_parametric_kling_gupta: Metric {
                 name: "Non-parametric Kling-Gupta".to_string(),
                 ideal_value: 1.0,
-                value: Self::kge(observed, simulated, KlingGuptaMethod::NonParametric),
+                value: f64::NAN,
             },
             rmse: Metric {
                 name: "Root-mean-square error".to_string(),
                 ideal_value: 0.0,
-                value: Self::rmse(observed, simulated),
+                value: f64::NAN,
             },
             volume_error: Metric {
                 name: "Volume error".to_string(),
                 ideal_value: 0.0,
-                value: Self::volume_error(observed, simulated),
+                value: f64::NAN,
             },
-            optional_metrics: optional_metrics.clone(),
-        };
+            percent_bias: Metric {
+                name: "Percent bias".to_string(),
+                ideal_value: 0.0,
+                value: f64::NAN,
+            },
+            error_sign_fractions: (f64::NAN, f64::NAN, f64::NAN),
+            anomaly_nash_sutcliffe: Metric {
+                name: "Nash-Sutcliffe with flow anomalies".to_string(),
+                ideal_value: 1.0,
+                value: f64::NAN,
+            },
+            pearson: Metric {
+                name: "Pearson correlation".to_string(),
+                ideal_value: 1.0,
+                value: f64::NAN,
+            },
+            r_squared: Metric {
+                name: "Coefficient of determination".to_string(),
+                ideal_value: 1.0,
+                value: f64::NAN,
+            },
+            kling_gupta2009_components: None,
+            kling_gupta2012_components: None,
+            non_parametric_kling_gupta_components: None,
+            optional_metrics,
+        }
+    }
 
-        if optional_metrics.kling_gupta2009 {
-            metrics.kling_gupta2009.value = Self::kge(observed, simulated, KlingGuptaMethod::Y2009);
+    /// Get the correlation, variability bias and mean bias that make up a Kling-Gupta efficiency.
+    ///
+    /// # Arguments
+    ///
+    /// * `method`: The Kling-Gupta method.
+    ///
+    /// returns: `Option<KgeComponents>`. This is None when the Kling-Gupta efficiency was not
+    /// calculated (see [`OptionalMetrics`]).
+    pub fn kge_components(&self, method: KlingGuptaMethod) -> Option<KgeComponents> {
+        match method {
+            KlingGuptaMethod::Y2009 => self.kling_gupta2009_components,
+            KlingGuptaMethod::Y2012 => self.kling_gupta2012_components,
+            KlingGuptaMethod::NonParametric => self.non_parametric_kling_gupta_components,
         }
-        if optional_metrics.kling_gupta2012 {
-            metrics.kling_gupta2012.value = Self::kge(observed, simulated, KlingGuptaMethod::Y2012);
+    }
+
+    /// Get a metric by its type.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric_type`: The metric to get.
+    ///
+    /// returns: `&Metric`
+    pub fn get(&self, metric_type: MetricType) -> &Metric {
+        match metric_type {
+            MetricType::NashSutcliffe => &self.nash_sutcliffe,
+            MetricType::LogNashSutcliffe => &self.log_nash_sutcliffe,
+            MetricType::KlingGupta2009 => &self.kling_gupta2009,
+            MetricType::KlingGupta2012 => &self.kling_gupta2012,
+            MetricType::NonParametricKlingGupta => &self.non_parametric_kling_gupta,
+            MetricType::Rmse => &self.rmse,
+            MetricType::VolumeError => &self.volume_error,
+            MetricType::AnomalyNashSutcliffe => &self.anomaly_nash_sutcliffe,
+            MetricType::Pearson => &self.pearson,
+            MetricType::RSquared => &self.r_squared,
+            MetricType::PercentBias => &self.percent_bias,
         }
-        Ok(metrics)
+    }
+
+    /// Whether a metric was calculated. The optional metrics are only calculated when they are
+    /// enabled (see [`OptionalMetrics`]); the other metrics are always calculated.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric_type`: The metric to check.
+    ///
+    /// returns: `bool`
+    pub fn is_calculated(&self, metric_type: MetricType) -> bool {
+        match metric_type {
+            MetricType::KlingGupta2009 => self.optional_metrics.kling_gupta2009,
+            MetricType::KlingGupta2012 => self.optional_metrics.kling_gupta2012,
+            MetricType::AnomalyNashSutcliffe => self.optional_metrics.anomaly_nash_sutcliffe,
+            _ => true,
+        }
+    }
+
+    /// Get all the calculated metrics, for example to loop over their name, value and ideal value.
+    /// The optional metrics are only included when they are enabled (see [`OptionalMetrics`]).
+    /// These are the metrics exported to the CSV files.
+    ///
+    /// returns: `Vec<&Metric>`
+    pub fn all_metrics(&self) -> Vec<&Metric> {
+        let mut metrics = vec![&self.nash_sutcliffe, &self.log_nash_sutcliffe];
+        if self.optional_metrics.kling_gupta2009 {
+            metrics.push(&self.kling_gupta2009);
+        }
+        if self.optional_metrics.kling_gupta2012 {
+            metrics.push(&self.kling_gupta2012);
+        }
+        metrics.push(&self.non_parametric_kling_gupta);
+        metrics.push(&self.rmse);
+        metrics.push(&self.volume_error);
+        metrics.push(&self.percent_bias);
+        metrics.push(&self.pearson);
+        metrics.push(&self.r_squared);
+        if self.optional_metrics.anomaly_nash_sutcliffe {
+            metrics.push(&self.anomaly_nash_sutcliffe);
+        }
+        metrics
+    }
+
+    /// The fractions in [`CalibrationMetric::error_sign_fractions`] with their name and ideal value
+    /// to export them to CSV alongside the other metrics.
+    ///
+    /// returns: `[(&str, f64, f64); 3]`
+    fn error_sign_fraction_rows(&self) -> [(&'static str, f64, f64); 3] {
+        let (over, equal, under) = self.error_sign_fractions;
+        [
+            ("Fraction of over-estimated days", over, 0.0),
+            ("Fraction of exact days", equal, 1.0),
+            ("Fraction of under-estimated days", under, 0.0),
+        ]
     }
 
     /// Append the metric values to a CSV file as row.
@@ -146,22 +509,27 @@ impl<'a> CalibrationMetric {
     ///
     /// * `wtr`: The file writer.
     /// * `index`: A string to write as index to identify the row number.
+    /// * `precision`: The number of decimal places of the metric values. When None this defaults
+    ///   to [`CSV_PRECISION`].
     ///
     /// returns: Result<(), csv::Error>
-    pub fn append_row_to_csv(&self, wtr: &mut Writer<File>, index: Option<String>) -> Result<(), csv::Error> {
+    pub fn append_row_to_csv(
+        &self,
+        wtr: &mut Writer<File>,
+        index: Option<String>,
+        precision: Option<usize>,
+    ) -> Result<(), csv::Error> {
+        let precision = precision.unwrap_or(CSV_PRECISION);
         let mut row = vec![];
         if let Some(i) = index {
             row.push(i);
         }
-        row.push(self.nash_sutcliffe.value.to_string());
-        row.push(self.log_nash_sutcliffe.value.to_string());
-        if self.optional_metrics.kling_gupta2009 {
-            row.push(self.kling_gupta2009.value.to_string());
-        }
-        if self.optional_metrics.kling_gupta2012 {
-            row.push(self.kling_gupta2012.value.to_string());
-        }
-        row.push(self.non_parametric_kling_gupta.value.to_string());
+        row.extend(self.all_metrics().iter().map(|m| format_float(m.value, precision)));
+        row.extend(
+            self.error_sign_fraction_rows()
+                .iter()
+                .map(|(_, value, _)| format_float(*value, precision)),
+        );
         wtr.write_record(row)?;
         wtr.flush()?;
 
@@ -181,15 +549,12 @@ impl<'a> CalibrationMetric {
         if let Some(i) = index {
             row.push(i);
         }
-        row.push(self.nash_sutcliffe.name.to_string());
-        row.push(self.log_nash_sutcliffe.name.to_string());
-        if self.optional_metrics.kling_gupta2009 {
-            row.push(self.kling_gupta2009.name.to_string());
-        }
-        if self.optional_metrics.kling_gupta2012 {
-            row.push(self.kling_gupta2012.name.to_string());
-        }
-        row.push(self.non_parametric_kling_gupta.name.to_string());
+        row.extend(self.all_metrics().iter().map(|m| m.name.to_string()));
+        row.extend(
+            self.error_sign_fraction_rows()
+                .iter()
+                .map(|(name, _, _)| name.to_string()),
+        );
         wtr.write_record(row)?;
         wtr.flush()?;
 
@@ -201,40 +566,28 @@ impl<'a> CalibrationMetric {
     /// # Arguments
     ///
     /// * `destination`: The destination CSV file.
+    /// * `precision`: The number of decimal places of the metric values. When None this defaults
+    ///   to [`CSV_PRECISION`].
     ///
     /// returns: Result<(), csv::Error>
-    pub fn to_csv(&self, destination: PathBuf) -> Result<(), csv::Error> {
+    pub fn to_csv(&self, destination: PathBuf, precision: Option<usize>) -> Result<(), csv::Error> {
+        let precision = precision.unwrap_or(CSV_PRECISION);
         let mut wtr = Writer::from_path(destination)?;
         wtr.write_record(["Metric", "Value", "Ideal value"])?;
-        wtr.write_record([
-            self.nash_sutcliffe.name.to_string(),
-            self.nash_sutcliffe.value.to_string(),
-            self.nash_sutcliffe.ideal_value.to_string(),
-        ])?;
-        wtr.write_record([
-            self.log_nash_sutcliffe.name.to_string(),
-            self.log_nash_sutcliffe.value.to_string(),
-            self.log_nash_sutcliffe.ideal_value.to_string(),
-        ])?;
-        if self.optional_metrics.kling_gupta2009 {
+        for metric in self.all_metrics() {
             wtr.write_record([
-                self.kling_gupta2009.name.to_string(),
-                self.kling_gupta2009.value.to_string(),
-                self.kling_gupta2009.ideal_value.to_string(),
+                metric.name.to_string(),
+                format_float(metric.value, precision),
+                format_float(metric.ideal_value, precision),
             ])?;
         }
-        if self.optional_metrics.kling_gupta2012 {
+        for (name, value, ideal_value) in self.error_sign_fraction_rows() {
             wtr.write_record([
-                self.kling_gupta2012.name.to_string(),
-                self.kling_gupta2012.value.to_string(),
-                self.kling_gupta2012.ideal_value.to_string(),
+                name.to_string(),
+                format_float(value, precision),
+                format_float(ideal_value, precision),
             ])?;
         }
-        wtr.write_record([
-            self.non_parametric_kling_gupta.name.to_string(),
-            self.non_parametric_kling_gupta.value.to_string(),
-            self.non_parametric_kling_gupta.ideal_value.to_string(),
-        ])?;
         wtr.flush()?;
 
         Ok(())
@@ -246,55 +599,68 @@ impl<'a> CalibrationMetric {
     ///
     /// * `observed`: The vector of observed data.
     /// * `simulated`: The vector of simulated values.
+    /// * `denominator`: The sum of the squared deviations of the observed data from their mean
+    ///   (see [`CalibrationMetric::nse_denominator`]).
     ///
     /// returns: f64
-    fn nse(observed: &[f64], simulated: &[f64]) -> f64 {
-        let obs_mean = NaNVec(observed).mean();
+    fn nse(observed: &[f64], simulated: &[f64], denominator: f64) -> f64 {
         let mut n: f64 = 0.0;
-        let mut d: f64 = 0.0;
         for (obs, sim) in observed.iter().zip(simulated) {
-            if !obs.is_nan() {
-                if !sim.is_nan() {
-                    n += (obs - sim).powi(2);
-                }
-                d += (obs - obs_mean).powi(2);
+            if !obs.is_nan() && !sim.is_nan() {
+                n += (obs - sim).powi(2);
             }
         }
 
-        1.0 - n / d
+        1.0 - n / denominator
     }
 
-    /// Calculate the Kling-Gupta coefficient. A perfect model simulation returns 1.0.
+    /// Calculate the denominator of the Nash-Sutcliffe efficiency. This only depends on the
+    /// observed data and is the sum of their squared deviations from the mean.
     ///
     /// # Arguments
     ///
     /// * `observed`: The vector of observed data.
-    /// * `simulated`: The vector of simulated values.
-    /// * `method`: The method to use.
     ///
     /// returns: f64
-    fn kge(observed: &[f64], simulated: &[f64], method: KlingGuptaMethod) -> f64 {
-        // remove NaNs from both vectors
-        let (observed, simulated) = NaNVec(observed).remove_nans_from_pair(simulated).unwrap();
+    fn nse_denominator(observed: &[f64]) -> f64 {
+        let obs_mean = NaNVec(observed).mean();
+        observed
+            .iter()
+            .filter(|obs| !obs.is_nan())
+            .map(|obs| (obs - obs_mean).powi(2))
+            .sum()
+    }
+
+    /// Calculate the Kling-Gupta coefficient. A perfect model simulation returns 1.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The statistics of the observed data.
+    /// * `simulated`: The vector of simulated values. This must not contain NaNs and must be
+    ///   aligned with [`ObservedStats::values`].
+    /// * `method`: The method to use.
+    ///
+    /// returns: `KgeComponents`
+    fn kge(observed: &ObservedStats, simulated: &[f64], method: KlingGuptaMethod) -> KgeComponents {
+        let sim = NaNVec(simulated);
 
-        let obs = NaNVec(observed.as_slice());
-        let sim = NaNVec(simulated.as_slice());
-        let r = obs.spearman(simulated.as_slice());
+        // Spearman's rank correlation coefficient
+        let n = simulated.len() as f64;
+        let d2_sum: f64 = observed
+            .rank
+            .iter()
+            .zip(sim.rank())
+            .map(|(x_r, y_r)| (x_r - y_r).powi(2))
+            .sum();
+        let r = 1.0 - (6.0 * d2_sum) / (n * (n.powi(2) - 1.0));
 
-        let obs_mean = obs.mean();
         let sim_mean = sim.mean();
-        let beta = sim_mean / obs_mean;
+        let beta = sim_mean / observed.mean;
 
         let alpha = match method {
-            KlingGuptaMethod::Y2009 => sim.std() / obs.std(),
-            KlingGuptaMethod::Y2012 => (sim.std() / sim_mean) / (obs.std() / obs_mean),
+            KlingGuptaMethod::Y2009 => sim.std() / observed.std,
+            KlingGuptaMethod::Y2012 => (sim.std() / sim_mean) / (observed.std / observed.mean),
             KlingGuptaMethod::NonParametric => {
-                let scaled_obs: Vec<f64> = observed
-                    .iter()
-                    .map(|x| x / (obs_mean * observed.len() as f64))
-                    .collect();
-                let obs_fdc = Fdc::new(scaled_obs.as_slice());
-
                 let scaled_sim: Vec<f64> = simulated
                     .iter()
                     .map(|x| x / (sim_mean * simulated.len() as f64))
@@ -304,14 +670,19 @@ impl<'a> CalibrationMetric {
                 let deltas: f64 = sim_fdc
                     .sorted_run_off
                     .iter()
-                    .zip(obs_fdc.sorted_run_off)
+                    .zip(&observed.sorted_scaled)
                     .map(|(x1, x2)| (x1 - x2).abs())
                     .sum();
                 1.0 - 0.5 * deltas
             }
         };
 
-        1.0 - ((r - 1.0).powi(2) + (alpha - 1.0).powi(2) + (beta - 1.0).powi(2)).powf(0.5)
+        KgeComponents {
+            r,
+            alpha,
+            beta,
+            kge: 1.0 - ((r - 1.0).powi(2) + (alpha - 1.0).powi(2) + (beta - 1.0).powi(2)).powf(0.5),
+        }
     }
 
     /// Calculate the root-mean-square deviation. A perfect model simulation returns 0.0.
@@ -339,6 +710,45 @@ impl<'a> CalibrationMetric {
         }
     }
 
+    /// Calculate the Pearson correlation coefficient. A perfect model simulation returns 1.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The vector of observed data.
+    /// * `simulated`: The vector of simulated values.
+    ///
+    /// returns: f64
+    fn pearson(observed: &[f64], simulated: &[f64]) -> f64 {
+        // remove NaNs from both vectors
+        let (observed, simulated) = NaNVec(observed).remove_nans_from_pair(simulated).unwrap();
+        let obs_mean = NaNVec(observed.as_slice()).mean();
+        let sim_mean = NaNVec(simulated.as_slice()).mean();
+
+        let mut covariance: f64 = 0.0;
+        let mut obs_variance: f64 = 0.0;
+        let mut sim_variance: f64 = 0.0;
+        for (obs, sim) in observed.iter().zip(&simulated) {
+            covariance += (obs - obs_mean) * (sim - sim_mean);
+            obs_variance += (obs - obs_mean).powi(2);
+            sim_variance += (sim - sim_mean).powi(2);
+        }
+
+        covariance / (obs_variance * sim_variance).sqrt()
+    }
+
+    /// Calculate the coefficient of determination as the square of the Pearson correlation
+    /// coefficient. A perfect model simulation returns 1.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The vector of observed data.
+    /// * `simulated`: The vector of simulated values.
+    ///
+    /// returns: f64
+    fn r_squared(observed: &[f64], simulated: &[f64]) -> f64 {
+        Self::pearson(observed, simulated).powi(2)
+    }
+
     /// Calculate the volume error as difference between the sum of the total simulated and
     /// observed flow. A good model simulation returns 0.0.
     ///
@@ -360,12 +770,427 @@ impl<'a> CalibrationMetric {
 
         (sim_volume / obs_volume - 1.0) * 100.0
     }
+
+    /// Calculate the percent bias as `100 * sum(sim - obs) / sum(obs)`. A perfect model simulation
+    /// returns 0.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The vector of observed data.
+    /// * `simulated`: The vector of simulated values.
+    ///
+    /// returns: f64
+    pub fn percent_bias(observed: &[f64], simulated: &[f64]) -> f64 {
+        let mut n: f64 = 0.0;
+        let mut d: f64 = 0.0;
+        for (obs, sim) in observed.iter().zip(simulated) {
+            if !obs.is_nan() && !sim.is_nan() {
+                n += sim - obs;
+                d += obs;
+            }
+        }
+
+        100.0 * n / d
+    }
+
+    /// Calculate the fraction of days when the simulated flow is larger than, equal to or smaller
+    /// than the observed flow. Days with a missing value in either series are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The vector of observed data.
+    /// * `simulated`: The vector of simulated values.
+    ///
+    /// returns: (f64, f64, f64)
+    pub fn error_sign_fractions(observed: &[f64], simulated: &[f64]) -> (f64, f64, f64) {
+        let mut over: usize = 0;
+        let mut equal: usize = 0;
+        let mut under: usize = 0;
+        for (obs, sim) in observed.iter().zip(simulated) {
+            match sim.partial_cmp(obs) {
+                Some(Ordering::Greater) => over += 1,
+                Some(Ordering::Equal) => equal += 1,
+                Some(Ordering::Less) => under += 1,
+                None => {}
+            }
+        }
+
+        let total = (over + equal + under) as f64;
+        if total == 0.0 {
+            (f64::NAN, f64::NAN, f64::NAN)
+        } else {
+            (over as f64 / total, equal as f64 / total, under as f64 / total)
+        }
+    }
+
+    /// Calculate the volume error (see [`CalibrationMetric::volume_error`]) separately for the high
+    /// and low flows. The high flows are the days when the observed flow is above the given
+    /// percentile of the observed flow, the low flows are the remaining days. This reveals
+    /// compensating errors, for example when the high flows are over-predicted and the low flows
+    /// under-predicted, that the total volume error hides.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The vector of observed data.
+    /// * `simulated`: The vector of simulated values.
+    /// * `split_percentile`: The percentile of the observed flow (0-100) that separates the high
+    ///   and low flows.
+    ///
+    /// returns: `Result<(f64, f64), String>` with the volume error of the high and low flows.
+    pub fn volume_bias_by_regime(
+        observed: &[f64],
+        simulated: &[f64],
+        split_percentile: f64,
+    ) -> Result<(f64, f64), String> {
+        if observed.len() != simulated.len() {
+            return Err(format!(
+                "The vector must have the same length. Observed has {} values and simulated has {} values",
+                observed.len(),
+                simulated.len()
+            ));
+        }
+        if !(0.0..=100.0).contains(&split_percentile) {
+            return Err(format!(
+                "The split percentile must be a number between 0 and 100, but {} was given",
+                split_percentile
+            ));
+        }
+        let threshold = NaNVec(observed).percentile(split_percentile);
+
+        let (high, low): (Vec<_>, Vec<_>) = observed
+            .iter()
+            .copied()
+            .zip(simulated.iter().copied())
+            .filter(|(obs, sim)| !obs.is_nan() && !sim.is_nan())
+            .partition(|(obs, _)| *obs > threshold);
+        let volume_error = |pairs: Vec<(f64, f64)>| {
+            let (observed, simulated): (Vec<f64>, Vec<f64>) = pairs.into_iter().unzip();
+            Self::volume_error(&observed, &simulated)
+        };
+        Ok((volume_error(high), volume_error(low)))
+    }
+
+    /// Calculate the benchmark efficiency as `1 - SSE_model / SSE_benchmark`, where `SSE` is the
+    /// sum of the squared errors from the observed flow. A perfect model simulation returns 1.0,
+    /// while 0.0 means that the model is as accurate as the benchmark. With [`Benchmark::Mean`]
+    /// this is the Nash-Sutcliffe efficiency.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The vector of observed data.
+    /// * `simulated`: The vector of simulated values.
+    /// * `time`: The time vector.
+    /// * `benchmark`: The benchmark series to compare the model against.
+    ///
+    /// returns: `Result<f64, String>`
+    pub fn benchmark_efficiency(
+        observed: &[f64],
+        simulated: &[f64],
+        time: &[NaiveDate],
+        benchmark: Benchmark,
+    ) -> Result<f64, String> {
+        if observed.len() != simulated.len() || observed.len() != time.len() {
+            return Err(format!(
+                "The vector must have the same length. Observed has {} values, simulated has {} values and time has {} values",
+                observed.len(),
+                simulated.len(),
+                time.len()
+            ));
+        }
+        let benchmark_series = match benchmark {
+            Benchmark::Mean => vec![NaNVec(observed).mean(); observed.len()],
+            Benchmark::DayOfYearMean => day_of_year_mean(time, observed, time),
+        };
+
+        let sse = |series: &[f64]| -> f64 {
+            observed
+                .iter()
+                .zip(series)
+                .filter(|(obs, q)| !obs.is_nan() && !q.is_nan())
+                .map(|(obs, q)| (obs - q).powi(2))
+                .sum()
+        };
+        Ok(1.0 - sse(simulated) / sse(&benchmark_series))
+    }
+}
+
+/// The statistics of the observed flow used to calculate the Kling-Gupta coefficients.
+#[derive(Debug, Clone)]
+struct ObservedStats {
+    /// The observed values without NaNs.
+    values: Vec<f64>,
+    /// The mean of the values.
+    mean: f64,
+    /// The standard deviation of the values.
+    std: f64,
+    /// The rank of the values.
+    rank: Vec<f64>,
+    /// The sorted values of the flow duration curve of the values scaled by their total volume.
+    sorted_scaled: Vec<f64>,
+}
+
+impl ObservedStats {
+    /// Calculate the statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `values`: The observed values without NaNs.
+    ///
+    /// returns: `ObservedStats`
+    fn new(values: Vec<f64>) -> Self {
+        let vec = NaNVec(values.as_slice());
+        let mean = vec.mean();
+        let std = vec.std();
+        let rank = vec.rank();
+        let scaled: Vec<f64> = values.iter().map(|x| x / (mean * values.len() as f64)).collect();
+        let sorted_scaled = Fdc::new(scaled.as_slice()).sorted_run_off;
+        Self {
+            values,
+            mean,
+            std,
+            rank,
+            sorted_scaled,
+        }
+    }
+}
+
+/// The observed flow anomalies used to calculate [`CalibrationMetric::anomaly_nash_sutcliffe`].
+#[derive(Debug, Clone)]
+struct ObservedAnomaly {
+    /// The day-of-year mean of the observed flow.
+    climatology: Vec<f64>,
+    /// The observed flow minus the climatology.
+    values: Vec<f64>,
+    /// The denominator of the Nash-Sutcliffe efficiency.
+    nse_denominator: f64,
+}
+
+/// Calculate the metrics of many simulated series against the same observed series. The
+/// quantities that only depend on the observed flow (such as its log, mean, standard deviation,
+/// rank and flow duration curve) are calculated once when the context is created and are reused
+/// by [`CalibrationMetricContext::metrics`]. The metrics are the same as the ones calculated by
+/// [`CalibrationMetric::new`] and [`CalibrationMetric::new_with_time`].
+#[derive(Debug, Clone)]
+pub struct CalibrationMetricContext {
+    /// The observed flow series.
+    observed: Vec<f64>,
+    /// The log of the observed flow series.
+    observed_log: Vec<f64>,
+    /// The denominator of the Nash-Sutcliffe efficiency.
+    nse_denominator: f64,
+    /// The denominator of the Nash-Sutcliffe efficiency with log flows.
+    log_nse_denominator: f64,
+    /// The statistics of the observed values that are not NaN.
+    stats: ObservedStats,
+    /// The observed anomalies. This is only available when the time vector is given.
+    anomaly: Option<ObservedAnomaly>,
+    /// The optional metrics to calculate.
+    optional_metrics: OptionalMetrics,
+}
+
+impl CalibrationMetricContext {
+    /// Prepare the observed flow to calculate the metrics.
+    ///
+    /// # Arguments
+    ///
+    /// * `observed`: The observed flow series.
+    /// * `optional_metrics`: The optional metric to calculate. If None this defaults to
+    ///   [`OptionalMetrics::default()`].
+    ///
+    /// returns: `CalibrationMetricContext`
+    pub fn new(observed: &[f64], optional_metrics: Option<OptionalMetrics>) -> Self {
+        let optional_metrics = optional_metrics.unwrap_or_default();
+        let observed_log = optional_metrics.log(observed);
+        Self {
+            observed: observed.to_vec(),
+            nse_denominator: CalibrationMetric::nse_denominator(observed),
+            log_nse_denominator: CalibrationMetric::nse_denominator(&observed_log),
+            observed_log,
+            stats: ObservedStats::new(NaNVec(observed).remove_nans()),
+            anomaly: None,
+            optional_metrics: OptionalMetrics {
+                anomaly_nash_sutcliffe: false,
+                ..optional_metrics
+            },
+        }
+    }
+
+    /// Prepare the observed flow to calculate the metrics, including the metrics that require
+    /// the dates of the series, such as [`CalibrationMetric::anomaly_nash_sutcliffe`].
+    ///
+    /// # Arguments
+    ///
+    /// * `time`: The time vector.
+    /// * `observed`: The observed flow series.
+    /// * `optional_metrics`: The optional metric to calculate. If None this defaults to
+    ///   [`OptionalMetrics::default()`].
+    ///
+    /// returns: `Result<CalibrationMetricContext, MetricError>`
+    pub fn new_with_time(
+        time: &[NaiveDate],
+        observed: &[f64],
+        optional_metrics: Option<OptionalMetrics>,
+    ) -> Result<Self, MetricError> {
+        if time.len() != observed.len() {
+            return Err(MetricError::TimeLengthMismatch {
+                time: time.len(),
+                observed: observed.len(),
+            });
+        }
+        let mut context = Self::new(observed, optional_metrics);
+
+        let climatology = day_of_year_mean(time, observed, time);
+        let values: Vec<f64> = observed.iter().zip(&climatology).map(|(q, c)| q - c).collect();
+        context.anomaly = Some(ObservedAnomaly {
+            nse_denominator: CalibrationMetric::nse_denominator(&values),
+            climatology,
+            values,
+        });
+        context.optional_metrics.anomaly_nash_sutcliffe = true;
+        Ok(context)
+    }
+
+    /// The observed flow series.
+    ///
+    /// returns: `&[f64]`
+    pub fn observed(&self) -> &[f64] {
+        &self.observed
+    }
+
+    /// Calculate the efficiency metrics between the observed flow and a simulated series.
+    ///
+    /// # Arguments
+    ///
+    /// * `simulated`: The simulated flow series.
+    ///
+    /// returns: `Result<CalibrationMetric, MetricError>`
+    pub fn metrics(&self, simulated: &[f64]) -> Result<CalibrationMetric, MetricError> {
+        if self.observed.len() != simulated.len() {
+            return Err(MetricError::LengthMismatch {
+                observed: self.observed.len(),
+                simulated: simulated.len(),
+            });
+        }
+        let observed = self.observed.as_slice();
+
+        // The observed statistics can only be reused when the simulated series does not remove
+        // any additional observed value from the pair
+        let pair_stats;
+        let (stats, simulated_values) = if observed.iter().zip(simulated).any(|(o, s)| !o.is_nan() && s.is_nan()) {
+            // the series have the same length
+            let (observed_values, simulated_values) = NaNVec(observed).remove_nans_from_pair(simulated).unwrap();
+            pair_stats = ObservedStats::new(observed_values);
+            (&pair_stats, simulated_values)
+        } else {
+            let simulated_values: Vec<f64> = observed
+                .iter()
+                .zip(simulated)
+                .filter(|(o, _)| !o.is_nan())
+                .map(|(_, s)| *s)
+                .collect();
+            (&self.stats, simulated_values)
+        };
+        debug_assert_eq!(stats.values.len(), simulated_values.len());
+        let non_parametric_kge = CalibrationMetric::kge(stats, &simulated_values, KlingGuptaMethod::NonParametric);
+
+        let mut metrics = CalibrationMetric::unavailable(self.optional_metrics.clone());
+        metrics.nash_sutcliffe.value = CalibrationMetric::nse(observed, simulated, self.nse_denominator);
+        metrics.log_nash_sutcliffe.value = CalibrationMetric::nse(
+            &self.observed_log,
+            self.optional_metrics.log(simulated).as_slice(),
+            self.log_nse_denominator,
+        );
+        metrics.non_parametric_kling_gupta.value = non_parametric_kge.kge;
+        metrics.non_parametric_kling_gupta_components = Some(non_parametric_kge);
+        metrics.rmse.value = CalibrationMetric::rmse(observed, simulated);
+        metrics.volume_error.value = CalibrationMetric::volume_error(observed, simulated);
+        metrics.percent_bias.value = CalibrationMetric::percent_bias(observed, simulated);
+        metrics.error_sign_fractions = CalibrationMetric::error_sign_fractions(observed, simulated);
+        metrics.pearson.value = CalibrationMetric::pearson(observed, simulated);
+        metrics.r_squared.value = CalibrationMetric::r_squared(observed, simulated);
+
+        if self.optional_metrics.kling_gupta2009 {
+            let components = CalibrationMetric::kge(stats, &simulated_values, KlingGuptaMethod::Y2009);
+            metrics.kling_gupta2009.value = components.kge;
+            metrics.kling_gupta2009_components = Some(components);
+        }
+        if self.optional_metrics.kling_gupta2012 {
+            let components = CalibrationMetric::kge(stats, &simulated_values, KlingGuptaMethod::Y2012);
+            metrics.kling_gupta2012.value = components.kge;
+            metrics.kling_gupta2012_components = Some(components);
+        }
+        if let Some(anomaly) = &self.anomaly {
+            let simulated: Vec<f64> = simulated.iter().zip(&anomaly.climatology).map(|(q, c)| q - c).collect();
+            metrics.anomaly_nash_sutcliffe.value =
+                CalibrationMetric::nse(&anomaly.values, &simulated, anomaly.nse_denominator);
+        }
+        Ok(metrics)
+    }
+}
+
+/// Calculate the metrics separately for each calendar month, for example to check whether the
+/// model performs worse in the dry season. The metrics of a month whose series contain less than
+/// two pairs of observed and simulated values are NaN.
+///
+/// # Arguments
+///
+/// * `time`: The time vector.
+/// * `observed`: The observed flow series.
+/// * `simulated`: The simulated flow series.
+///
+/// returns: `Result<Vec<(u32, CalibrationMetric)>, MetricError>` with the month number (1-12) and
+/// its metrics.
+pub fn monthly_metrics(
+    time: &[NaiveDate],
+    observed: &[f64],
+    simulated: &[f64],
+) -> Result<Vec<(u32, CalibrationMetric)>, MetricError> {
+    if observed.len() != simulated.len() {
+        return Err(MetricError::LengthMismatch {
+            observed: observed.len(),
+            simulated: simulated.len(),
+        });
+    }
+    if observed.len() != time.len() {
+        return Err(MetricError::TimeLengthMismatch {
+            time: time.len(),
+            observed: observed.len(),
+        });
+    }
+
+    (1..=12)
+        .map(|month| {
+            let (observed, simulated): (Vec<f64>, Vec<f64>) = time
+                .iter()
+                .zip(observed.iter().zip(simulated))
+                .filter(|(t, _)| t.month() == month)
+                .map(|(_, (obs, sim))| (*obs, *sim))
+                .unzip();
+            let pairs = observed
+                .iter()
+                .zip(&simulated)
+                .filter(|(obs, sim)| !obs.is_nan() && !sim.is_nan())
+                .count();
+            let metrics = match pairs {
+                0 | 1 => CalibrationMetric::unavailable(OptionalMetrics::default()),
+                _ => CalibrationMetric::new(&observed, &simulated, None)?,
+            };
+            Ok((month, metrics))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::metric::{CalibrationMetric, OptionalMetrics};
+    use crate::error::MetricError;
+    use crate::metric::{
+        monthly_metrics, Benchmark, CalibrationMetric, CalibrationMetricContext, FlowTransform, KlingGuptaMethod,
+        MetricType, MultiObjective, OptionalMetrics,
+    };
+    use chrono::{Datelike, NaiveDate, TimeDelta};
+    use csv::Writer;
     use float_cmp::{assert_approx_eq, F64Margin};
+    use std::{env, fs};
 
     const A: [f64; 6] = [1250.0, 0.3, 500.0, 5.2, 2.0, 10.0];
     const B: [f64; 6] = [150.0, 0.03, 200.0, 5.2, 20.0, 15.0];
@@ -376,6 +1201,8 @@ mod tests {
     const OPTIONAL_METRICS: Option<OptionalMetrics> = Some(OptionalMetrics {
         kling_gupta2009: true,
         kling_gupta2012: true,
+        anomaly_nash_sutcliffe: false,
+        epsilon: None,
     });
 
     #[test]
@@ -386,6 +1213,8 @@ mod tests {
         assert_eq!(metric.kling_gupta2009.ideal_value, 1.0);
         assert_eq!(metric.kling_gupta2012.ideal_value, 1.0);
         assert_eq!(metric.non_parametric_kling_gupta.ideal_value, 1.0);
+        assert_eq!(metric.pearson.ideal_value, 1.0);
+        assert_eq!(metric.r_squared.ideal_value, 1.0);
     }
 
     #[test]
@@ -493,6 +1322,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_log_nse_epsilon() {
+        let observed = [0.0, 1.0, 2.0, 3.0, 0.5];
+        let simulated = [0.5, 1.0, 2.5, 2.0, 0.0];
+
+        // the days with zero flow are excluded by default
+        let metric = CalibrationMetric::new(&observed, &simulated, None).unwrap();
+        let expected = CalibrationMetric::new(&observed[1..], &simulated[1..], None).unwrap();
+        assert_eq!(metric.log_nash_sutcliffe.value, expected.log_nash_sutcliffe.value);
+
+        // with the offset all days are included
+        let epsilon = 0.01;
+        let optional_metrics = OptionalMetrics {
+            epsilon: Some(epsilon),
+            ..OptionalMetrics::default()
+        };
+        let metric = CalibrationMetric::new(&observed, &simulated, Some(optional_metrics)).unwrap();
+        let shifted_obs: Vec<f64> = observed.iter().map(|q| q + epsilon).collect();
+        let shifted_sim: Vec<f64> = simulated.iter().map(|q| q + epsilon).collect();
+        let expected = CalibrationMetric::new(&shifted_obs, &shifted_sim, None).unwrap();
+        assert!(!metric.log_nash_sutcliffe.value.is_nan());
+        assert_eq!(metric.log_nash_sutcliffe.value, expected.log_nash_sutcliffe.value);
+
+        // the other metrics are not affected
+        let default = CalibrationMetric::new(&observed, &simulated, None).unwrap();
+        assert_eq!(metric.nash_sutcliffe.value, default.nash_sutcliffe.value);
+    }
+
+    #[test]
+    fn test_kge_components() {
+        let metric = CalibrationMetric::new(&A, &B, OPTIONAL_METRICS).unwrap();
+        for (method, value) in [
+            (KlingGuptaMethod::Y2009, metric.kling_gupta2009.value),
+            (KlingGuptaMethod::Y2012, metric.kling_gupta2012.value),
+            (KlingGuptaMethod::NonParametric, metric.non_parametric_kling_gupta.value),
+        ] {
+            let components = metric.kge_components(method).unwrap();
+            assert_eq!(components.kge, value);
+            let recombined = 1.0
+                - ((components.r - 1.0).powi(2) + (components.alpha - 1.0).powi(2) + (components.beta - 1.0).powi(2))
+                    .sqrt();
+            assert_approx_eq!(f64, recombined, value, MARGINS);
+        }
+
+        // the optional efficiencies are not calculated by default
+        let metric = CalibrationMetric::new(&A, &B, None).unwrap();
+        assert!(metric.kge_components(KlingGuptaMethod::Y2009).is_none());
+        assert!(metric.kge_components(KlingGuptaMethod::NonParametric).is_some());
+    }
+
     #[test]
     fn test_rmse_metric() {
         let metric = CalibrationMetric::new(&A, &B, OPTIONAL_METRICS).unwrap();
@@ -528,4 +1407,379 @@ mod tests {
         let metric = CalibrationMetric::new(&A_NAN, &B_NAN, OPTIONAL_METRICS).unwrap();
         assert_approx_eq!(f64, metric.volume_error.value, -55.61970443349754, MARGINS);
     }
+
+    #[test]
+    fn test_masked_metrics() {
+        // exclude the first time step
+        let mask = [false, true, true, true, true, true];
+        let masked = CalibrationMetric::new_masked(&A, &B, &mask, OPTIONAL_METRICS).unwrap();
+        let expected = CalibrationMetric::new(&A[1..], &B[1..], OPTIONAL_METRICS).unwrap();
+        assert_eq!(masked.nash_sutcliffe.value, expected.nash_sutcliffe.value);
+        assert_eq!(masked.rmse.value, expected.rmse.value);
+        assert_eq!(masked.volume_error.value, expected.volume_error.value);
+
+        // excluding a missing value gives the same metrics
+        let masked = CalibrationMetric::new_masked(&A_NAN, &B, &mask, OPTIONAL_METRICS).unwrap();
+        let expected = CalibrationMetric::new(&A_NAN, &B, OPTIONAL_METRICS).unwrap();
+        assert_eq!(masked.nash_sutcliffe.value, expected.nash_sutcliffe.value);
+
+        assert_eq!(
+            CalibrationMetric::new_masked(&A, &B, &[false; 6], None).unwrap_err(),
+            MetricError::EmptyMask()
+        );
+        assert_eq!(
+            CalibrationMetric::new_masked(&A, &B, &[true; 5], None).unwrap_err(),
+            MetricError::MaskLengthMismatch { mask: 5, series: 6 }
+        );
+    }
+
+    #[test]
+    fn test_pearson_metric() {
+        let metric = CalibrationMetric::new(&A, &B, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.pearson.value, 0.7820567145166031, MARGINS);
+    }
+
+    #[test]
+    fn test_pearson_metric_with_nan_1() {
+        let metric = CalibrationMetric::new(&A_NAN, &B, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.pearson.value, 0.9961454033582152, MARGINS);
+    }
+
+    #[test]
+    fn test_pearson_metric_with_nan_2() {
+        let metric = CalibrationMetric::new(&A, &B_NAN, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.pearson.value, 0.7638115214460922, MARGINS);
+    }
+
+    #[test]
+    fn test_r_squared_metric() {
+        let metric = CalibrationMetric::new(&A, &B, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.r_squared.value, 0.6116127047205037, MARGINS);
+    }
+
+    #[test]
+    fn test_r_squared_metric_with_nan_1() {
+        let metric = CalibrationMetric::new(&A_NAN, &B, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.r_squared.value, 0.9923056646317013, MARGINS);
+    }
+
+    #[test]
+    fn test_r_squared_metric_with_nan_2() {
+        let metric = CalibrationMetric::new(&A, &B_NAN, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.r_squared.value, 0.5834080402937942, MARGINS);
+    }
+
+    #[test]
+    fn test_percent_bias_metric() {
+        let metric = CalibrationMetric::new(&A, &B, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.percent_bias.value, -77.92192362093353, MARGINS);
+    }
+
+    #[test]
+    fn test_percent_bias_metric_with_nan_1() {
+        let metric = CalibrationMetric::new(&A_NAN, &B, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.percent_bias.value, -53.57874396135266, MARGINS);
+    }
+
+    #[test]
+    fn test_percent_bias_metric_with_nan_2() {
+        let metric = CalibrationMetric::new(&A, &B_NAN, OPTIONAL_METRICS).unwrap();
+        assert_approx_eq!(f64, metric.percent_bias.value, -78.64978662873399, MARGINS);
+    }
+
+    #[test]
+    fn test_error_sign_fractions() {
+        let metric = CalibrationMetric::new(&A, &B, OPTIONAL_METRICS).unwrap();
+        let (over, equal, under) = metric.error_sign_fractions;
+        assert_approx_eq!(f64, over, 2.0 / 6.0, MARGINS);
+        assert_approx_eq!(f64, equal, 1.0 / 6.0, MARGINS);
+        assert_approx_eq!(f64, under, 3.0 / 6.0, MARGINS);
+    }
+
+    #[test]
+    fn test_error_sign_fractions_with_nan() {
+        let metric = CalibrationMetric::new(&A_NAN, &B_NAN, OPTIONAL_METRICS).unwrap();
+        let (over, equal, under) = metric.error_sign_fractions;
+        assert_approx_eq!(f64, over, 1.0 / 4.0, MARGINS);
+        assert_approx_eq!(f64, equal, 1.0 / 4.0, MARGINS);
+        assert_approx_eq!(f64, under, 2.0 / 4.0, MARGINS);
+    }
+
+    #[test]
+    fn test_context_reuse() {
+        // the same context is reused for simulations with and without missing values
+        let context = CalibrationMetricContext::new(&A_NAN, OPTIONAL_METRICS);
+        for simulated in [B, B_NAN, A] {
+            let from_context = context.metrics(&simulated).unwrap();
+            let expected = CalibrationMetric::new(&A_NAN, &simulated, OPTIONAL_METRICS).unwrap();
+            for metric_type in [
+                MetricType::NashSutcliffe,
+                MetricType::LogNashSutcliffe,
+                MetricType::KlingGupta2009,
+                MetricType::KlingGupta2012,
+                MetricType::NonParametricKlingGupta,
+                MetricType::Rmse,
+                MetricType::VolumeError,
+                MetricType::PercentBias,
+                MetricType::Pearson,
+                MetricType::RSquared,
+            ] {
+                assert_eq!(
+                    from_context.get(metric_type).value.to_bits(),
+                    expected.get(metric_type).value.to_bits(),
+                    "{:?}",
+                    metric_type
+                );
+            }
+        }
+        assert_eq!(context.observed().len(), A_NAN.len());
+        assert!(context.metrics(&B[0..3]).is_err());
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let error = CalibrationMetric::new(&A, &B[0..4], None).unwrap_err();
+        assert_eq!(
+            error,
+            MetricError::LengthMismatch {
+                observed: 6,
+                simulated: 4
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "The vector must have the same length. Observed has 6 values and simulated has 4 values"
+        );
+
+        let time: Vec<NaiveDate> = (0..5)
+            .map(|d| NaiveDate::from_ymd_opt(2000, 1, 1).unwrap() + TimeDelta::try_days(d).unwrap())
+            .collect();
+        assert_eq!(
+            CalibrationMetric::new_with_time(&time, &A, &B, None).unwrap_err(),
+            MetricError::TimeLengthMismatch { time: 5, observed: 6 }
+        );
+    }
+
+    #[test]
+    fn test_all_metrics() {
+        let metric = CalibrationMetric::new(&A, &B, None).unwrap();
+        assert_eq!(metric.all_metrics().len(), 8);
+        assert!(metric.all_metrics().iter().all(|m| !m.name.contains("(20")));
+
+        let metric = CalibrationMetric::new(&A, &B, OPTIONAL_METRICS).unwrap();
+        let all_metrics = metric.all_metrics();
+        assert_eq!(all_metrics.len(), 10);
+        assert_eq!(all_metrics[2].name, metric.kling_gupta2009.name);
+        assert_eq!(all_metrics[3].value.to_bits(), metric.kling_gupta2012.value.to_bits());
+
+        // the anomaly Nash-Sutcliffe is available with the time vector
+        let time: Vec<NaiveDate> = (0..A.len() as i64)
+            .map(|d| NaiveDate::from_ymd_opt(2000, 1, 1).unwrap() + TimeDelta::try_days(d).unwrap())
+            .collect();
+        let metric = CalibrationMetric::new_with_time(&time, &A, &B, OPTIONAL_METRICS).unwrap();
+        assert_eq!(metric.all_metrics().len(), 11);
+        assert_eq!(
+            metric.all_metrics().last().unwrap().name,
+            metric.anomaly_nash_sutcliffe.name
+        );
+    }
+
+    #[test]
+    fn test_csv_header_and_row() {
+        let metric = CalibrationMetric::new(&A, &B, None).unwrap();
+        let path = env::temp_dir().join("gr6j_test_csv_header_and_row.csv");
+        let mut wtr = Writer::from_path(&path).unwrap();
+        metric
+            .append_header_to_csv(&mut wtr, Some("Simulation".to_string()))
+            .unwrap();
+        metric
+            .append_row_to_csv(&mut wtr, Some("#1".to_string()), Some(3))
+            .unwrap();
+        drop(wtr);
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Simulation,Nash-Sutcliffe,Nash-Sutcliffe with log flows,Non-parametric Kling-Gupta,\
+            Root-mean-square error,Volume error,Percent bias,Pearson correlation,Coefficient of determination,\
+            Fraction of over-estimated days,Fraction of exact days,Fraction of under-estimated days"
+        );
+        let row: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(row.len(), 12);
+        assert_eq!(row[4], format!("{:.3}", metric.rmse.value));
+        assert_eq!(row[5], format!("{:.3}", metric.volume_error.value));
+        let (over, equal, under) = metric.error_sign_fractions;
+        assert_eq!(
+            row[9..],
+            [format!("{:.3}", over), format!("{:.3}", equal), format!("{:.3}", under)]
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let metric = CalibrationMetric::new(&A, &B, None).unwrap();
+        let path = env::temp_dir().join("gr6j_test_csv_export.csv");
+        metric.to_csv(path.clone(), Some(3)).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "Metric,Value,Ideal value");
+        assert_eq!(lines.len(), 1 + metric.all_metrics().len() + 3);
+
+        let (over, equal, under) = metric.error_sign_fractions;
+        assert_eq!(
+            lines[lines.len() - 3..],
+            [
+                format!("Fraction of over-estimated days,{:.3},0.000", over),
+                format!("Fraction of exact days,{:.3},1.000", equal),
+                format!("Fraction of under-estimated days,{:.3},0.000", under),
+            ]
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_anomaly_nash_sutcliffe() {
+        let margins = F64Margin {
+            epsilon: 1e-12,
+            ulps: 2,
+        };
+
+        // two years with the same seasonal cycle shifted up and down
+        let start = NaiveDate::from_ymd_opt(2001, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..730).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let seasonal: Vec<f64> = (0..730).map(|d| 10.0 + 5.0 * (d % 365) as f64 / 365.0).collect();
+        let observed: Vec<f64> = seasonal
+            .iter()
+            .enumerate()
+            .map(|(d, q)| if d < 365 { q + 1.0 } else { q - 1.0 })
+            .collect();
+
+        let (obs_anomaly, sim_anomaly) = FlowTransform::DeseasonalisedAnomaly.apply(&time, &observed, &seasonal);
+        assert_approx_eq!(f64, obs_anomaly[0], 1.0, margins);
+        assert_approx_eq!(f64, obs_anomaly[400], -1.0, margins);
+        assert_approx_eq!(f64, sim_anomaly[10], 0.0, margins);
+
+        // the simulation only reproduces the seasonal cycle
+        let metric = CalibrationMetric::new_with_time(&time, &observed, &seasonal, None).unwrap();
+        assert!(metric.nash_sutcliffe.value > 0.0);
+        assert_approx_eq!(f64, metric.anomaly_nash_sutcliffe.value, 0.0, margins);
+
+        let metric = CalibrationMetric::new_with_time(&time, &observed, &observed, None).unwrap();
+        assert_approx_eq!(f64, metric.anomaly_nash_sutcliffe.value, 1.0, margins);
+
+        // not available without the time vector
+        let metric = CalibrationMetric::new(&observed, &observed, None).unwrap();
+        assert!(metric.anomaly_nash_sutcliffe.value.is_nan());
+    }
+
+    #[test]
+    fn test_volume_bias_by_regime() {
+        let observed = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        // the high flows are over-predicted by 10% and the low flows are under-predicted by 20%
+        let simulated: Vec<f64> = observed
+            .iter()
+            .map(|q| if *q > 5.5 { q * 1.1 } else { q * 0.8 })
+            .collect();
+
+        let (high, low) = CalibrationMetric::volume_bias_by_regime(&observed, &simulated, 50.0).unwrap();
+        assert_approx_eq!(f64, high, 10.0, epsilon = 1e-10);
+        assert_approx_eq!(f64, low, -20.0, epsilon = 1e-10);
+
+        assert!(CalibrationMetric::volume_bias_by_regime(&observed, &simulated, 101.0).is_err());
+        assert!(CalibrationMetric::volume_bias_by_regime(&observed, &simulated[1..], 50.0).is_err());
+    }
+
+    #[test]
+    fn test_monthly_metrics() {
+        let start = NaiveDate::from_ymd_opt(2001, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..730).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let observed: Vec<f64> = (0..730).map(|d| 10.0 + 5.0 * (d % 365) as f64 / 365.0).collect();
+        // the simulation is perfect in January only and all observations in March are missing
+        let simulated: Vec<f64> = time
+            .iter()
+            .zip(&observed)
+            .map(|(t, q)| if t.month() == 1 { *q } else { q * 1.2 })
+            .collect();
+        let observed: Vec<f64> = time
+            .iter()
+            .zip(&observed)
+            .map(|(t, q)| if t.month() == 3 { f64::NAN } else { *q })
+            .collect();
+
+        let metrics = monthly_metrics(&time, &observed, &simulated).unwrap();
+        assert_eq!(metrics.len(), 12);
+        assert_eq!(
+            metrics.iter().map(|(m, _)| *m).collect::<Vec<u32>>(),
+            (1..=12).collect::<Vec<u32>>()
+        );
+        assert_eq!(metrics[0].1.nash_sutcliffe.value, 1.0);
+        assert_eq!(metrics[0].1.rmse.value, 0.0);
+        assert!(metrics[1].1.nash_sutcliffe.value < 1.0);
+        assert!(metrics[2].1.nash_sutcliffe.value.is_nan());
+        assert!(metrics[2].1.rmse.value.is_nan());
+
+        assert!(monthly_metrics(&time[1..], &observed, &simulated).is_err());
+    }
+
+    #[test]
+    fn test_benchmark_efficiency() {
+        let margins = F64Margin {
+            epsilon: 1e-12,
+            ulps: 2,
+        };
+        let start = NaiveDate::from_ymd_opt(2001, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..730).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let seasonal: Vec<f64> = (0..730).map(|d| 10.0 + 5.0 * (d % 365) as f64 / 365.0).collect();
+        let observed: Vec<f64> = seasonal
+            .iter()
+            .enumerate()
+            .map(|(d, q)| if d < 365 { q + 1.0 } else { q - 1.0 })
+            .collect();
+
+        // the mean benchmark gives the Nash-Sutcliffe efficiency
+        let metric = CalibrationMetric::new(&observed, &seasonal, None).unwrap();
+        let efficiency = CalibrationMetric::benchmark_efficiency(&observed, &seasonal, &time, Benchmark::Mean).unwrap();
+        assert_approx_eq!(f64, efficiency, metric.nash_sutcliffe.value, margins);
+
+        // the simulation is the climatology and has no skill over the seasonal benchmark
+        let efficiency =
+            CalibrationMetric::benchmark_efficiency(&observed, &seasonal, &time, Benchmark::DayOfYearMean).unwrap();
+        assert_approx_eq!(f64, efficiency, 0.0, margins);
+
+        assert!(CalibrationMetric::benchmark_efficiency(&observed, &seasonal, &time[1..], Benchmark::Mean).is_err());
+    }
+
+    #[test]
+    fn test_multi_objective() {
+        let metric = CalibrationMetric::new(&A, &B, None).unwrap();
+        let objective = MultiObjective {
+            weights: vec![(MetricType::NashSutcliffe, 0.5), (MetricType::VolumeError, 0.5)],
+        };
+        assert!(objective.validate().is_ok());
+        let expected = 0.5 * (1.0 - metric.nash_sutcliffe.value).abs() + 0.5 * metric.volume_error.value.abs();
+        assert_approx_eq!(f64, objective.score(&metric), expected, MARGINS);
+
+        // a perfect simulation has a zero score
+        let perfect = CalibrationMetric::new(&A, &A, None).unwrap();
+        assert_eq!(objective.score(&perfect), 0.0);
+
+        // the anomaly Nash-Sutcliffe is not available without the time vector
+        let objective = MultiObjective {
+            weights: vec![
+                (MetricType::NashSutcliffe, 0.5),
+                (MetricType::AnomalyNashSutcliffe, 0.5),
+            ],
+        };
+        assert!(objective.score(&metric).is_nan());
+
+        // invalid weights
+        assert!(MultiObjective { weights: vec![] }.validate().is_err());
+        let objective = MultiObjective {
+            weights: vec![(MetricType::NashSutcliffe, -1.0)],
+        };
+        assert!(objective.validate().is_err());
+    }
 }
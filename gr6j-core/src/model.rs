@@ -1,19 +1,45 @@
 use std::fmt::Debug;
+#[cfg(feature = "serde")]
+use std::fs;
 use std::fs::create_dir;
 use std::path::{Path, PathBuf};
 
-use chrono::{Local, NaiveDate, TimeDelta};
+use chrono::{Local, Months, NaiveDate, TimeDelta};
 use csv::Writer;
 use log::{debug, info, warn};
+use rayon::prelude::*;
 
-use crate::chart::{generate_summary_chart, save_fdc_chart};
+use crate::chart::{
+    fdc_chart_png, generate_summary_chart, save_catchment_contribution_chart, save_cumulative_flow_chart,
+    save_fdc_chart, save_monthly_regime_chart, save_scatter_chart, summary_chart_png,
+};
 use crate::error::{LoadModelError, RunModelError};
-use crate::inputs::{GR6JModelInputs, ModelPeriod, RunOffUnit, StoreLevels};
-use crate::metric::CalibrationMetric;
-use crate::outputs::{GR6JOutputs, ModelStepData, ModelStepDataVector};
+use crate::inputs::{
+    CatchmentData, ChartTheme, DiagnosticChart, GR6JModelInputs, ImageFormat, InitialStoreLevels, ModelPeriod,
+    OutputPrecision, RunOffUnit, StoreLevels, WarmupStrategy,
+};
+use crate::metric::{CalibrationMetric, OptionalMetrics};
+use crate::outputs::{
+    CatchmentDescription, DayOutput, GR6JOutputs, ModelDescription, ModelState, ModelStepData, ModelStepDataVector,
+    RunMetadata,
+};
 use crate::parameter::{Parameter, X1, X2, X3, X4, X5, X6};
 use crate::unit_hydrograph::{UnitHydrograph, UnitHydrographInputs, UnitHydrographType};
-use crate::utils::{vector_nan_indices, Fdc};
+use crate::utils::{day_of_year_mean, format_float, vector_nan_indices, vector_negative_indices, Fdc, CSV_PRECISION};
+
+/// The maximum value of the net rainfall or evapotranspiration scaled by `X1`. This is the same
+/// guard used in airGR to prevent the exponential in the production store equations from
+/// overflowing when `X1` is very small.
+pub const SCALED_RAINFALL_CAP: f64 = 13.0;
+
+/// The default fraction of the effective rainfall routed through the first unit hydrograph.
+pub const UH_SPLIT: f64 = 0.9;
+
+/// The default fraction of the first unit hydrograph output sent to the exponential store.
+pub const ROUTING_SPLIT: f64 = 0.4;
+
+/// The default exponent of the S-curves of the unit hydrographs.
+pub const UH_EXPONENT: f64 = 2.5;
 
 /// Internal state variables
 #[derive(Debug)]
@@ -28,6 +54,43 @@ struct InternalState {
     unit_hydrograph2: UnitHydrograph,
 }
 
+impl InternalState {
+    /// Initialise the state of a model at the first time step.
+    ///
+    /// # Arguments
+    ///
+    /// * `store_levels`: The initial store levels.
+    /// * `x1`: Parameter X1.
+    /// * `x3`: Parameter X3.
+    /// * `x4`: Parameter X4.
+    /// * `uh_exponent`: The exponent of the unit hydrographs.
+    ///
+    /// returns: InternalState
+    fn new(store_levels: InitialStoreLevels, x1: &X1, x3: &X3, x4: &X4, uh_exponent: f64) -> Self {
+        // scale the levels
+        let store_levels = store_levels.to_absolute(x1.value(), x3.value());
+
+        // initialise the unit hydrographs
+        let unit_hydrograph1 = UnitHydrograph::new(UnitHydrographInputs {
+            uh_type: UnitHydrographType::T1,
+            time_constant: x4.value(),
+            exponent: uh_exponent,
+        });
+        let unit_hydrograph2 = UnitHydrograph::new(UnitHydrographInputs {
+            uh_type: UnitHydrographType::T2,
+            time_constant: x4.value(),
+            exponent: uh_exponent,
+        });
+
+        InternalState {
+            step: 0,
+            store_levels,
+            unit_hydrograph1,
+            unit_hydrograph2,
+        }
+    }
+}
+
 /// The struct containing the state and GR6J parameters for one model.
 #[derive(Debug)]
 struct ModelData {
@@ -45,8 +108,241 @@ struct ModelData {
     x5: X5,
     /// Parameter X6
     x6: X6,
+    /// The initial store levels.
+    initial_store_levels: InitialStoreLevels,
+    /// The exponent of the unit hydrographs.
+    uh_exponent: f64,
     /// The current internal state of the model
     state: InternalState,
+    /// The store levels at the last step of the warm-up period.
+    warmup_end_state: Option<StoreLevels>,
+}
+
+impl ModelData {
+    /// Restore the initial state of the model.
+    fn reset(&mut self) {
+        self.state = InternalState::new(
+            self.initial_store_levels,
+            &self.x1,
+            &self.x3,
+            &self.x4,
+            self.uh_exponent,
+        );
+        self.warmup_end_state = None;
+    }
+
+    /// Store the levels reached at a step of the warm-up period. The levels of the last warm-up
+    /// step are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `step_data`: The data of the simulated step.
+    /// * `collect_data_from`: The first day of the run period.
+    fn record_warmup_state(&mut self, step_data: &ModelStepData, collect_data_from: NaiveDate) {
+        if step_data.time < collect_data_from {
+            self.warmup_end_state = Some(step_data.store_levels);
+        }
+    }
+
+    /// Create a copy of the model with the same parameters at its initial state.
+    ///
+    /// returns: ModelData
+    fn fresh_copy(&self) -> ModelData {
+        ModelData {
+            area: self.area,
+            x1: self.x1,
+            x2: self.x2,
+            x3: self.x3,
+            x4: self.x4,
+            x5: self.x5,
+            x6: self.x6,
+            initial_store_levels: self.initial_store_levels,
+            uh_exponent: self.uh_exponent,
+            state: InternalState::new(
+                self.initial_store_levels,
+                &self.x1,
+                &self.x3,
+                &self.x4,
+                self.uh_exponent,
+            ),
+            warmup_end_state: None,
+        }
+    }
+
+    /// Advance the model by one day.
+    ///
+    /// # Arguments
+    ///
+    /// * `time`: The date of the time step.
+    /// * `p`: The total precipitation (mm).
+    /// * `e`: The potential evapotranspiration (mm).
+    /// * `scaled_rainfall_cap`: The maximum value of the scaled net rainfall or evapotranspiration.
+    /// * `uh_split`: The fraction of the effective rainfall routed through the first unit hydrograph.
+    /// * `routing_split`: The fraction of the UH1 output sent to the exponential store.
+    ///
+    /// returns: ModelStepData
+    fn step(
+        &mut self,
+        time: NaiveDate,
+        p: f64,
+        e: f64,
+        scaled_rainfall_cap: f64,
+        uh_split: f64,
+        routing_split: f64,
+    ) -> ModelStepData {
+        // skip the days without forcing (see `GR6JModelInputs::fill_gaps`)
+        if p.is_nan() || e.is_nan() {
+            self.state.step += 1;
+            return ModelStepData {
+                time,
+                evapotranspiration: e,
+                precipitation: p,
+                net_rainfall: f64::NAN,
+                store_levels: self.state.store_levels,
+                storage_p: f64::NAN,
+                actual_evapotranspiration: f64::NAN,
+                percolation: f64::NAN,
+                pr: f64::NAN,
+                exchange: f64::NAN,
+                exchange_from_routing_store: f64::NAN,
+                exchange_from_direct_branch: f64::NAN,
+                actual_exchange: f64::NAN,
+                routing_store_outflow: f64::NAN,
+                exponential_store_outflow: f64::NAN,
+                outflow_from_uh2_branch: f64::NAN,
+                run_off: f64::NAN,
+            };
+        }
+
+        let b = uh_split;
+        let c = routing_split;
+        let x1 = self.x1.value();
+        let x3 = self.x3.value();
+        let x6 = self.x6.value();
+
+        let storage_ratio = self.state.store_levels.production_store / x1;
+
+        // update production store level
+        let mut net_p = 0.0;
+        let mut pr = 0.0;
+        let mut storage_p = 0.0;
+        #[allow(unused_assignments)]
+        let mut actual_e = 0.0;
+        if p < e {
+            let net_e = e - p;
+            let scaled_e = (net_e / x1).min(scaled_rainfall_cap);
+            let exp_scaled_e = (2.0 * scaled_e).exp();
+
+            let tws = (exp_scaled_e - 1.0) / (exp_scaled_e + 1.0);
+            let storage_e = self.state.store_levels.production_store * (2.0 - storage_ratio) * tws
+                / (1.0 + (1.0 - storage_ratio) * tws);
+
+            actual_e = storage_e + p;
+            self.state.store_levels.production_store -= storage_e;
+        } else {
+            actual_e = e;
+            net_p = p - e;
+            let scaled_p = (net_p / x1).min(scaled_rainfall_cap);
+            let exp_scaled_p = (2.0 * scaled_p).exp();
+
+            let tws = (exp_scaled_p - 1.0) / (exp_scaled_p + 1.0);
+            storage_p = x1 * (1.0 - storage_ratio.powi(2)) * tws / (1.0 + storage_ratio * tws);
+            pr = net_p - storage_p;
+            self.state.store_levels.production_store += storage_p;
+        }
+
+        if self.state.store_levels.production_store < 0.0 {
+            self.state.store_levels.production_store = 0.0;
+        }
+
+        // update percolation in production store
+        let percolation = self.state.store_levels.production_store
+            * (1. - (1. + (self.state.store_levels.production_store / (9. / 4. * x1)).powi(4)).powf(-0.25));
+        self.state.store_levels.production_store -= percolation;
+        pr += percolation;
+
+        // split the effective rainfall into the two routing components and generate the two new hydrographs
+        let precipitation_uh1 = pr * b;
+        let precipitation_uh2 = pr * (1.0 - b);
+
+        // Combine the two hydrographs
+        let x4 = self.x4.value() as i32;
+        self.state.unit_hydrograph1.convolution(x4, precipitation_uh1);
+        self.state.unit_hydrograph2.convolution(x4, precipitation_uh2);
+
+        // potential inter-catchment semi-exchange
+        let exchange = self.x2.value() * (self.state.store_levels.routing_store / x3 - self.x5.value());
+
+        // routing store
+        let new_routing_store =
+            self.state.store_levels.routing_store + (1.0 - c) * self.state.unit_hydrograph1.values[0] + exchange;
+        let exchange_from_routing_store = {
+            if new_routing_store < 0.0 {
+                -(new_routing_store - exchange)
+            } else {
+                exchange
+            }
+        };
+        self.state.store_levels.routing_store = new_routing_store;
+        if self.state.store_levels.routing_store < 0.0 {
+            self.state.store_levels.routing_store = 0.0;
+        }
+
+        let scaled_routing_store = (self.state.store_levels.routing_store / x3).powi(4);
+        let routing_store_outflow =
+            self.state.store_levels.routing_store * (1. - 1. / f64::sqrt(f64::sqrt(1. + scaled_routing_store)));
+        self.state.store_levels.routing_store -= routing_store_outflow;
+
+        // exponential store
+        self.state.store_levels.exponential_store += c * self.state.unit_hydrograph1.values[0] + exchange;
+        let scaled_exp_store = self.state.store_levels.exponential_store / x6.clamp(-33.0, 33.0);
+        let exponential_store_outflow = {
+            if scaled_exp_store > 7.0 {
+                self.state.store_levels.exponential_store + x6 / scaled_exp_store.exp()
+            } else if scaled_exp_store < -7.0 {
+                x6 * scaled_exp_store.exp()
+            } else {
+                x6 * (scaled_exp_store.exp() + 1.0).ln()
+            }
+        };
+        self.state.store_levels.exponential_store -= exponential_store_outflow;
+
+        // run-off from outflow from UH2 branch after exchange
+        let exchange_from_direct_branch = {
+            if self.state.unit_hydrograph2.values[0] + exchange < 0.0 {
+                -self.state.unit_hydrograph2.values[0]
+            } else {
+                exchange
+            }
+        };
+        let outflow_from_uh2_branch = (self.state.unit_hydrograph2.values[0] + exchange).max(0.0);
+
+        // total run-off
+        let run_off = routing_store_outflow + outflow_from_uh2_branch + exponential_store_outflow;
+
+        // update the step index
+        self.state.step += 1;
+
+        ModelStepData {
+            time,
+            evapotranspiration: e,
+            precipitation: p,
+            net_rainfall: net_p,
+            store_levels: self.state.store_levels,
+            storage_p,
+            actual_evapotranspiration: actual_e,
+            percolation,
+            pr,
+            exchange,
+            exchange_from_routing_store,
+            exchange_from_direct_branch,
+            actual_exchange: exchange_from_routing_store + exchange_from_direct_branch + exchange,
+            routing_store_outflow,
+            exponential_store_outflow,
+            outflow_from_uh2_branch,
+            run_off,
+        }
+    }
 }
 
 /// The GR6J model
@@ -62,6 +358,12 @@ pub struct GR6JModel {
     models: Vec<ModelData>,
     /// The first day of the simulation (without the warm-up period).
     collect_data_from: NaiveDate,
+    /// The model run period.
+    run_period: ModelPeriod,
+    /// The warm-up period used to initialise the stores.
+    warmup_period: Option<ModelPeriod>,
+    /// The strategy used to initialise the stores.
+    warmup_strategy: WarmupStrategy,
     /// The path where to save the files
     destination: Option<PathBuf>,
     /// The observed run=off time-series.
@@ -70,19 +372,91 @@ pub struct GR6JModel {
     pub run_off_unit: RunOffUnit,
     /// Enable logging
     logging: bool,
+    /// The maximum value of the scaled net rainfall or evapotranspiration.
+    scaled_rainfall_cap: f64,
+    /// Run the hydrological units in parallel.
+    parallel_catchments: bool,
+    /// The precision of the stored results.
+    output_precision: OutputPrecision,
+    /// Whether to draw the log-scale panel in the FDC chart.
+    fdc_log_panel: bool,
+    /// The file format of the charts.
+    image_format: ImageFormat,
+    /// The colours of the charts.
+    pub chart_theme: ChartTheme,
+    /// The additional diagnostic charts to export.
+    diagnostic_charts: Vec<DiagnosticChart>,
+    /// The optional metrics to calculate.
+    optional_metrics: Option<OptionalMetrics>,
+    /// The number of decimal places of the numbers exported to the CSV files.
+    csv_precision: usize,
+    /// The factor applied to the precipitation series.
+    precip_correction: f64,
+    /// The factor applied to the potential evapotranspiration series.
+    pet_correction: f64,
+    /// Whether to write the run metadata file.
+    #[cfg(feature = "serde")]
+    write_metadata: bool,
+    /// The fraction of the effective rainfall routed through the first unit hydrograph.
+    uh_split: f64,
+    /// The fraction of the first unit hydrograph output sent to the exponential store.
+    routing_split: f64,
 }
 
 impl GR6JModel {
-    /// Create a new instance(s) of the GR6J model(s). More instances are created if more than
-    /// one hydrological unit is provided.
+    /// Check the model inputs without loading the model. This performs the same checks of
+    /// [`GR6JModel::new`] on the options, the length and continuity of the time series, the
+    /// run and warm-up periods, the destination folder and the missing or negative forcing
+    /// values, but the model and its output folder are not created. This can be used to validate
+    /// user inputs before committing to a run.
     ///
     /// # Arguments
     ///
     /// * `inputs`: The `GR6JModelInputs` struct containing the model input data.
     ///
-    /// returns: `Result<Self, LoadModelError>`
-    pub fn new(inputs: GR6JModelInputs) -> Result<Self, LoadModelError> {
-        let logging = inputs.logging.unwrap_or(true);
+    /// returns: `Result<(), LoadModelError>`
+    pub fn validate(inputs: &GR6JModelInputs) -> Result<(), LoadModelError> {
+        let scaled_rainfall_cap = inputs.scaled_rainfall_cap.unwrap_or(SCALED_RAINFALL_CAP);
+        if !scaled_rainfall_cap.is_finite() || scaled_rainfall_cap <= 0.0 {
+            return Err(LoadModelError::Generic(
+                "The scaled rainfall cap must be larger than 0".to_string(),
+            ));
+        }
+        for (name, split) in [
+            ("unit hydrograph", inputs.uh_split.unwrap_or(UH_SPLIT)),
+            ("routing", inputs.routing_split.unwrap_or(ROUTING_SPLIT)),
+        ] {
+            if !(0.0..=1.0).contains(&split) {
+                return Err(LoadModelError::Generic(format!(
+                    "The {} split must be between 0 and 1",
+                    name
+                )));
+            }
+        }
+        for (name, factor) in [
+            ("precipitation", inputs.precip_correction.unwrap_or(1.0)),
+            ("evapotranspiration", inputs.pet_correction.unwrap_or(1.0)),
+        ] {
+            if !factor.is_finite() || factor <= 0.0 {
+                return Err(LoadModelError::Generic(format!(
+                    "The {} correction factor must be larger than 0",
+                    name
+                )));
+            }
+        }
+        if cfg!(not(feature = "serde")) && inputs.write_metadata == Some(true) {
+            return Err(LoadModelError::Generic(
+                "The run metadata file can only be written with the serde feature".to_string(),
+            ));
+        }
+        for catchment_data in inputs.catchment.iter() {
+            let uh_exponent = catchment_data.uh_exponent.unwrap_or(UH_EXPONENT);
+            if !uh_exponent.is_finite() || uh_exponent <= 0.0 {
+                return Err(LoadModelError::Generic(
+                    "The unit hydrograph exponent must be larger than 0".to_string(),
+                ));
+            }
+        }
 
         // Check hydrological data
         if inputs.time.len() != inputs.precipitation.len() {
@@ -97,92 +471,165 @@ impl GR6JModel {
             }
         }
 
+        // insert the missing dates
+        let filled = match inputs.fill_gaps.unwrap_or(false) {
+            true => Some(fill_date_gaps(inputs)?),
+            false => None,
+        };
+        let (time, precipitation, evapotranspiration, observed_runoff, filled_dates) = match &filled {
+            Some(filled) => (
+                filled.time.as_slice(),
+                filled.precipitation.as_slice(),
+                filled.evapotranspiration.as_slice(),
+                filled.observed_runoff.as_deref(),
+                filled.filled_dates.as_slice(),
+            ),
+            None => (
+                inputs.time,
+                inputs.precipitation,
+                inputs.evapotranspiration,
+                inputs.observed_runoff,
+                &[][..],
+            ),
+        };
+
         // Check time
-        if inputs
-            .time
-            .windows(2)
-            .map(|ts| (ts[1] - ts[0]).num_days())
-            .max()
-            .unwrap()
-            != 1
-        {
+        if time.len() < 2 {
+            return Err(LoadModelError::Generic(
+                "The time vector must contain at least two dates".to_string(),
+            ));
+        }
+        if time.windows(2).map(|ts| (ts[1] - ts[0]).num_days()).max().unwrap() != 1 {
             return Err(LoadModelError::NotContinuousDates());
         }
-        if inputs.run_period.start < *inputs.time.first().unwrap() {
+        if inputs.run_period.start < *time.first().unwrap() {
             return Err(LoadModelError::DateOutsideTVector(
                 "run start date".to_string(),
                 "first date".to_string(),
             ));
         }
-        if inputs.run_period.end > *inputs.time.last().unwrap() {
+        if inputs.run_period.end > *time.last().unwrap() {
             return Err(LoadModelError::DateOutsideTVector(
                 "run end date".to_string(),
                 "last date".to_string(),
             ));
         }
+        let (warmup_strategy, warmup_period) = resolve_warmup_period(
+            time,
+            inputs.run_period,
+            inputs.warmup_strategy.unwrap_or_default(),
+            inputs.warmup_period,
+            false,
+        )?;
+
+        // Check the destination folder
+        if let Some(dest) = &inputs.destination {
+            if !dest.exists() {
+                return Err(LoadModelError::DestinationNotFound(dest.to_str().unwrap().to_string()));
+            }
+            if !dest.is_dir() {
+                return Err(LoadModelError::DestinationNotADirectory(
+                    dest.to_str().unwrap().to_string(),
+                ));
+            }
+        }
 
-        // initialise the warm-up period
-        let warmup_period = match inputs.warmup_period {
-            None => {
-                let warmup_end = inputs.run_period.start - TimeDelta::try_days(1).unwrap();
-                let mut warmup_start = warmup_end - TimeDelta::try_days(364).unwrap();
-                // check leap year
-                if warmup_end + TimeDelta::try_days(1).unwrap() != inputs.run_period.start {
-                    warmup_start -= TimeDelta::try_days(1).unwrap();
-                }
-                if logging {
-                    warn!(
-                        "Model warm-up period not defined. Using default period {}-{}",
-                        warmup_start, warmup_end
-                    );
-                }
+        // check the forcing used in the simulation. The missing values on the inserted dates are
+        // allowed
+        let (sim_time, sim_precipitation, sim_evapotranspiration) = simulation_forcing(
+            time,
+            precipitation,
+            evapotranspiration,
+            inputs.run_period,
+            warmup_strategy,
+            warmup_period,
+        );
+        let mask_filled_dates = |data: &[f64], time: &[NaiveDate]| -> Vec<f64> {
+            data.iter()
+                .zip(time)
+                .map(|(v, t)| if filled_dates.binary_search(t).is_ok() { 0.0 } else { *v })
+                .collect()
+        };
+        let i = vector_nan_indices(&mask_filled_dates(&sim_precipitation, &sim_time));
+        if !i.is_empty() {
+            return Err(LoadModelError::NanData("precipitation".to_string(), i));
+        }
+        let i = vector_nan_indices(&mask_filled_dates(&sim_evapotranspiration, &sim_time));
+        if !i.is_empty() {
+            return Err(LoadModelError::NanData("evapo-transpiration".to_string(), i));
+        }
+        if let Some(o) = observed_runoff {
+            let (start_index, end_index) = period_indices(time, inputs.run_period);
+            let o = &o[start_index..=end_index];
+            let i = vector_nan_indices(&mask_filled_dates(o, &sim_time[sim_time.len() - o.len()..]));
+            if !i.is_empty() {
+                return Err(LoadModelError::NanData("observed run-off".to_string(), i));
+            }
+        }
+        let i = vector_negative_indices(&sim_precipitation);
+        if !i.is_empty() {
+            return Err(LoadModelError::NegativeData("precipitation".to_string(), i));
+        }
+        let i = vector_negative_indices(&sim_evapotranspiration);
+        if !i.is_empty() {
+            return Err(LoadModelError::NegativeData("evapo-transpiration".to_string(), i));
+        }
+        Ok(())
+    }
 
-                if warmup_start >= inputs.time[0] {
-                    // one year is available
-                    Some(
-                        ModelPeriod::new(warmup_start, warmup_end)
-                            .map_err(|e| LoadModelError::Generic(e.to_string()))?,
-                    )
-                } else if inputs.run_period.start > inputs.time[0] {
-                    // reduced warm-up period
-                    if logging {
-                        warn!(
-                            "The input data is too short to define a one-year warm-up period. Period \
-                        will start from {} which is the first date in the time vector",
-                            inputs.time[0]
-                        );
-                    }
-                    Some(
-                        ModelPeriod::new(inputs.time[0], warmup_end)
-                            .map_err(|e| LoadModelError::Generic(e.to_string()))?,
-                    )
-                } else {
-                    // disregard warm-up period if there is no enough data
-                    if logging {
-                        warn!("The input data is too short to define a warm-up period");
-                    }
-                    None
-                }
+    /// Create a new instance(s) of the GR6J model(s). More instances are created if more than
+    /// one hydrological unit is provided. The inputs are checked with [`GR6JModel::validate`]
+    /// first.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs`: The `GR6JModelInputs` struct containing the model input data.
+    ///
+    /// returns: `Result<Self, LoadModelError>`
+    pub fn new(inputs: GR6JModelInputs) -> Result<Self, LoadModelError> {
+        Self::validate(&inputs)?;
+
+        let logging = inputs.logging.unwrap_or(true);
+        let scaled_rainfall_cap = inputs.scaled_rainfall_cap.unwrap_or(SCALED_RAINFALL_CAP);
+        let uh_split = inputs.uh_split.unwrap_or(UH_SPLIT);
+        let routing_split = inputs.routing_split.unwrap_or(ROUTING_SPLIT);
+        let precip_correction = inputs.precip_correction.unwrap_or(1.0);
+        let pet_correction = inputs.pet_correction.unwrap_or(1.0);
+
+        // insert the missing dates
+        let filled = match inputs.fill_gaps.unwrap_or(false) {
+            true => Some(fill_date_gaps(&inputs)?),
+            false => None,
+        };
+        let mut inputs: GR6JModelInputs<'_> = inputs;
+        if let Some(filled) = &filled {
+            inputs.time = &filled.time;
+            inputs.precipitation = &filled.precipitation;
+            inputs.evapotranspiration = &filled.evapotranspiration;
+            inputs.observed_runoff = filled.observed_runoff.as_deref();
+            if logging && !filled.filled_dates.is_empty() {
+                warn!(
+                    "Inserted {} missing dates in the time vector. The run-off on these dates is undefined",
+                    filled.filled_dates.len()
+                );
             }
-            Some(period) => {
-                // check date validity
-                if period.start >= inputs.run_period.start {
-                    return Err(LoadModelError::DateTooSmall("warm-up start".to_string()));
-                }
-                if period.end > inputs.run_period.start {
-                    return Err(LoadModelError::DateTooSmall("warm-up end".to_string()));
-                }
-                if (inputs.run_period.start - period.end).num_days() != 1 {
-                    return Err(LoadModelError::TooFarWarmUpPeriod(
-                        period.end.to_string(),
-                        inputs.run_period.start.to_string(),
-                    ));
+        }
+
+        // initialise the warm-up period
+        let (warmup_strategy, warmup_period) = resolve_warmup_period(
+            inputs.time,
+            inputs.run_period,
+            inputs.warmup_strategy.unwrap_or_default(),
+            inputs.warmup_period,
+            logging,
+        )?;
+        if logging {
+            if let Some(period) = &warmup_period {
+                info!("Model warm-up period set to: {}", period);
+                if warmup_strategy == WarmupStrategy::Climatology {
+                    info!("The warm-up uses the day-of-year mean of the precipitation and evapotranspiration");
                 }
-                Some(period)
             }
-        };
-        if warmup_period.is_some() && logging {
-            info!("Model warm-up period set to: {:?}", warmup_period.as_ref().unwrap());
         }
         if logging {
             info!(
@@ -191,80 +638,51 @@ impl GR6JModel {
             );
         }
 
-        // create the destination folder
-        let destination: Option<PathBuf> = if let Some(dest) = inputs.destination {
-            if !dest.exists() {
-                return Err(LoadModelError::DestinationNotFound(dest.to_str().unwrap().to_string()));
-            }
-            let destination = dest.join(Local::now().format("%Y%m%d_%H%M").to_string());
-            Some(destination)
-        } else {
-            None
-        };
+        // set the destination folder
+        let destination: Option<PathBuf> = inputs
+            .destination
+            .map(|dest| dest.join(Local::now().format("%Y%m%d_%H%M").to_string()));
 
         // truncate the data based on the warm-up and run periods
-        let start_date = match warmup_period {
-            None => inputs.run_period.start,
-            Some(p) => p.start,
-        };
-        let start_index = inputs.time.iter().position(|&r| r == start_date).unwrap();
-        let end_index = inputs.time.iter().position(|&r| r == inputs.run_period.end).unwrap();
-
-        // include warm-up
-        let time = inputs.time[start_index..end_index].to_owned();
-        let precipitation = inputs.precipitation[start_index..end_index].to_owned();
-        let evapotranspiration = inputs.evapotranspiration[start_index..end_index].to_owned();
+        let (time, mut precipitation, mut evapotranspiration) = simulation_forcing(
+            inputs.time,
+            inputs.precipitation,
+            inputs.evapotranspiration,
+            inputs.run_period,
+            warmup_strategy,
+            warmup_period,
+        );
 
         // exclude warm-up
-        let start_index = inputs.time.iter().position(|&r| r == inputs.run_period.start).unwrap();
-        let observed = inputs.observed_runoff.map(|q| q[start_index..end_index].to_owned());
-
-        // check the input data
-        let i = vector_nan_indices(precipitation.as_slice());
-        if !i.is_empty() {
-            return Err(LoadModelError::NanData("precipitation".to_string(), i));
-        }
-        let i = vector_nan_indices(evapotranspiration.as_slice());
-        if !i.is_empty() {
-            return Err(LoadModelError::NanData("evapo-transpiration".to_string(), i));
-        }
-        if let Some(ref o) = observed {
-            let i = vector_nan_indices(o.as_slice());
-            if !i.is_empty() {
-                return Err(LoadModelError::NanData("observed run-off".to_string(), i));
+        let (start_index, end_index) = period_indices(inputs.time, inputs.run_period);
+        let observed = inputs.observed_runoff.map(|q| q[start_index..=end_index].to_owned());
+
+        // apply the bias correction
+        if precip_correction != 1.0 || pet_correction != 1.0 {
+            precipitation.iter_mut().for_each(|p| *p *= precip_correction);
+            evapotranspiration.iter_mut().for_each(|e| *e *= pet_correction);
+            if logging {
+                info!(
+                    "Applied correction factors: precipitation x{}, evapotranspiration x{}",
+                    precip_correction, pet_correction
+                );
             }
         }
 
         let mut models: Vec<ModelData> = vec![];
         for catchment_data in inputs.catchment.to_vec().iter() {
             // initialise the reservoir levels
-            let mut int_store_levels = catchment_data.store_levels.unwrap_or_default();
-
-            // scale the levels
-            int_store_levels = StoreLevels {
-                production_store: int_store_levels.production_store * catchment_data.x1.value(),
-                routing_store: int_store_levels.routing_store * catchment_data.x3.value(),
-                exponential_store: int_store_levels.exponential_store,
-            };
-
-            // initialise the unit hydrographs
-            let unit_hydrograph1 = UnitHydrograph::new(UnitHydrographInputs {
-                uh_type: UnitHydrographType::T1,
-                time_constant: catchment_data.x4.value(),
-                exponent: 2.5,
-            });
-            let unit_hydrograph2 = UnitHydrograph::new(UnitHydrographInputs {
-                uh_type: UnitHydrographType::T2,
-                time_constant: catchment_data.x4.value(),
-                exponent: 2.5,
-            });
-
-            let internal_state = InternalState {
-                step: 0,
-                store_levels: int_store_levels,
-                unit_hydrograph1,
-                unit_hydrograph2,
-            };
+            let initial_store_levels = catchment_data
+                .store_levels
+                .unwrap_or_else(CatchmentData::default_store_levels);
+            let uh_exponent = catchment_data.uh_exponent.unwrap_or(UH_EXPONENT);
+            let internal_state = InternalState::new(
+                initial_store_levels,
+                &catchment_data.x1,
+                &catchment_data.x3,
+                &catchment_data.x4,
+                uh_exponent,
+            );
 
             models.push(ModelData {
                 area: catchment_data.area,
@@ -274,7 +692,10 @@ impl GR6JModel {
                 x4: *catchment_data.x4,
                 x5: *catchment_data.x5,
                 x6: *catchment_data.x6,
+                initial_store_levels,
+                uh_exponent,
                 state: internal_state,
+                warmup_end_state: None,
             })
         }
         Ok(GR6JModel {
@@ -282,89 +703,274 @@ impl GR6JModel {
             precipitation,
             evapotranspiration,
             collect_data_from: inputs.run_period.start,
+            run_period: inputs.run_period,
+            warmup_period,
+            warmup_strategy,
             models,
             destination,
             observed,
             run_off_unit: inputs.run_off_unit,
             logging,
+            scaled_rainfall_cap,
+            parallel_catchments: inputs.parallel_catchments.unwrap_or(false),
+            output_precision: inputs.output_precision.unwrap_or_default(),
+            fdc_log_panel: inputs.fdc_log_panel.unwrap_or(true),
+            image_format: inputs.image_format.unwrap_or_default(),
+            chart_theme: inputs.chart_theme.unwrap_or_default(),
+            diagnostic_charts: inputs.diagnostic_charts.unwrap_or_default(),
+            optional_metrics: inputs.optional_metrics,
+            csv_precision: inputs.csv_precision.unwrap_or(CSV_PRECISION),
+            precip_correction,
+            pet_correction,
+            #[cfg(feature = "serde")]
+            write_metadata: inputs.write_metadata.unwrap_or(true),
+            uh_split,
+            routing_split,
         })
     }
 
-    pub fn run(&mut self) -> Result<GR6JOutputs, RunModelError> {
-        if let Some(destination) = &self.destination {
-            if !destination.exists() {
-                create_dir(destination)
-                    .map_err(|_| RunModelError::DestinationNotWritable(destination.to_str().unwrap().to_string()))?;
-            }
-        }
+    /// Restore the initial state of all hydrological units, so that the model can be run again.
+    pub fn reset(&mut self) {
+        self.models.iter_mut().for_each(|model| model.reset());
+    }
 
-        let mut catchment_outputs: Vec<ModelStepDataVector> = vec![];
-        for model_index in 0..self.models.len() {
-            let mut outputs: Vec<ModelStepData> = vec![];
-            if self.logging {
-                debug!("Started run for hydrological unit {model_index}");
-            }
-            catchment_outputs.push({
-                loop {
-                    let out = self.step(model_index);
-                    if out.is_ok() {
-                        let step_data = out.unwrap();
-                        if step_data.time < self.collect_data_from {
-                            continue;
-                        }
-                        outputs.push(step_data);
-                    } else {
-                        break;
-                    }
-                }
-                ModelStepDataVector(outputs)
-            });
-        }
+    /// Replace the parameters of one hydrological unit and restore its initial state. Use this
+    /// with [`GR6JModel::reset`] to run the model with different parameter values without
+    /// truncating the forcing data and resolving the warm-up period again.
+    ///
+    /// # Arguments
+    ///
+    /// * `catchment_index`: The index of the hydrological unit.
+    /// * `params`: The values of the parameters from X1 to X6.
+    ///
+    /// returns: `Result<(), LoadModelError>`
+    pub fn set_parameters(&mut self, catchment_index: usize, params: [f64; 6]) -> Result<(), LoadModelError> {
+        let total_models = self.models.len();
+        let model = self.models.get_mut(catchment_index).ok_or_else(|| {
+            LoadModelError::Generic(format!(
+                "The hydrological unit #{} does not exist. The model has {} unit(s)",
+                catchment_index, total_models
+            ))
+        })?;
+
+        // validate all parameters before changing the model
+        let x1 = X1::new(params[0])?;
+        let x2 = X2::new(params[1])?;
+        let x3 = X3::new(params[2])?;
+        let x4 = X4::new(params[3])?;
+        let x5 = X5::new(params[4])?;
+        let x6 = X6::new(params[5])?;
+
+        model.x1 = *x1;
+        model.x2 = *x2;
+        model.x3 = *x3;
+        model.x4 = *x4;
+        model.x5 = *x5;
+        model.x6 = *x6;
+        model.reset();
+        Ok(())
+    }
 
-        if self.logging {
-            info!("Simulation is completed :)");
+    /// Load and run a model in one call. This is the same as calling [`GR6JModel::new`] and
+    /// [`GR6JModel::run`]; the errors raised when loading the model are returned as
+    /// [`RunModelError::CannotLoadModel`].
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs`: The `GR6JModelInputs` struct containing the model input data.
+    ///
+    /// returns: Result<GR6JOutputs, RunModelError>
+    pub fn run_once(inputs: GR6JModelInputs) -> Result<GR6JOutputs, RunModelError> {
+        GR6JModel::new(inputs)?.run()
+    }
+
+    /// Run the model from its current state. A second call continues from the end of the previous
+    /// simulation; call [`GR6JModel::reset`] first to run the same configured model again.
+    ///
+    /// returns: Result<GR6JOutputs, RunModelError>
+    pub fn run(&mut self) -> Result<GR6JOutputs, RunModelError> {
+        let mut results = GR6JOutputs::default();
+        self.run_into(&mut results)?;
+        Ok(results)
+    }
+
+    /// Run the model one day at a time. Each item contains the outputs of all hydrological units
+    /// for one day, after the warm-up period is skipped. Unlike [`GR6JModel::run`], the results
+    /// are not stored nor exported, therefore long simulations can be streamed or aggregated using
+    /// constant memory. The iterator stops after the first error.
+    ///
+    /// returns: `impl Iterator<Item = Result<DayOutput, RunModelError>>`
+    pub fn run_iter(&mut self) -> impl Iterator<Item = Result<DayOutput, RunModelError>> + '_ {
+        let mut completed = false;
+        std::iter::from_fn(move || {
+            if completed {
+                return None;
+            }
+            let conv_factor = self.run_off_unit.conv_factor();
+            if conv_factor <= 0.0 {
+                completed = true;
+                return Some(Err(RunModelError::WrongConversion()));
+            }
+
+            loop {
+                let mut catchment_outputs = Vec::with_capacity(self.models.len());
+                for model_index in 0..self.models.len() {
+                    match self.step(model_index) {
+                        Ok(step_data) => catchment_outputs.push(step_data),
+                        Err(RunModelError::ReachedSimulationEnd()) => {
+                            completed = true;
+                            return None;
+                        }
+                        Err(e) => {
+                            completed = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+
+                let time = catchment_outputs[0].time;
+                if time < self.collect_data_from {
+                    continue;
+                }
+                let run_off: f64 = catchment_outputs
+                    .iter()
+                    .zip(&self.models)
+                    .map(|(step_data, model)| step_data.run_off * model.area)
+                    .sum();
+                return Some(Ok(DayOutput {
+                    time,
+                    catchment_outputs,
+                    run_off: run_off * conv_factor,
+                }));
+            }
+        })
+    }
+
+    /// Run the model and write the results into an existing [`GR6JOutputs`]. The vectors in
+    /// `results` are cleared and reused, therefore repeated runs of models with the same number of
+    /// time steps and hydrological units do not allocate new memory for the results.
+    ///
+    /// # Arguments
+    ///
+    /// * `results`: The outputs to overwrite with the new results.
+    ///
+    /// returns: Result<(), RunModelError>
+    pub fn run_into(&mut self, results: &mut GR6JOutputs) -> Result<(), RunModelError> {
+        if let Some(destination) = &self.destination {
+            if !destination.exists() {
+                create_dir(destination)
+                    .map_err(|_| RunModelError::DestinationNotWritable(destination.to_str().unwrap().to_string()))?;
+            }
         }
-        let time = catchment_outputs[0].time();
 
-        // get the run off for each hydrological unit and scale it by area to get the volume
-        if self.logging {
-            debug!("Collecting run-off data");
+        // reuse the vectors of each hydrological unit
+        results
+            .catchment_outputs
+            .resize_with(self.models.len(), ModelStepDataVector::default);
+        results
+            .catchment_outputs
+            .iter_mut()
+            .for_each(|outputs| outputs.0.clear());
+
+        if self.parallel_catchments {
+            let time = &self.time;
+            let precipitation = &self.precipitation;
+            let evapotranspiration = &self.evapotranspiration;
+            let collect_data_from = self.collect_data_from;
+            let scaled_rainfall_cap = self.scaled_rainfall_cap;
+            let uh_split = self.uh_split;
+            let routing_split = self.routing_split;
+            let logging = self.logging;
+
+            // each unit owns its state and the run-off is only combined after all units
+            // complete, so the results do not depend on the order the threads run in
+            self.models
+                .par_iter_mut()
+                .zip(results.catchment_outputs.par_iter_mut())
+                .enumerate()
+                .try_for_each(|(model_index, (model, outputs))| {
+                    if logging {
+                        debug!("Started run for hydrological unit {model_index}");
+                    }
+                    let forcing = time.iter().zip(precipitation).zip(evapotranspiration);
+                    for ((t, p), e) in forcing.skip(model.state.step) {
+                        let step = model.state.step;
+                        let step_data = model.step(*t, *p, *e, scaled_rainfall_cap, uh_split, routing_split);
+                        check_stability(step, &step_data)?;
+                        model.record_warmup_state(&step_data, collect_data_from);
+                        if step_data.time >= collect_data_from {
+                            outputs.0.push(step_data);
+                        }
+                    }
+                    Ok::<(), RunModelError>(())
+                })?;
+        } else {
+            for day in self.run_iter() {
+                for (outputs, step_data) in results.catchment_outputs.iter_mut().zip(day?.catchment_outputs) {
+                    outputs.0.push(step_data);
+                }
+            }
         }
-        let mut run_offs: Vec<Vec<f64>> = vec![];
-        for (model_index, data) in catchment_outputs.iter().enumerate() {
-            run_offs.push(data.run_off(Some(self.models[model_index].area)));
+
+        if self.logging {
+            info!("Simulation is completed :)");
         }
+        results.time.clear();
+        results
+            .time
+            .extend(results.catchment_outputs[0].0.iter().map(|step_data| step_data.time));
 
         let conv_factor = self.run_off_unit.conv_factor();
         if conv_factor <= 0.0 {
             return Err(RunModelError::WrongConversion());
         }
 
-        // get the combined run off components for all hydrological units
-        let mut total_run_off: Vec<f64> = vec![];
-        for step_index in 0..run_offs[0].len() {
-            let mut q = 0.0;
-            for q_t in run_offs.iter() {
-                // convert from mm*km2/day to m3/day
-                q += q_t[step_index] * conv_factor;
+        // get the combined run off for all hydrological units. The run off of each unit is scaled
+        // by its area to get the volume
+        if self.logging {
+            debug!("Collecting run-off data");
+        }
+        results.base_run_off.clear();
+        results.base_run_off.resize(results.time.len(), 0.0);
+        results
+            .run_off_by_catchment
+            .resize_with(results.catchment_outputs.len(), Vec::new);
+        for (model_index, (data, unit_run_off)) in results
+            .catchment_outputs
+            .iter()
+            .zip(results.run_off_by_catchment.iter_mut())
+            .enumerate()
+        {
+            let area = self.models[model_index].area;
+            unit_run_off.clear();
+            for (q, step_data) in results.base_run_off.iter_mut().zip(&data.0) {
+                *q += step_data.run_off * area;
+                unit_run_off.push(step_data.run_off * area * conv_factor);
             }
-            total_run_off.push(q);
         }
-
-        let sim_fdc = Fdc::new(&total_run_off);
-        let mut results = GR6JOutputs {
-            catchment_outputs,
-            time,
-            run_off: total_run_off,
-            metrics: None,
-        };
+        results.run_off.clear();
+        results
+            .run_off
+            .extend(results.base_run_off.iter().map(|q| q * conv_factor));
+
+        let sim_fdc = Fdc::new(&results.run_off);
+        results.metrics = None;
+        results.observed.clone_from(&self.observed);
+        results.warmup_strategy = self.warmup_strategy;
+        results.precision = OutputPrecision::F64;
+        results.catchment_outputs_f32.clear();
+        results.run_off_f32.clear();
+        results.run_off_by_catchment_f32.clear();
+        results.base_run_off_f32.clear();
 
         // Calculate the simulation metrics
         if let Some(observed) = &self.observed {
-            results.metrics = Some(
-                CalibrationMetric::new(observed, results.run_off.as_ref(), None)
-                    .map_err(|e| RunModelError::CannotCalculateMetrics(e.to_string()))?,
-            );
+            results.metrics = Some(CalibrationMetric::new_with_time(
+                &results.time,
+                observed,
+                results.run_off.as_ref(),
+                self.optional_metrics.clone(),
+            )?);
         }
 
         // Export the data if a destination folder is provided
@@ -381,6 +987,20 @@ impl GR6JModel {
                 debug!("Exported run-off file {}", runoff_dest.to_str().unwrap().to_string());
             }
 
+            // Export residuals CSV file
+            if let Some(residuals) = results.residuals() {
+                let residual_dest = destination.join("Residuals.csv");
+                self.write_residual_file(
+                    results.time.as_ref(),
+                    &residuals,
+                    self.run_off_unit.unit_label(),
+                    &residual_dest,
+                )?;
+                if self.logging {
+                    debug!("Exported residual file {}", residual_dest.to_str().unwrap().to_string());
+                }
+            }
+
             // Export parameters
             match results.catchment_outputs.len() {
                 1 => {
@@ -409,268 +1029,657 @@ impl GR6JModel {
 
             // Export FDC
             let fdc_dest = destination.join("FDC.csv");
-            sim_fdc.to_csv(&fdc_dest, self.run_off_unit.unit_label())?;
+            sim_fdc.to_csv(&fdc_dest, self.run_off_unit.unit_label(), self.csv_precision)?;
             if self.logging {
                 debug!("Exported FDC CSV file {}", fdc_dest.to_str().unwrap().to_string());
             }
 
             // Generate charts
-            generate_summary_chart(self, &results, destination)
+            generate_summary_chart(self, results, destination, self.image_format)
                 .map_err(|e| RunModelError::CannotGenerateChart("summary".to_string(), e.to_string()))?;
 
-            let obs_fdc = self.observed.as_ref().map(|q| Fdc::new(q));
-            save_fdc_chart(self, sim_fdc, obs_fdc, destination)
+            if self.models.len() > 1 {
+                let dest = destination.join(format!("Catchment_contributions.{}", self.image_format.extension()));
+                save_catchment_contribution_chart(
+                    &results.time,
+                    &results.run_off_by_catchment,
+                    &results.run_off,
+                    &dest,
+                    &self.run_off_unit,
+                    self.image_format,
+                )
+                .map_err(|e| RunModelError::CannotGenerateChart("catchment contribution".to_string(), e.to_string()))?;
+                if self.logging {
+                    debug!("Exported catchment contribution chart");
+                }
+            }
+
+            let (obs_fdc, log_panel) = self.fdc_chart_options(&sim_fdc);
+            save_fdc_chart(self, sim_fdc, obs_fdc, log_panel, destination, self.image_format)
                 .map_err(|e| RunModelError::CannotGenerateChart("fdc".to_string(), e.to_string()))?;
             if self.logging {
                 debug!("Exported flow duration curve chart");
             }
 
+            if let Some(observed) = &self.observed {
+                if self.diagnostic_charts.contains(&DiagnosticChart::CumulativeFlow) {
+                    let dest = destination.join(format!("Cumulative_flow.{}", self.image_format.extension()));
+                    save_cumulative_flow_chart(
+                        &results.time,
+                        &results.run_off,
+                        observed,
+                        "Cumulative run-off".to_string(),
+                        &dest,
+                        &self.run_off_unit,
+                        self.image_format,
+                        &self.chart_theme,
+                    )
+                    .map_err(|e| RunModelError::CannotGenerateChart("cumulative flow".to_string(), e.to_string()))?;
+                    if self.logging {
+                        debug!("Exported cumulative flow chart");
+                    }
+                }
+
+                if self.diagnostic_charts.contains(&DiagnosticChart::Scatter) {
+                    let dest = destination.join(format!("Scatter.{}", self.image_format.extension()));
+                    save_scatter_chart(
+                        observed,
+                        &results.run_off,
+                        "Observed vs. simulated run-off".to_string(),
+                        &dest,
+                        &self.run_off_unit,
+                        self.image_format,
+                        &self.chart_theme,
+                    )
+                    .map_err(|e| RunModelError::CannotGenerateChart("scatter".to_string(), e.to_string()))?;
+                    if self.logging {
+                        debug!("Exported scatter chart");
+                    }
+                }
+            }
+
+            if self.diagnostic_charts.contains(&DiagnosticChart::MonthlyRegime) {
+                let dest = destination.join(format!("Monthly_regime.{}", self.image_format.extension()));
+                save_monthly_regime_chart(
+                    &results.time,
+                    &results.run_off,
+                    &dest,
+                    &self.run_off_unit,
+                    self.image_format,
+                    &self.chart_theme,
+                )
+                .map_err(|e| RunModelError::CannotGenerateChart("monthly regime".to_string(), e.to_string()))?;
+                if self.logging {
+                    debug!("Exported monthly regime chart");
+                }
+            }
+
             // Export metrics
             if let Some(ref metrics) = results.metrics {
                 let metric_dest = destination.join("Metrics.csv");
                 let metric_dest_string = metric_dest.to_str().unwrap().to_string();
-                metrics.to_csv(metric_dest)?;
+                metrics.to_csv(metric_dest, Some(self.csv_precision))?;
                 if self.logging {
                     debug!("Exported metric file {}", metric_dest_string);
                 }
             }
+
+            // Export the run metadata
+            #[cfg(feature = "serde")]
+            if self.write_metadata {
+                let metadata_dest = destination.join("run_metadata.json");
+                self.write_metadata_file(&metadata_dest)?;
+                if self.logging {
+                    debug!("Exported metadata file {}", metadata_dest.to_str().unwrap().to_string());
+                }
+            }
         }
 
-        Ok(results)
+        // the metrics and exported files use the double-precision results
+        if self.output_precision == OutputPrecision::F32 {
+            results.to_single_precision();
+        }
+
+        Ok(())
     }
 
-    /// Advance time for one model.
+    /// Get the production, routing and exponential store levels (mm) combined across all the
+    /// hydrological units. The levels of each unit are weighted by the unit area. With one unit,
+    /// the levels are returned unchanged.
     ///
     /// # Arguments
     ///
-    /// * `model_index`: The index model to step.
+    /// * `outputs`: The results returned by [`GR6JModel::run`].
     ///
-    /// returns: Result<ModelStepData, RunModelError>
-    pub fn step(&mut self, model_index: usize) -> Result<ModelStepData, RunModelError> {
-        let b = 0.9;
-        let c = 0.4;
-        let x1 = self.models[model_index].x1.value();
-        let x3 = self.models[model_index].x3.value();
-        let x6 = self.models[model_index].x6.value();
+    /// returns: (Vec<f64>, Vec<f64>, Vec<f64>)
+    pub fn weighted_store_levels(&self, outputs: &GR6JOutputs) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let levels: Vec<Vec<[f64; 3]>> = match outputs.precision {
+            OutputPrecision::F64 => outputs
+                .catchment_outputs
+                .iter()
+                .map(|unit| {
+                    unit.0
+                        .iter()
+                        .map(|d| {
+                            [
+                                d.store_levels.production_store,
+                                d.store_levels.routing_store,
+                                d.store_levels.exponential_store,
+                            ]
+                        })
+                        .collect()
+                })
+                .collect(),
+            OutputPrecision::F32 => outputs
+                .catchment_outputs_f32
+                .iter()
+                .map(|unit| {
+                    unit.iter()
+                        .map(|d| {
+                            [
+                                d.production_store as f64,
+                                d.routing_store as f64,
+                                d.exponential_store as f64,
+                            ]
+                        })
+                        .collect()
+                })
+                .collect(),
+        };
 
-        let step = self.models[model_index].state.step;
-        if step == self.precipitation.len() {
-            return Err(RunModelError::ReachedSimulationEnd());
+        let weights: Vec<f64> = if self.models.len() == 1 {
+            vec![1.0]
+        } else {
+            let total_area: f64 = self.models.iter().map(|m| m.area).sum();
+            self.models.iter().map(|m| m.area / total_area).collect()
+        };
+        let steps = levels.first().map_or(0, |l| l.len());
+        let mut weighted = [vec![0.0; steps], vec![0.0; steps], vec![0.0; steps]];
+        for (weight, unit_levels) in weights.iter().zip(levels) {
+            for (step, step_levels) in unit_levels.iter().enumerate() {
+                for (series, level) in weighted.iter_mut().zip(step_levels) {
+                    series[step] += level * weight;
+                }
+            }
         }
 
-        debug!("Running step #{} - {}", step, self.time[step]);
-
-        let p = self.precipitation[step];
-        let e = self.evapotranspiration[step];
-
-        let storage_ratio = self.models[model_index].state.store_levels.production_store / x1;
-
-        // update production store level
-        let mut net_p = 0.0;
-        let mut pr = 0.0;
-        let mut storage_p = 0.0;
-        #[allow(unused_assignments)]
-        let mut actual_e = 0.0;
-        if p < e {
-            let net_e = e - p;
-            let scaled_e = (net_e / x1).min(13.0);
-            let exp_scaled_e = (2.0 * scaled_e).exp();
-
-            let tws = (exp_scaled_e - 1.0) / (exp_scaled_e + 1.0);
-            let storage_e = self.models[model_index].state.store_levels.production_store * (2.0 - storage_ratio) * tws
-                / (1.0 + (1.0 - storage_ratio) * tws);
-
-            actual_e = storage_e + p;
-            self.models[model_index].state.store_levels.production_store -= storage_e;
-        } else {
-            actual_e = e;
-            net_p = p - e;
-            let scaled_p = (net_p / x1).min(13.0);
-            let exp_scaled_p = (2.0 * scaled_p).exp();
+        let [production_store, routing_store, exponential_store] = weighted;
+        (production_store, routing_store, exponential_store)
+    }
 
-            let tws = (exp_scaled_p - 1.0) / (exp_scaled_p + 1.0);
-            storage_p = x1 * (1.0 - storage_ratio.powi(2)) * tws / (1.0 + storage_ratio * tws);
-            pr = net_p - storage_p;
-            self.models[model_index].state.store_levels.production_store += storage_p;
+    /// Get the response of the model to a unit rainfall pulse (1 mm on the first day followed by
+    /// no rainfall and no evapotranspiration). Each hydrological unit starts from its initial store
+    /// levels, and the run-off that the stores would release without the pulse is subtracted so
+    /// the series only contains the contribution of the pulse. The baseline cannot be avoided by
+    /// starting from empty stores because the exponential store releases water at any level (even
+    /// when its level is 0 or negative), therefore the raw run-off always includes a recession
+    /// that does not depend on the pulse. The run-off of all units is combined using the unit
+    /// areas (mm*km2/day) like [`GR6JOutputs::base_run_off`], so that the response of the model
+    /// can be compared with its simulated run-off. The model itself is not changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `days`: The number of days to simulate.
+    ///
+    /// returns: Vec<f64>
+    pub fn impulse_response(&self, days: usize) -> Vec<f64> {
+        let mut response = vec![0.0; days];
+        for model in self.models.iter() {
+            let mut pulse_model = model.fresh_copy();
+            let mut baseline_model = model.fresh_copy();
+            let dates = self.run_period.start.iter_days().take(days);
+            for (day, (time, q)) in dates.zip(response.iter_mut()).enumerate() {
+                let p = if day == 0 { 1.0 } else { 0.0 };
+                let pulse = pulse_model.step(
+                    time,
+                    p,
+                    0.0,
+                    self.scaled_rainfall_cap,
+                    self.uh_split,
+                    self.routing_split,
+                );
+                let baseline = baseline_model.step(
+                    time,
+                    0.0,
+                    0.0,
+                    self.scaled_rainfall_cap,
+                    self.uh_split,
+                    self.routing_split,
+                );
+                *q += (pulse.run_off - baseline.run_off) * model.area;
+            }
         }
+        response
+    }
 
-        if self.models[model_index].state.store_levels.production_store < 0.0 {
-            self.models[model_index].state.store_levels.production_store = 0.0;
+    /// Get the FDC of the observed run-off and whether the FDC chart can have the log-scale panel.
+    /// The panel is not drawn when the run-off contains zero or negative values.
+    ///
+    /// # Arguments
+    ///
+    /// * `simulated`: The FDC of the simulated run-off.
+    ///
+    /// returns: `(Option<Fdc>, bool)`
+    fn fdc_chart_options(&self, simulated: &Fdc) -> (Option<Fdc>, bool) {
+        let observed = self.observed.as_ref().map(|q| Fdc::new(q));
+        let mut log_panel = self.fdc_log_panel;
+        if log_panel
+            && (simulated.has_non_positive_values() || observed.as_ref().is_some_and(|f| f.has_non_positive_values()))
+        {
+            if self.logging {
+                warn!("The run-off contains zero or negative values. The FDC log-scale panel will not be drawn");
+            }
+            log_panel = false;
         }
+        (observed, log_panel)
+    }
 
-        // update percolation in production store
-        let percolation = self.models[model_index].state.store_levels.production_store
-            * (1.
-                - (1. + (self.models[model_index].state.store_levels.production_store / (9. / 4. * x1)).powi(4))
-                    .powf(-0.25));
-        self.models[model_index].state.store_levels.production_store -= percolation;
-        pr += percolation;
+    /// Render the chart with the input data and the simulated run-off as a PNG image in memory.
+    /// This is the same chart exported to the `Summary.png` file, but no file is written.
+    ///
+    /// # Arguments
+    ///
+    /// * `results`: The results returned by [`GR6JModel::run`].
+    ///
+    /// returns: `Result<Vec<u8>, RunModelError>`
+    pub fn summary_chart_bytes(&self, results: &GR6JOutputs) -> Result<Vec<u8>, RunModelError> {
+        summary_chart_png(self, results)
+            .map_err(|e| RunModelError::CannotGenerateChart("summary".to_string(), e.to_string()))
+    }
 
-        // split the effective rainfall into the two routing components and generate the two new hydrographs
-        let precipitation_uh1 = pr * b;
-        let precipitation_uh2 = pr * (1.0 - b);
+    /// Render the flow duration curve chart as a PNG image in memory. This is the same chart
+    /// exported to the `FDC.png` file, but no file is written.
+    ///
+    /// # Arguments
+    ///
+    /// * `results`: The results returned by [`GR6JModel::run`].
+    ///
+    /// returns: `Result<Vec<u8>, RunModelError>`
+    pub fn fdc_chart_bytes(&self, results: &GR6JOutputs) -> Result<Vec<u8>, RunModelError> {
+        let sim_fdc = Fdc::new(&results.run_off);
+        let (obs_fdc, log_panel) = self.fdc_chart_options(&sim_fdc);
+        fdc_chart_png(self, sim_fdc, obs_fdc, log_panel)
+            .map_err(|e| RunModelError::CannotGenerateChart("fdc".to_string(), e.to_string()))
+    }
 
-        // Combine the two hydrographs
-        let x4 = self.models[model_index].x4.value() as i32;
-        self.models[model_index]
-            .state
-            .unit_hydrograph1
-            .convolution(x4, precipitation_uh1);
-        self.models[model_index]
-            .state
-            .unit_hydrograph2
-            .convolution(x4, precipitation_uh2);
+    /// Get the values of the two unit hydrographs (UH1 and UH2) for each hydrological unit. These
+    /// contain the effective rainfall that is still being routed and, together with the store
+    /// levels, describe the full state of the model.
+    ///
+    /// returns: Vec<(Vec<f64>, Vec<f64>)>
+    pub fn uh_state(&self) -> Vec<(Vec<f64>, Vec<f64>)> {
+        self.models
+            .iter()
+            .map(|model| {
+                (
+                    model.state.unit_hydrograph1.values.clone(),
+                    model.state.unit_hydrograph2.values.clone(),
+                )
+            })
+            .collect()
+    }
 
-        // potential inter-catchment semi-exchange
-        let exchange = self.models[model_index].x2.value()
-            * (self.models[model_index].state.store_levels.routing_store / x3 - self.models[model_index].x5.value());
+    /// Get the ordinates of the two unit hydrographs (UH1 and UH2) of a hydrological unit. The
+    /// ordinates only depend on X4 and the unit hydrograph exponent and each set sums to 1; the
+    /// model does not need to run.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_index`: The index of the hydrological unit.
+    ///
+    /// returns: (Vec<f64>, Vec<f64>)
+    pub fn unit_hydrograph_ordinates(&self, model_index: usize) -> (Vec<f64>, Vec<f64>) {
+        let state = &self.models[model_index].state;
+        (
+            state.unit_hydrograph1.ordinates.clone(),
+            state.unit_hydrograph2.ordinates.clone(),
+        )
+    }
 
-        // routing store
-        let new_routing_store = self.models[model_index].state.store_levels.routing_store
-            + (1.0 - c) * self.models[model_index].state.unit_hydrograph1.values[0]
-            + exchange;
-        let exchange_from_routing_store = {
-            if new_routing_store < 0.0 {
-                -(new_routing_store - exchange)
-            } else {
-                exchange
-            }
-        };
-        self.models[model_index].state.store_levels.routing_store = new_routing_store;
-        if self.models[model_index].state.store_levels.routing_store < 0.0 {
-            self.models[model_index].state.store_levels.routing_store = 0.0;
+    /// Set the values of the two unit hydrographs (UH1 and UH2) for each hydrological unit. Use
+    /// this with the state returned by [`GR6JModel::uh_state`] to restart a simulation exactly.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The values of UH1 and UH2 for each hydrological unit.
+    ///
+    /// returns: `Result<(), LoadModelError>`
+    pub fn set_uh_state(&mut self, state: Vec<(Vec<f64>, Vec<f64>)>) -> Result<(), LoadModelError> {
+        if state.len() != self.models.len() {
+            return Err(LoadModelError::Generic(format!(
+                "The unit hydrograph state has {} unit(s), but the model has {} unit(s)",
+                state.len(),
+                self.models.len()
+            )));
         }
-
-        let scaled_routing_store = (self.models[model_index].state.store_levels.routing_store / x3).powi(4);
-        let routing_store_outflow = self.models[model_index].state.store_levels.routing_store
-            * (1. - 1. / f64::sqrt(f64::sqrt(1. + scaled_routing_store)));
-        self.models[model_index].state.store_levels.routing_store -= routing_store_outflow;
-
-        // exponential store
-        self.models[model_index].state.store_levels.exponential_store +=
-            c * self.models[model_index].state.unit_hydrograph1.values[0] + exchange;
-        let scaled_exp_store = self.models[model_index].state.store_levels.exponential_store / x6.max(-33.0).min(33.0);
-        let exponential_store_outflow = {
-            if scaled_exp_store > 7.0 {
-                self.models[model_index].state.store_levels.exponential_store + x6 / scaled_exp_store.exp()
-            } else if scaled_exp_store < -7.0 {
-                x6 * scaled_exp_store.exp()
-            } else {
-                x6 * (scaled_exp_store.exp() + 1.0).ln()
-            }
-        };
-        self.models[model_index].state.store_levels.exponential_store -= exponential_store_outflow;
-
-        // run-off from outflow from UH2 branch after exchange
-        let exchange_from_direct_branch = {
-            if self.models[model_index].state.unit_hydrograph2.values[0] + exchange < 0.0 {
-                -self.models[model_index].state.unit_hydrograph2.values[0]
-            } else {
-                exchange
+        for (model_index, (model, (uh1, uh2))) in self.models.iter().zip(&state).enumerate() {
+            let expected_sizes = (
+                model.state.unit_hydrograph1.uh_type.size(),
+                model.state.unit_hydrograph2.uh_type.size(),
+            );
+            if (uh1.len(), uh2.len()) != expected_sizes {
+                return Err(LoadModelError::Generic(format!(
+                    "The unit hydrographs of unit #{} must have {} and {} values, but {} and {} were given",
+                    model_index,
+                    expected_sizes.0,
+                    expected_sizes.1,
+                    uh1.len(),
+                    uh2.len()
+                )));
             }
-        };
-        let outflow_from_uh2_branch = (self.models[model_index].state.unit_hydrograph2.values[0] + exchange).max(0.0);
-
-        // total run-off
-        let run_off = routing_store_outflow + outflow_from_uh2_branch + exponential_store_outflow;
-
-        // update the step index
-        self.models[model_index].state.step += 1;
+        }
 
-        Ok(ModelStepData {
-            time: self.time[self.models[model_index].state.step - 1],
-            evapotranspiration: e,
-            precipitation: p,
-            net_rainfall: net_p,
-            store_levels: self.models[model_index].state.store_levels,
-            storage_p,
-            actual_evapotranspiration: actual_e,
-            percolation,
-            pr,
-            exchange,
-            exchange_from_routing_store,
-            exchange_from_direct_branch,
-            actual_exchange: exchange_from_routing_store + exchange_from_direct_branch + exchange,
-            routing_store_outflow,
-            exponential_store_outflow,
-            outflow_from_uh2_branch,
-            run_off,
-        })
+        for (model, (uh1, uh2)) in self.models.iter_mut().zip(state) {
+            model.state.unit_hydrograph1.values = uh1;
+            model.state.unit_hydrograph2.values = uh2;
+        }
+        Ok(())
     }
 
-    /// Export the run-off data to a CSV file.
+    /// Get a snapshot of the internal state of a hydrological unit.
     ///
     /// # Arguments
     ///
-    /// * `time`: The vector with the date.
-    /// * `total_run_off`: The vector with the run-off values.
-    /// * `run_off_unit`: The run-off unit of measurement.
-    /// * `destination`: The path to the CSV file.
+    /// * `model_index`: The index of the hydrological unit.
     ///
-    /// returns: Result<(), csv::Error>
-    fn write_run_off_file(
-        &self,
-        time: &[NaiveDate],
-        total_run_off: &[f64],
-        run_off_unit: &str,
-        destination: &Path,
-    ) -> Result<(), csv::Error> {
-        let mut wtr = Writer::from_path(destination)?;
-        wtr.write_record(["Date", format!("Run-off ({})", run_off_unit).as_str()])?;
+    /// returns: `ModelState`
+    pub fn state(&self, model_index: usize) -> ModelState {
+        let state = &self.models[model_index].state;
+        ModelState {
+            step: state.step,
+            store_levels: state.store_levels,
+            unit_hydrograph1: state.unit_hydrograph1.values.clone(),
+            unit_hydrograph2: state.unit_hydrograph2.values.clone(),
+        }
+    }
 
-        for (step_index, q) in total_run_off.iter().enumerate() {
-            wtr.write_record(&[time[step_index].to_string(), q.to_string()])?;
-            wtr.flush()?;
+    /// Restore the internal state of a hydrological unit from a snapshot taken with
+    /// [`GR6JModel::state`]. The simulation then continues from the step stored in the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_index`: The index of the hydrological unit.
+    /// * `state`: The state to restore.
+    ///
+    /// returns: `Result<(), LoadModelError>`
+    pub fn set_state(&mut self, model_index: usize, state: ModelState) -> Result<(), LoadModelError> {
+        let total_steps = self.precipitation.len();
+        let total_models = self.models.len();
+        let model = self.models.get_mut(model_index).ok_or_else(|| {
+            LoadModelError::Generic(format!(
+                "The hydrological unit #{} does not exist. The model has {} unit(s)",
+                model_index, total_models
+            ))
+        })?;
+
+        let expected_sizes = (
+            model.state.unit_hydrograph1.uh_type.size(),
+            model.state.unit_hydrograph2.uh_type.size(),
+        );
+        if (state.unit_hydrograph1.len(), state.unit_hydrograph2.len()) != expected_sizes {
+            return Err(LoadModelError::Generic(format!(
+                "The unit hydrographs of unit #{} must have {} and {} values, but {} and {} were given",
+                model_index,
+                expected_sizes.0,
+                expected_sizes.1,
+                state.unit_hydrograph1.len(),
+                state.unit_hydrograph2.len()
+            )));
+        }
+        if state.step > total_steps {
+            return Err(LoadModelError::Generic(format!(
+                "The state step #{} is larger than the number of time steps ({})",
+                state.step, total_steps
+            )));
         }
+
+        model.state.step = state.step;
+        model.state.store_levels = state.store_levels;
+        model.state.unit_hydrograph1.values = state.unit_hydrograph1;
+        model.state.unit_hydrograph2.values = state.unit_hydrograph2;
         Ok(())
     }
 
-    /// Export the list of parameters for one hydrological unit.
+    /// Get a summary of the model configuration.
+    ///
+    /// returns: ModelDescription
+    pub fn describe(&self) -> ModelDescription {
+        ModelDescription {
+            catchments: self
+                .models
+                .iter()
+                .map(|m| CatchmentDescription {
+                    area: m.area,
+                    x1: m.x1.value(),
+                    x2: m.x2.value(),
+                    x3: m.x3.value(),
+                    x4: m.x4.value(),
+                    x5: m.x5.value(),
+                    x6: m.x6.value(),
+                })
+                .collect(),
+            run_period: self.run_period,
+            warmup_period: self.warmup_period,
+            warmup_strategy: self.warmup_strategy,
+            run_off_unit: self.run_off_unit.clone(),
+            has_observed_runoff: self.observed.is_some(),
+            precip_correction: self.precip_correction,
+            pet_correction: self.pet_correction,
+        }
+    }
+
+    /// Advance time for one model.
     ///
     /// # Arguments
     ///
-    /// * `data`: The model data.
-    /// * `destination`: The path to the CSV file.
+    /// * `model_index`: The index model to step.
+    ///
+    /// returns: Result<ModelStepData, RunModelError>
+    pub fn step(&mut self, model_index: usize) -> Result<ModelStepData, RunModelError> {
+        let step = self.models[model_index].state.step;
+        if step == self.precipitation.len() {
+            return Err(RunModelError::ReachedSimulationEnd());
+        }
+
+        debug!("Running step #{} - {}", step, self.time[step]);
+
+        let step_data = self.models[model_index].step(
+            self.time[step],
+            self.precipitation[step],
+            self.evapotranspiration[step],
+            self.scaled_rainfall_cap,
+            self.uh_split,
+            self.routing_split,
+        );
+        check_stability(step, &step_data)?;
+        self.models[model_index].record_warmup_state(&step_data, self.collect_data_from);
+        Ok(step_data)
+    }
+
+    /// Get the store levels of a hydrological unit at the end of the warm-up period, for example to
+    /// initialise the stores of a separate run (see [`StoreLevels::absolute`]). This is available
+    /// once the warm-up period has been simulated with [`GR6JModel::run`] or [`GR6JModel::step`].
+    ///
+    /// # Arguments
+    ///
+    /// * `model_index`: The index of the hydrological unit.
+    ///
+    /// returns: `Option<StoreLevels>`. This is None when the model has no warm-up period or the
+    /// warm-up has not been simulated yet.
+    pub fn warmup_end_state(&self, model_index: usize) -> Option<StoreLevels> {
+        self.models[model_index].warmup_end_state
+    }
+
+    /// Advance time for one model using the provided forcing instead of the data loaded in the
+    /// model. This can be used to couple the model to a live data feed without creating a new
+    /// model every day. The date of the step follows the last simulated date and can go past the
+    /// end of the loaded time series. The precipitation and evapotranspiration correction factors
+    /// are not applied to the forcing.
+    ///
+    /// Mixing this with [`GR6JModel::run`] or [`GR6JModel::run_iter`] is undefined, because
+    /// these read the loaded forcing from the current step of each unit. Call
+    /// [`GR6JModel::reset`] before switching between the two.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_index`: The index model to step.
+    /// * `precipitation`: The total precipitation for the day (mm).
+    /// * `evapotranspiration`: The potential evapotranspiration for the day (mm).
+    ///
+    /// returns: Result<ModelStepData, RunModelError>
+    pub fn advance(
+        &mut self,
+        model_index: usize,
+        precipitation: f64,
+        evapotranspiration: f64,
+    ) -> Result<ModelStepData, RunModelError> {
+        let step = self.models[model_index].state.step;
+        let time = self.time[0] + TimeDelta::try_days(step as i64).unwrap();
+        debug!("Advancing step #{} - {}", step, time);
+
+        let step_data = self.models[model_index].step(
+            time,
+            precipitation,
+            evapotranspiration,
+            self.scaled_rainfall_cap,
+            self.uh_split,
+            self.routing_split,
+        );
+        check_stability(step, &step_data)?;
+        Ok(step_data)
+    }
+
+    /// Export the run-off data to a CSV file.
+    ///
+    /// # Arguments
+    ///
+    /// * `time`: The vector with the date.
+    /// * `total_run_off`: The vector with the run-off values.
+    /// * `run_off_unit`: The run-off unit of measurement.
+    /// * `destination`: The path to the CSV file.
+    ///
+    /// returns: Result<(), csv::Error>
+    fn write_run_off_file(
+        &self,
+        time: &[NaiveDate],
+        total_run_off: &[f64],
+        run_off_unit: &str,
+        destination: &Path,
+    ) -> Result<(), csv::Error> {
+        let mut wtr = Writer::from_path(destination)?;
+        wtr.write_record(["Date", format!("Run-off ({})", run_off_unit).as_str()])?;
+
+        for (step_index, q) in total_run_off.iter().enumerate() {
+            wtr.write_record(&[time[step_index].to_string(), format_float(*q, self.csv_precision)])?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Get the metadata of a model run.
+    ///
+    /// returns: RunMetadata
+    pub fn metadata(&self) -> RunMetadata {
+        RunMetadata {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: Local::now().to_rfc3339(),
+            model: self.describe(),
+            forcing_length: self.time.len(),
+        }
+    }
+
+    /// Export the run metadata to a JSON file.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination`: The path to the JSON file.
+    ///
+    /// returns: Result<(), RunModelError>
+    #[cfg(feature = "serde")]
+    fn write_metadata_file(&self, destination: &Path) -> Result<(), RunModelError> {
+        let content = serde_json::to_string_pretty(&self.metadata())
+            .map_err(|e| RunModelError::CannotExportMetadata(e.to_string()))?;
+        fs::write(destination, content).map_err(|e| RunModelError::CannotExportMetadata(e.to_string()))
+    }
+
+    /// Export the residuals (observed minus simulated run-off).
+    ///
+    /// # Arguments
+    ///
+    /// * `time`: The vector with the date.
+    /// * `residuals`: The vector with the residuals.
+    /// * `run_off_unit`: The run-off unit of measurement.
+    /// * `destination`: The path to the CSV file.
+    ///
+    /// returns: Result<(), csv::Error>
+    fn write_residual_file(
+        &self,
+        time: &[NaiveDate],
+        residuals: &[f64],
+        run_off_unit: &str,
+        destination: &Path,
+    ) -> Result<(), csv::Error> {
+        let mut wtr = Writer::from_path(destination)?;
+        wtr.write_record(["Date", format!("Residual ({})", run_off_unit).as_str()])?;
+
+        for (t, residual) in time.iter().zip(residuals) {
+            wtr.write_record(&[t.to_string(), format_float(*residual, self.csv_precision)])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Export the list of parameters for one hydrological unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: The model data.
+    /// * `destination`: The path to the CSV file.
     ///
     /// returns: Result<(), csv::Error>
     fn write_parameter_file(&self, data: &ModelData, destination: &Path) -> Result<(), csv::Error> {
         let mut wtr = Writer::from_path(destination)?;
         wtr.write_record(["Parameter", "Value", "Unit", "Description"])?;
 
-        wtr.write_record(["Area", data.area.to_string().as_str(), "Km2", "Catchment area"])?;
+        wtr.write_record([
+            "Area",
+            format_float(data.area, self.csv_precision).as_str(),
+            "Km2",
+            "Catchment area",
+        ])?;
         wtr.write_record([
             "X1",
-            data.x1.value().to_string().as_str(),
+            format_float(data.x1.value(), self.csv_precision).as_str(),
             X1::unit(),
             X1::description(),
         ])?;
         wtr.write_record([
             "X2",
-            data.x2.value().to_string().as_str(),
+            format_float(data.x2.value(), self.csv_precision).as_str(),
             X2::unit(),
             X2::description(),
         ])?;
         wtr.write_record([
             "X3",
-            data.x3.value().to_string().as_str(),
+            format_float(data.x3.value(), self.csv_precision).as_str(),
             X3::unit(),
             X3::description(),
         ])?;
         wtr.write_record([
             "X4",
-            data.x4.value().to_string().as_str(),
+            format_float(data.x4.value(), self.csv_precision).as_str(),
             X4::unit(),
             X4::description(),
         ])?;
         wtr.write_record([
             "X5",
-            data.x5.value().to_string().as_str(),
+            format_float(data.x5.value(), self.csv_precision).as_str(),
             X5::unit(),
             X5::description(),
         ])?;
         wtr.write_record([
             "X6",
-            data.x6.value().to_string().as_str(),
+            format_float(data.x6.value(), self.csv_precision).as_str(),
             X6::unit(),
             X6::description(),
         ])?;
@@ -679,20 +1688,284 @@ impl GR6JModel {
     }
 }
 
+/// Get the start and end dates of the calendar year preceding the run start date. The period has
+/// 366 days when it includes the 29th of February.
+///
+/// # Arguments
+///
+/// * `run_start`: The first day of the run period.
+///
+/// returns: (NaiveDate, NaiveDate)
+fn default_warmup_dates(run_start: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let warmup_start = run_start - Months::new(12);
+    let warmup_end = run_start - TimeDelta::try_days(1).unwrap();
+    (warmup_start, warmup_end)
+}
+
+/// Get the strategy and the period used to warm up the model. The strategy is changed to
+/// [`WarmupStrategy::NoWarmUp`] when the data preceding the run period is not enough to define a
+/// warm-up period.
+///
+/// # Arguments
+///
+/// * `time`: The continuous time vector.
+/// * `run_period`: The run period.
+/// * `warmup_strategy`: The requested warm-up strategy.
+/// * `warmup_period`: The user-defined warm-up period, if any.
+/// * `logging`: Whether to log the changes to the warm-up period.
+///
+/// returns: `Result<(WarmupStrategy, Option<ModelPeriod>), LoadModelError>`
+pub(crate) fn resolve_warmup_period(
+    time: &[NaiveDate],
+    run_period: ModelPeriod,
+    mut warmup_strategy: WarmupStrategy,
+    warmup_period: Option<ModelPeriod>,
+    logging: bool,
+) -> Result<(WarmupStrategy, Option<ModelPeriod>), LoadModelError> {
+    let warmup_period = match (warmup_strategy, warmup_period) {
+        (WarmupStrategy::NoWarmUp, _) => None,
+        (WarmupStrategy::Climatology, Some(_)) => {
+            return Err(LoadModelError::Generic(
+                "The warm-up period cannot be set when the climatology warm-up strategy is used".to_string(),
+            ));
+        }
+        (WarmupStrategy::Climatology, None) => {
+            // one year of synthetic data preceding the run period
+            let (warmup_start, warmup_end) = default_warmup_dates(run_period.start);
+            Some(ModelPeriod::new(warmup_start, warmup_end).map_err(|e| LoadModelError::Generic(e.to_string()))?)
+        }
+        (WarmupStrategy::PrecedingData, None) => {
+            let (warmup_start, warmup_end) = default_warmup_dates(run_period.start);
+            if logging {
+                warn!(
+                    "Model warm-up period not defined. Using default period {}-{}",
+                    warmup_start, warmup_end
+                );
+            }
+
+            if warmup_start >= time[0] {
+                // one year is available
+                Some(ModelPeriod::new(warmup_start, warmup_end).map_err(|e| LoadModelError::Generic(e.to_string()))?)
+            } else if run_period.start > time[0] {
+                // reduced warm-up period
+                if logging {
+                    warn!(
+                        "The input data is too short to define a one-year warm-up period. Period \
+                    will start from {} which is the first date in the time vector",
+                        time[0]
+                    );
+                }
+                Some(ModelPeriod::new(time[0], warmup_end).map_err(|e| LoadModelError::Generic(e.to_string()))?)
+            } else {
+                // disregard warm-up period if there is no enough data
+                if logging {
+                    warn!("The input data is too short to define a warm-up period");
+                }
+                warmup_strategy = WarmupStrategy::NoWarmUp;
+                None
+            }
+        }
+        (WarmupStrategy::PrecedingData, Some(period)) => {
+            // check date validity
+            if period.start < time[0] {
+                return Err(LoadModelError::DateOutsideTVector(
+                    "warm-up start date".to_string(),
+                    "first date".to_string(),
+                ));
+            }
+            if period.start >= run_period.start {
+                return Err(LoadModelError::DateTooSmall("warm-up start".to_string()));
+            }
+            if period.end >= run_period.start {
+                return Err(LoadModelError::DateTooSmall("warm-up end".to_string()));
+            }
+            // the days between the warm-up and run periods are used as additional spin-up
+            let warmup_end = run_period.start.pred_opt().unwrap();
+            if period.end < warmup_end && logging {
+                warn!(
+                    "The warm-up period ends on {} before the run period. The warm-up is extended to {}",
+                    period.end, warmup_end
+                );
+            }
+            Some(ModelPeriod::new(period.start, warmup_end).map_err(|e| LoadModelError::Generic(e.to_string()))?)
+        }
+    };
+    Ok((warmup_strategy, warmup_period))
+}
+
+/// Get the indexes of the start and end dates of a period in the time vector.
+///
+/// # Arguments
+///
+/// * `time`: The time vector containing the period.
+/// * `period`: The period.
+///
+/// returns: `(usize, usize)`
+fn period_indices(time: &[NaiveDate], period: ModelPeriod) -> (usize, usize) {
+    let start_index = time.iter().position(|&r| r == period.start).unwrap();
+    let end_index = time.iter().position(|&r| r == period.end).unwrap();
+    (start_index, end_index)
+}
+
+/// Get the time vector and the forcing used to run the model, including the warm-up period. With
+/// [`WarmupStrategy::Climatology`], the synthetic warm-up forcing is prepended to the data of the
+/// run period.
+///
+/// # Arguments
+///
+/// * `time`: The continuous time vector.
+/// * `precipitation`: The precipitation series.
+/// * `evapotranspiration`: The evapotranspiration series.
+/// * `run_period`: The run period.
+/// * `warmup_strategy`: The warm-up strategy.
+/// * `warmup_period`: The warm-up period.
+///
+/// returns: `(Vec<NaiveDate>, Vec<f64>, Vec<f64>)`
+fn simulation_forcing(
+    time: &[NaiveDate],
+    precipitation: &[f64],
+    evapotranspiration: &[f64],
+    run_period: ModelPeriod,
+    warmup_strategy: WarmupStrategy,
+    warmup_period: Option<ModelPeriod>,
+) -> (Vec<NaiveDate>, Vec<f64>, Vec<f64>) {
+    // include warm-up
+    let start_date = match (warmup_strategy, warmup_period) {
+        (WarmupStrategy::PrecedingData, Some(p)) => p.start,
+        _ => run_period.start,
+    };
+    let start_index = time.iter().position(|&r| r == start_date).unwrap();
+    let end_index = time.iter().position(|&r| r == run_period.end).unwrap();
+    let sim_time = time[start_index..=end_index].to_owned();
+    let sim_precipitation = precipitation[start_index..=end_index].to_owned();
+    let sim_evapotranspiration = evapotranspiration[start_index..=end_index].to_owned();
+
+    // prepend the synthetic warm-up forcing
+    if let (WarmupStrategy::Climatology, Some(period)) = (warmup_strategy, warmup_period) {
+        let warmup_time: Vec<NaiveDate> = period.start.iter_days().take_while(|d| *d <= period.end).collect();
+        let warmup_precipitation = day_of_year_mean(time, precipitation, &warmup_time);
+        let warmup_evapotranspiration = day_of_year_mean(time, evapotranspiration, &warmup_time);
+
+        return (
+            [warmup_time, sim_time].concat(),
+            [warmup_precipitation, sim_precipitation].concat(),
+            [warmup_evapotranspiration, sim_evapotranspiration].concat(),
+        );
+    }
+    (sim_time, sim_precipitation, sim_evapotranspiration)
+}
+
+/// Check that the store levels and run-off of a time step are finite. Pathological parameter
+/// combinations may let the stores grow until they overflow; the simulation is stopped as soon as
+/// this happens instead of producing meaningless results.
+///
+/// # Arguments
+///
+/// * `step`: The step index.
+/// * `data`: The data of the time step.
+///
+/// returns: Result<(), RunModelError>
+fn check_stability(step: usize, data: &ModelStepData) -> Result<(), RunModelError> {
+    // the run-off is not defined on the days without forcing
+    if data.precipitation.is_nan() || data.evapotranspiration.is_nan() {
+        return Ok(());
+    }
+    let values = [
+        ("production store level", data.store_levels.production_store),
+        ("routing store level", data.store_levels.routing_store),
+        ("exponential store level", data.store_levels.exponential_store),
+        ("run-off", data.run_off),
+    ];
+    for (name, value) in values {
+        if !value.is_finite() {
+            return Err(RunModelError::Unstable(
+                step,
+                format!("the {} is not finite ({}) on {}", name, value, data.time),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The forcing data with the missing dates inserted.
+struct FilledForcing {
+    /// The continuous time vector.
+    time: Vec<NaiveDate>,
+    /// The precipitation with NaN on the inserted dates.
+    precipitation: Vec<f64>,
+    /// The evapotranspiration with NaN on the inserted dates.
+    evapotranspiration: Vec<f64>,
+    /// The observed run-off with NaN on the inserted dates.
+    observed_runoff: Option<Vec<f64>>,
+    /// The inserted dates.
+    filled_dates: Vec<NaiveDate>,
+}
+
+/// Insert the dates missing from the time vector of the model inputs. The forcing and observed
+/// run-off of the new dates are set to NaN.
+///
+/// # Arguments
+///
+/// * `inputs`: The model inputs. The vectors must have the same length.
+///
+/// returns: Result<FilledForcing, LoadModelError>
+fn fill_date_gaps(inputs: &GR6JModelInputs) -> Result<FilledForcing, LoadModelError> {
+    if inputs.time.windows(2).any(|ts| ts[1] <= ts[0]) {
+        return Err(LoadModelError::NotContinuousDates());
+    }
+
+    let mut filled = FilledForcing {
+        time: vec![],
+        precipitation: vec![],
+        evapotranspiration: vec![],
+        observed_runoff: inputs.observed_runoff.map(|_| vec![]),
+        filled_dates: vec![],
+    };
+    for (index, t) in inputs.time.iter().enumerate() {
+        // the days between the previous and the current date
+        if let Some(last) = filled.time.last().copied() {
+            for missing in last.iter_days().skip(1).take_while(|d| d < t) {
+                filled.time.push(missing);
+                filled.precipitation.push(f64::NAN);
+                filled.evapotranspiration.push(f64::NAN);
+                if let Some(observed) = filled.observed_runoff.as_mut() {
+                    observed.push(f64::NAN);
+                }
+                filled.filled_dates.push(missing);
+            }
+        }
+
+        filled.time.push(*t);
+        filled.precipitation.push(inputs.precipitation[index]);
+        filled.evapotranspiration.push(inputs.evapotranspiration[index]);
+        if let (Some(observed), Some(input_observed)) = (filled.observed_runoff.as_mut(), inputs.observed_runoff) {
+            observed.push(input_observed[index]);
+        }
+    }
+    Ok(filled)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::error::LoadModelError;
+    use crate::error::{LoadModelError, RunModelError};
     use chrono::{Datelike, NaiveDate, TimeDelta};
     use std::env;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::path::{Path, PathBuf};
     use std::str::FromStr;
 
-    use crate::inputs::{CatchmentData, RunOffUnit, StoreLevels};
-    use crate::model::{GR6JModel, GR6JModelInputs, ModelPeriod, Parameter};
-    use crate::outputs::{ModelStepData, ModelStepDataVector};
+    use crate::inputs::{
+        CatchmentData, DiagnosticChart, InitialStoreLevels, OutputPrecision, RunOffUnit, StoreLevels, WarmupStrategy,
+    };
+    use crate::metric::{MetricType, OptionalMetrics};
+    use crate::model::{
+        default_warmup_dates, GR6JModel, GR6JModelInputs, InternalState, ModelPeriod, Parameter, ROUTING_SPLIT,
+        UH_EXPONENT, UH_SPLIT,
+    };
+    use crate::outputs::{DayOutput, GR6JOutputs, ModelState, ModelStepData, ModelStepDataVector};
     use crate::parameter::{X1, X2, X3, X4, X5, X6};
     use crate::utils::assert_approx_array_eq;
+    use float_cmp::{approx_eq, F64Margin};
 
     fn default_catchment_data() -> Vec<CatchmentData> {
         vec![CatchmentData {
@@ -704,6 +1977,7 @@ mod tests {
             x5: X5::new(0.0).unwrap(),
             x6: X6::new(0.4).unwrap(),
             store_levels: None,
+            uh_exponent: None,
         }]
     }
 
@@ -758,6 +2032,8 @@ mod tests {
         x4: Result<Box<X4>, LoadModelError>,
         x5: Result<Box<X5>, LoadModelError>,
         x6: Result<Box<X6>, LoadModelError>,
+        /// The UH1 and routing splits. When None the defaults are used.
+        splits: Option<(f64, f64)>,
     }
 
     /// Run the model and compare the results against data generate for the airGR R package.
@@ -814,6 +2090,7 @@ mod tests {
             x5: args.x5.unwrap(),
             x6: args.x6.unwrap(),
             store_levels: None,
+            uh_exponent: None,
         };
         let area = catchment_data.area;
         let inputs = GR6JModelInputs {
@@ -822,11 +2099,11 @@ mod tests {
             evapotranspiration: &evapotranspiration,
             catchment: vec![catchment_data],
             run_period: ModelPeriod::new(start, end).unwrap(),
-            warmup_period: None,
-            destination: None,
-            observed_runoff: None,
             run_off_unit: RunOffUnit::NoConversion,
             logging: Some(false),
+            uh_split: args.splits.map(|s| s.0),
+            routing_split: args.splits.map(|s| s.1),
+            ..Default::default()
         };
 
         let mut model = GR6JModel::new(inputs).unwrap();
@@ -868,11 +2145,9 @@ mod tests {
             evapotranspiration: &evapotranspiration,
             catchment: default_catchment_data(),
             run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
-            warmup_period: None,
-            destination: None,
-            observed_runoff: None,
             run_off_unit: RunOffUnit::NoConversion,
             logging: Some(false),
+            ..Default::default()
         };
 
         let model = GR6JModel::new(inputs);
@@ -893,11 +2168,9 @@ mod tests {
             evapotranspiration: &evapotranspiration,
             catchment: default_catchment_data(),
             run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
-            warmup_period: None,
-            destination: None,
-            observed_runoff: None,
             run_off_unit: RunOffUnit::NoConversion,
             logging: Some(false),
+            ..Default::default()
         };
         let model = GR6JModel::new(inputs);
         assert_eq!(
@@ -918,11 +2191,9 @@ mod tests {
             evapotranspiration: &evapotranspiration,
             catchment: default_catchment_data(),
             run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
-            warmup_period: None,
-            destination: None,
-            observed_runoff: None,
             run_off_unit: RunOffUnit::NoConversion,
             logging: Some(false),
+            ..Default::default()
         };
         let model = GR6JModel::new(inputs);
         assert_eq!(
@@ -942,11 +2213,9 @@ mod tests {
             evapotranspiration: &evapotranspiration,
             catchment: default_catchment_data(),
             run_period: ModelPeriod::new(NaiveDate::from_ymd_opt(1999, 1, 1).unwrap(), t[365]).unwrap(),
-            warmup_period: None,
-            destination: None,
-            observed_runoff: None,
             run_off_unit: RunOffUnit::NoConversion,
             logging: Some(false),
+            ..Default::default()
         };
         let model = GR6JModel::new(inputs);
         assert_eq!(
@@ -972,11 +2241,9 @@ mod tests {
             evapotranspiration: &evapotranspiration,
             catchment: default_catchment_data(),
             run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
-            warmup_period: None,
-            destination: None,
-            observed_runoff: None,
             run_off_unit: RunOffUnit::NoConversion,
             logging: Some(false),
+            ..Default::default()
         };
         let model = GR6JModel::new(inputs);
         assert_eq!(
@@ -985,6 +2252,145 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_negative_values() {
+        let t0 = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let t: Vec<NaiveDate> = (0..366).map(|d| t0 + TimeDelta::try_days(d).unwrap()).collect();
+
+        let mut precipitation = vec![1.0; t.len()];
+        let mut evapotranspiration = vec![1.0; t.len()];
+        precipitation[3] = -0.1;
+        precipitation[10] = -2.0;
+        evapotranspiration[5] = -1.0;
+
+        let load = |precipitation: &[f64], evapotranspiration: &[f64]| -> Result<GR6JModel, LoadModelError> {
+            GR6JModel::new(GR6JModelInputs {
+                time: &t,
+                precipitation,
+                evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            })
+        };
+
+        // negative precipitation
+        let error = load(&precipitation, &vec![1.0; t.len()]).err().unwrap();
+        assert!(matches!(
+            &error,
+            LoadModelError::NegativeData(name, indices) if name == "precipitation" && *indices == vec!["3", "10"]
+        ));
+
+        // negative evapotranspiration
+        let error = load(&vec![1.0; t.len()], &evapotranspiration).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The evapo-transpiration series contains at least one negative value at the following indices: [\"5\"]. Negative values are not allowed"
+        );
+    }
+
+    #[test]
+    /// Test that the cap on the scaled rainfall keeps the stores finite when X1 is very small.
+    fn test_scaled_rainfall_cap() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|d| if d % 2 == 0 { 500.0 } else { 0.0 }).collect();
+        let evapotranspiration: Vec<f64> = (0..t.len()).map(|d| if d % 2 == 0 { 0.0 } else { 500.0 }).collect();
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+
+        let mut model = GR6JModel::new(inputs).unwrap();
+        let results = model.run().expect("Cannot fetch results");
+        let outputs = &results.catchment_outputs[0];
+        assert!(outputs.production_store().iter().all(|v| v.is_finite()));
+        assert!(outputs.routing_store().iter().all(|v| v.is_finite()));
+        assert!(outputs.exponential_store().iter().all(|v| v.is_finite()));
+        assert!(results.run_off.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_invalid_scaled_rainfall_cap() {
+        let t = build_t_vector();
+        let precipitation = vec![0.0; t.len()];
+        let evapotranspiration = vec![0.0; t.len()];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            scaled_rainfall_cap: Some(0.0),
+            ..Default::default()
+        };
+        let model = GR6JModel::new(inputs);
+        assert_eq!(
+            model.unwrap_err().to_string(),
+            "The scaled rainfall cap must be larger than 0".to_string()
+        );
+    }
+
+    #[test]
+    /// The default splits between the unit hydrographs and stores reproduce the airGR results.
+    fn test_gr6j_l0123001_default_splits() {
+        compare_against_r_data(CompareInputArgs {
+            r_csv_file: "airGR_results_L0123001_no_warmup.csv",
+            start_year: 1984,
+            stop_year: 1998,
+            start: Some(NaiveDate::from_ymd_opt(1984, 1, 1).unwrap()),
+            end: Some(NaiveDate::from_ymd_opt(1994, 12, 31).unwrap()),
+            x1: X1::new(1250.0),
+            x2: X2::new(0.3),
+            x3: X3::new(500.0),
+            x4: X4::new(5.2),
+            x5: X5::new(2.0),
+            x6: X6::new(10.0),
+            splits: Some((UH_SPLIT, ROUTING_SPLIT)),
+        })
+    }
+
+    #[test]
+    fn test_splits() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|d| if d % 7 == 0 { 20.0 } else { 0.0 }).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let run = |uh_split: Option<f64>, routing_split: Option<f64>| {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                uh_split,
+                routing_split,
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).map(|mut model| model.run().unwrap().run_off)
+        };
+
+        let default = run(None, None).unwrap();
+        assert_eq!(default, run(Some(UH_SPLIT), Some(ROUTING_SPLIT)).unwrap());
+        assert_ne!(default, run(Some(0.6), None).unwrap());
+        assert_ne!(default, run(None, Some(0.2)).unwrap());
+
+        // the splits must be fractions
+        assert!(run(Some(1.1), None).is_err());
+        assert!(run(None, Some(-0.1)).is_err());
+    }
+
     #[test]
     /// Test simulation with L0123001 dataset from 1994-01-01 to 1998-12-31 w/o warmup period.
     fn test_gr6j_l0123001_no_warm_up() {
@@ -1000,6 +2406,7 @@ mod tests {
             x4: X4::new(5.2),
             x5: X5::new(2.0),
             x6: X6::new(10.0),
+            splits: None,
         })
     }
 
@@ -1018,6 +2425,7 @@ mod tests {
             x4: X4::new(5.2),
             x5: X5::new(2.0),
             x6: X6::new(10.0),
+            splits: None,
         });
     }
 
@@ -1037,6 +2445,7 @@ mod tests {
             x4: X4::new(1.0),
             x5: X5::new(0.0),
             x6: X6::new(20.0),
+            splits: None,
         });
     }
 
@@ -1056,6 +2465,7 @@ mod tests {
             x4: X4::new(2.1),
             x5: X5::new(0.55),
             x6: X6::new(5.3),
+            splits: None,
         });
     }
 
@@ -1085,6 +2495,7 @@ mod tests {
             x5: X5::new(0.0).unwrap(),
             x6: X6::new(20.0).unwrap(),
             store_levels: None,
+            uh_exponent: None,
         };
         let hu2 = CatchmentData {
             area: 5.0,
@@ -1095,6 +2506,7 @@ mod tests {
             x5: X5::new(0.0).unwrap(),
             x6: X6::new(15.0).unwrap(),
             store_levels: None,
+            uh_exponent: None,
         };
         let start = *time.first().unwrap();
         let end = *time.last().unwrap();
@@ -1104,15 +2516,1572 @@ mod tests {
             evapotranspiration: &evapotranspiration,
             catchment: vec![hu1, hu2],
             run_period: ModelPeriod::new(start, end).unwrap(),
-            warmup_period: None,
-            destination: None,
-            observed_runoff: None,
             run_off_unit: RunOffUnit::NoConversion,
             logging: Some(false),
+            ..Default::default()
         };
 
         let mut model = GR6JModel::new(inputs).unwrap();
         let results = model.run().expect("Cannot fetch results");
         assert_eq!(results.catchment_outputs.len(), 2);
     }
+
+    #[test]
+    /// Test that running the hydrological units in parallel gives the same results of a serial run
+    fn test_parallel_catchments_determinism() {
+        let file = File::open(test_path().join("airGR_L0123001_dataset.csv")).expect("Failed to read CSV file");
+        let mut rdr = csv::Reader::from_reader(file);
+
+        let mut time: Vec<NaiveDate> = vec![];
+        let mut precipitation: Vec<f64> = vec![];
+        let mut evapotranspiration: Vec<f64> = vec![];
+        for result in rdr.records() {
+            let record = result.unwrap();
+            let date = NaiveDate::parse_from_str(record.get(0).unwrap(), "%d/%m/%Y").unwrap();
+            time.push(date);
+            precipitation.push(record.get(1).unwrap().parse::<f64>().unwrap());
+            evapotranspiration.push(record.get(2).unwrap().parse::<f64>().unwrap());
+        }
+
+        let catchment = vec![
+            CatchmentData {
+                area: 10.0,
+                x1: X1::new(1000.0).unwrap(),
+                x2: X2::new(0.0).unwrap(),
+                x3: X3::new(200.).unwrap(),
+                x4: X4::new(1.0).unwrap(),
+                x5: X5::new(0.0).unwrap(),
+                x6: X6::new(20.0).unwrap(),
+                store_levels: None,
+                uh_exponent: None,
+            },
+            CatchmentData {
+                area: 5.0,
+                x1: X1::new(2000.0).unwrap(),
+                x2: X2::new(2.0).unwrap(),
+                x3: X3::new(500.0).unwrap(),
+                x4: X4::new(3.2).unwrap(),
+                x5: X5::new(0.0).unwrap(),
+                x6: X6::new(15.0).unwrap(),
+                store_levels: None,
+                uh_exponent: None,
+            },
+        ];
+        let start = NaiveDate::from_ymd_opt(1994, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(1998, 12, 31).unwrap();
+
+        let mut run_off: Vec<Vec<f64>> = vec![];
+        for parallel_catchments in [false, true] {
+            let inputs = GR6JModelInputs {
+                time: &time,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: catchment.clone(),
+                run_period: ModelPeriod::new(start, end).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                parallel_catchments: Some(parallel_catchments),
+                ..Default::default()
+            };
+            let mut model = GR6JModel::new(inputs).unwrap();
+            let results = model.run().expect("Cannot fetch results");
+            assert_eq!(results.catchment_outputs.len(), 2);
+            run_off.push(results.run_off);
+        }
+
+        let bits = |q: &Vec<f64>| q.iter().map(|v| v.to_bits()).collect::<Vec<u64>>();
+        assert_eq!(bits(&run_off[0]), bits(&run_off[1]));
+    }
+
+    #[test]
+    fn test_describe() {
+        let t = build_t_vector();
+        let precipitation = vec![0.0; t.len()];
+        let evapotranspiration = vec![0.0; t.len()];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let model = GR6JModel::new(inputs).unwrap();
+        let description = model.describe();
+
+        assert_eq!(description.catchments.len(), 1);
+        assert_eq!(description.catchments[0].x1, 0.01);
+        assert_eq!(description.run_period.start, t[0]);
+        assert!(description.warmup_period.is_none());
+        assert!(!description.has_observed_runoff);
+        assert!(description
+            .to_string()
+            .starts_with("GR6J model with 1 hydrological unit(s)"));
+    }
+
+    #[test]
+    fn test_climatology_warmup() {
+        let t = build_t_vector();
+        let precipitation = vec![5.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+        let run = |warmup_strategy: Option<WarmupStrategy>| {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                warmup_strategy,
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            let mut model = GR6JModel::new(inputs).unwrap();
+            let description = model.describe();
+            (description, model.run().unwrap())
+        };
+
+        // no data precedes the run period
+        let (description, results) = run(None);
+        assert_eq!(results.warmup_strategy, WarmupStrategy::NoWarmUp);
+        assert!(description.warmup_period.is_none());
+
+        let (description, climatology_results) = run(Some(WarmupStrategy::Climatology));
+        assert_eq!(climatology_results.warmup_strategy, WarmupStrategy::Climatology);
+        let warmup_period = description.warmup_period.unwrap();
+        assert_eq!(warmup_period.start, t[0] - TimeDelta::try_days(365).unwrap());
+        assert_eq!(warmup_period.end, t[0] - TimeDelta::try_days(1).unwrap());
+
+        // the warm-up is excluded from the results but fills the stores
+        assert_eq!(climatology_results.time[0], t[0]);
+        assert_eq!(climatology_results.time.len(), results.time.len());
+        let first_step = |r: &GR6JOutputs| r.catchment_outputs[0].0[0].store_levels.production_store;
+        assert!(first_step(&climatology_results) > first_step(&results));
+    }
+
+    #[test]
+    fn test_climatology_warmup_with_period() {
+        let t = build_t_vector();
+        let precipitation = vec![5.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[100], t[365]).unwrap(),
+            warmup_period: Some(ModelPeriod::new(t[0], t[99]).unwrap()),
+            warmup_strategy: Some(WarmupStrategy::Climatology),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        assert!(matches!(GR6JModel::new(inputs), Err(LoadModelError::Generic(_))));
+    }
+
+    #[test]
+    fn test_f32_output_precision() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 7) as f64 * 3.0).collect();
+        let evapotranspiration = vec![1.5; t.len()];
+        let observed = vec![0.5; t.len()];
+        let run = |output_precision: Option<OutputPrecision>| {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                observed_runoff: Some(&observed),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                output_precision,
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap().run().unwrap()
+        };
+
+        let f64_results = run(None);
+        let f32_results = run(Some(OutputPrecision::F32));
+        assert_eq!(f32_results.precision, OutputPrecision::F32);
+        assert!(f32_results.run_off.is_empty());
+        assert!(f32_results.catchment_outputs.is_empty());
+        assert!(f32_results.run_off_by_catchment.is_empty());
+        assert_eq!(f32_results.run_off_f32.len(), f64_results.run_off.len());
+        assert_eq!(f32_results.run_off_by_catchment_f32.len(), 1);
+        for (q32, q64) in f32_results.run_off_by_catchment_f32[0]
+            .iter()
+            .zip(f64_results.run_off_by_catchment[0].iter())
+        {
+            assert_eq!(*q32, *q64 as f32);
+        }
+
+        for (q32, q64) in f32_results.run_off_f32.iter().zip(f64_results.run_off.iter()) {
+            assert_eq!(*q32, *q64 as f32);
+        }
+        let step = f32_results.catchment_outputs_f32[0][10];
+        let expected = &f64_results.catchment_outputs[0].0[10];
+        assert_eq!(step.time, expected.time);
+        assert_eq!(step.production_store, expected.store_levels.production_store as f32);
+
+        // metrics are calculated in double precision
+        assert_eq!(
+            f32_results.metrics.unwrap().nash_sutcliffe.value.to_bits(),
+            f64_results.metrics.unwrap().nash_sutcliffe.value.to_bits()
+        );
+    }
+
+    #[test]
+    fn test_unstable_simulation() {
+        let t = build_t_vector();
+        let mut precipitation = vec![2.0; t.len()];
+        // an overflowing rainfall blows up the stores
+        precipitation[10] = f64::INFINITY;
+        let evapotranspiration = vec![1.0; t.len()];
+        for parallel_catchments in [false, true] {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                parallel_catchments: Some(parallel_catchments),
+                ..Default::default()
+            };
+            let mut model = GR6JModel::new(inputs).unwrap();
+            match model.run() {
+                Err(RunModelError::Unstable(step, _)) => assert_eq!(step, 10),
+                other => panic!("expected an unstable simulation, got {:?}", other.map(|r| r.time.len())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_warmup_end_state() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 7) as f64 * 3.0).collect();
+        let evapotranspiration = vec![1.5; t.len()];
+        let build_model = |run_start: usize, parallel_catchments: bool| {
+            GR6JModel::new(GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[run_start], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                parallel_catchments: Some(parallel_catchments),
+                ..Default::default()
+            })
+            .unwrap()
+        };
+
+        // step through the warm-up to get the state preceding the first collected step
+        let mut model = build_model(100, false);
+        assert!(model.warmup_end_state(0).is_none());
+        let mut last_warmup_step = model.step(0).unwrap();
+        while last_warmup_step.time < t[99] {
+            last_warmup_step = model.step(0).unwrap();
+        }
+        assert_eq!(model.warmup_end_state(0), Some(last_warmup_step.store_levels));
+
+        for parallel_catchments in [false, true] {
+            let mut model = build_model(100, parallel_catchments);
+            let results = model.run().unwrap();
+            assert_eq!(results.time[0], t[100]);
+            assert_eq!(model.warmup_end_state(0), Some(last_warmup_step.store_levels));
+
+            model.reset();
+            assert!(model.warmup_end_state(0).is_none());
+        }
+
+        // no warm-up
+        let mut model = build_model(0, false);
+        model.run().unwrap();
+        assert!(model.warmup_end_state(0).is_none());
+    }
+
+    #[test]
+    fn test_run_off_by_catchment() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 5) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let mut catchment = default_catchment_data();
+        catchment.push(CatchmentData {
+            area: 5.0,
+            x1: X1::new(20.0).unwrap(),
+            x2: X2::new(0.5).unwrap(),
+            x3: X3::new(10.0).unwrap(),
+            x4: X4::new(2.0).unwrap(),
+            x5: X5::new(0.0).unwrap(),
+            x6: X6::new(3.0).unwrap(),
+            store_levels: None,
+            uh_exponent: None,
+        });
+        let mut model = GR6JModel::new(GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment,
+            run_period: ModelPeriod::new(t[100], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::CubicMetrePerSecond,
+            logging: Some(false),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let results = model.run().unwrap();
+        assert_eq!(results.run_off_by_catchment.len(), 2);
+        let conv_factor = RunOffUnit::CubicMetrePerSecond.conv_factor();
+        for (unit_run_off, (data, area)) in results
+            .run_off_by_catchment
+            .iter()
+            .zip(results.catchment_outputs.iter().zip([1.0, 5.0]))
+        {
+            assert_eq!(unit_run_off.len(), results.time.len());
+            let expected: Vec<f64> = data.0.iter().map(|s| s.run_off * area * conv_factor).collect();
+            assert_eq!(unit_run_off, &expected);
+        }
+
+        // the per-unit series add up to the combined run-off
+        let margins = F64Margin {
+            epsilon: 1e-12,
+            ulps: 4,
+        };
+        for (i, q) in results.run_off.iter().enumerate() {
+            let combined: f64 = results.run_off_by_catchment.iter().map(|unit_q| unit_q[i]).sum();
+            assert!(approx_eq!(f64, combined, *q, margins));
+        }
+        assert!(results.run_off.iter().any(|q| *q > 0.0));
+    }
+
+    /// Build the inputs used to test the validation
+    fn validation_inputs<'a>(
+        t: &'a [NaiveDate],
+        precipitation: &'a [f64],
+        evapotranspiration: &'a [f64],
+    ) -> GR6JModelInputs<'a> {
+        GR6JModelInputs {
+            time: t,
+            precipitation,
+            evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let t = build_t_vector();
+        let precipitation = vec![1.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+        let destination = env::temp_dir().join("gr6j_test_validate");
+        if destination.exists() {
+            fs::remove_dir_all(&destination).unwrap();
+        }
+        fs::create_dir(&destination).unwrap();
+
+        // the output folder is not created
+        let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        inputs.destination = Some(destination.clone());
+        assert!(GR6JModel::validate(&inputs).is_ok());
+        assert_eq!(fs::read_dir(&destination).unwrap().count(), 0);
+        fs::remove_dir_all(&destination).unwrap();
+
+        // the destination must exist
+        assert!(matches!(
+            GR6JModel::validate(&inputs).unwrap_err(),
+            LoadModelError::DestinationNotFound(path) if path == destination.to_str().unwrap()
+        ));
+
+        // the errors are the same as the ones returned when loading the model
+        let mut precipitation = precipitation.clone();
+        precipitation[0] = f64::NAN;
+        let validation_error = GR6JModel::validate(&validation_inputs(&t, &precipitation, &evapotranspiration));
+        let load_error = GR6JModel::new(validation_inputs(&t, &precipitation, &evapotranspiration)).err();
+        assert_eq!(
+            validation_error.err().map(|e| e.to_string()),
+            load_error.map(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_series() {
+        let mut t = build_t_vector();
+        let mut precipitation = vec![1.0; t.len()];
+        let mut evapotranspiration = vec![1.0; t.len()];
+        let short = vec![0.0; t.len() - 10];
+
+        let inputs = validation_inputs(&t, &short, &evapotranspiration);
+        assert_eq!(
+            GR6JModel::validate(&inputs).unwrap_err().to_string(),
+            "The time and precipitation vectors must have the same length"
+        );
+        let inputs = validation_inputs(&t, &precipitation, &short);
+        assert_eq!(
+            GR6JModel::validate(&inputs).unwrap_err().to_string(),
+            "The time and evapotranspiration vectors must have the same length"
+        );
+        let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        inputs.observed_runoff = Some(&short);
+        assert!(matches!(
+            GR6JModel::validate(&inputs).unwrap_err(),
+            LoadModelError::MismatchedLength(name) if name == "observed run-off"
+        ));
+
+        // missing values are only checked in the simulated period
+        precipitation[0] = f64::NAN;
+        let inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        assert_eq!(
+            GR6JModel::validate(&inputs).unwrap_err().to_string(),
+            "The precipitation series contains at least one NA value at the following indices: [\"0\"]. Missing values are not allowed"
+        );
+        let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        inputs.run_period = ModelPeriod::new(t[100], t[365]).unwrap();
+        inputs.warmup_strategy = Some(WarmupStrategy::NoWarmUp);
+        assert!(GR6JModel::validate(&inputs).is_ok());
+        precipitation[0] = 1.0;
+
+        evapotranspiration[5] = -1.0;
+        let inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        assert!(matches!(
+            GR6JModel::validate(&inputs).unwrap_err(),
+            LoadModelError::NegativeData(name, indices) if name == "evapo-transpiration" && indices == vec!["5"]
+        ));
+        evapotranspiration[5] = 1.0;
+
+        // non-continuous dates are only allowed when the gaps are filled
+        t[365] += TimeDelta::try_days(3).unwrap();
+        let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        assert_eq!(
+            GR6JModel::validate(&inputs).unwrap_err().to_string(),
+            "The time vector must have continuous dates"
+        );
+        inputs.fill_gaps = Some(true);
+        assert!(GR6JModel::validate(&inputs).is_ok());
+    }
+
+    #[test]
+    fn test_validate_periods() {
+        let t = build_t_vector();
+        let precipitation = vec![1.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+
+        let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        inputs.run_period = ModelPeriod::new(NaiveDate::from_ymd_opt(1999, 1, 1).unwrap(), t[365]).unwrap();
+        assert_eq!(
+            GR6JModel::validate(&inputs).unwrap_err().to_string(),
+            "The run start date must be larger or equal to the first date in the time vector"
+        );
+
+        let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+        inputs.run_period = ModelPeriod::new(t[100], t[365]).unwrap();
+        inputs.warmup_period = Some(ModelPeriod::new(t[0], t[100]).unwrap());
+        assert!(matches!(
+            GR6JModel::validate(&inputs).unwrap_err(),
+            LoadModelError::DateTooSmall(name) if name == "warm-up end"
+        ));
+        inputs.warmup_strategy = Some(WarmupStrategy::Climatology);
+        assert!(matches!(
+            GR6JModel::validate(&inputs).unwrap_err(),
+            LoadModelError::Generic(_)
+        ));
+        inputs.warmup_period = Some(ModelPeriod::new(t[0], t[99]).unwrap());
+        inputs.warmup_strategy = None;
+        assert!(GR6JModel::validate(&inputs).is_ok());
+
+        // the warm-up period must start within the time vector
+        inputs.warmup_period = Some(ModelPeriod::new(NaiveDate::from_ymd_opt(1999, 1, 1).unwrap(), t[99]).unwrap());
+        assert_eq!(
+            GR6JModel::validate(&inputs).unwrap_err().to_string(),
+            "The warm-up start date must be larger or equal to the first date in the time vector"
+        );
+    }
+
+    #[test]
+    fn test_validate_short_time_vector() {
+        let t = build_t_vector();
+        let precipitation = vec![1.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+
+        for length in [0, 1] {
+            let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+            inputs.time = &t[..length];
+            inputs.precipitation = &precipitation[..length];
+            inputs.evapotranspiration = &evapotranspiration[..length];
+            assert_eq!(
+                GR6JModel::validate(&inputs).unwrap_err().to_string(),
+                "The time vector must contain at least two dates"
+            );
+        }
+
+        // the default inputs have no data
+        assert!(GR6JModel::new(GR6JModelInputs::default()).is_err());
+    }
+
+    #[test]
+    fn test_validate_options() {
+        let t = build_t_vector();
+        let precipitation = vec![1.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+        let validate = |update: &dyn Fn(&mut GR6JModelInputs)| -> Result<(), LoadModelError> {
+            let mut inputs = validation_inputs(&t, &precipitation, &evapotranspiration);
+            update(&mut inputs);
+            GR6JModel::validate(&inputs)
+        };
+
+        assert!(validate(&|_| {}).is_ok());
+        assert!(validate(&|i| i.scaled_rainfall_cap = Some(0.0)).is_err());
+        assert!(validate(&|i| i.scaled_rainfall_cap = Some(f64::NAN)).is_err());
+        assert!(validate(&|i| i.scaled_rainfall_cap = Some(f64::INFINITY)).is_err());
+        assert!(validate(&|i| i.uh_split = Some(1.5)).is_err());
+        assert!(validate(&|i| i.routing_split = Some(-0.1)).is_err());
+        assert!(validate(&|i| i.precip_correction = Some(0.0)).is_err());
+        assert!(validate(&|i| i.pet_correction = Some(f64::INFINITY)).is_err());
+        assert_eq!(
+            validate(&|i| i.catchment[0].uh_exponent = Some(0.0))
+                .unwrap_err()
+                .to_string(),
+            "The unit hydrograph exponent must be larger than 0"
+        );
+    }
+
+    #[test]
+    fn test_run_once() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 5) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let build_inputs = |precip_correction: Option<f64>| GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[100], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            precip_correction,
+            ..Default::default()
+        };
+
+        let results = GR6JModel::run_once(build_inputs(None)).unwrap();
+        let expected = GR6JModel::new(build_inputs(None)).unwrap().run().unwrap();
+        assert_eq!(results.time, expected.time);
+        assert_eq!(results.run_off, expected.run_off);
+        assert_eq!(
+            results.catchment_outputs[0].production_store(),
+            expected.catchment_outputs[0].production_store()
+        );
+
+        // the loading error is returned
+        let error = GR6JModel::run_once(build_inputs(Some(-1.0))).unwrap_err();
+        assert!(matches!(error, RunModelError::CannotLoadModel(_)));
+        assert_eq!(
+            error.to_string(),
+            "The model cannot be loaded because The precipitation correction factor must be larger than 0"
+        );
+    }
+
+    #[test]
+    fn test_run_into() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 5) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let build_model = || {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap()
+        };
+
+        let expected = build_model().run().unwrap();
+        let mut results = GR6JOutputs::default();
+        build_model().run_into(&mut results).unwrap();
+        let run_off_ptr = results.run_off.as_ptr();
+        let step_ptr = results.catchment_outputs[0].0.as_ptr();
+
+        // the second run reuses the same allocations
+        build_model().run_into(&mut results).unwrap();
+        assert_eq!(results.run_off.as_ptr(), run_off_ptr);
+        assert_eq!(results.catchment_outputs[0].0.as_ptr(), step_ptr);
+
+        assert_eq!(results.time, expected.time);
+        assert_eq!(results.run_off, expected.run_off);
+        assert_eq!(results.catchment_outputs.len(), expected.catchment_outputs.len());
+    }
+
+    #[test]
+    /// The exported step data can be read back with the parser of the airGR results.
+    fn test_step_data_csv() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 5) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[100], t[365]).unwrap(),
+            warmup_period: Some(ModelPeriod::new(t[0], t[99]).unwrap()),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let mut model = GR6JModel::new(inputs).unwrap();
+        let results = model.run().unwrap();
+        let outputs = &results.catchment_outputs[0];
+
+        let file = env::temp_dir().join("gr6j_test_step_data.csv");
+        outputs.to_csv(&file, Some(12)).unwrap();
+        let parsed = parse_r_file(&file);
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(parsed.time(), outputs.time());
+        assert_approx_array_eq(&parsed.production_store(), &outputs.production_store());
+        assert_approx_array_eq(&parsed.routing_store(), &outputs.routing_store());
+        assert_approx_array_eq(&parsed.exponential_store(), &outputs.exponential_store());
+        assert_approx_array_eq(&parsed.run_off(None), &outputs.run_off(None));
+        for (a, b) in parsed.0.iter().zip(&outputs.0) {
+            assert!((a.percolation - b.percolation).abs() < 1e-12);
+            assert!((a.actual_exchange - b.actual_exchange).abs() < 1e-12);
+            assert!((a.actual_evapotranspiration - b.actual_evapotranspiration).abs() < 1e-12);
+            assert!((a.outflow_from_uh2_branch - b.outflow_from_uh2_branch).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_fill_gaps() {
+        // remove one week of data
+        let full_time = build_t_vector();
+        let keep = |i: &usize| !(50..57).contains(i);
+        let t: Vec<NaiveDate> = (0..full_time.len()).filter(keep).map(|i| full_time[i]).collect();
+        let precipitation: Vec<f64> = (0..full_time.len()).filter(keep).map(|i| (i % 4) as f64).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let observed = vec![0.5; t.len()];
+        let inputs = |fill_gaps: Option<bool>| GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], *t.last().unwrap()).unwrap(),
+            warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+            observed_runoff: Some(&observed),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            fill_gaps,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            GR6JModel::new(inputs(None)),
+            Err(LoadModelError::NotContinuousDates())
+        ));
+
+        let mut model = GR6JModel::new(inputs(Some(true))).unwrap();
+        assert_eq!(model.time, full_time);
+        assert!(model.precipitation[50..57].iter().all(|p| p.is_nan()));
+
+        // the gap days are skipped
+        let results = model.run().unwrap();
+        assert_eq!(results.time, full_time);
+        assert!(results.run_off[50..57].iter().all(|q| q.is_nan()));
+        assert!(results.run_off[..50]
+            .iter()
+            .chain(&results.run_off[57..])
+            .all(|q| q.is_finite()));
+        let outputs = &results.catchment_outputs[0].0;
+        assert_eq!(outputs[56].store_levels, outputs[49].store_levels);
+        assert!(results.metrics.unwrap().rmse.value.is_finite());
+    }
+
+    #[test]
+    fn test_water_balance() {
+        let t = build_t_vector();
+        // wet period followed by a long dry period to empty the unit hydrographs
+        let precipitation: Vec<f64> = (0..t.len())
+            .map(|d| if (10..200).contains(&d) && d % 2 == 0 { 8.0 } else { 0.0 })
+            .collect();
+        let evapotranspiration: Vec<f64> = (0..t.len()).map(|d| if d < 200 { 1.0 } else { 6.0 }).collect();
+        let catchment = vec![CatchmentData {
+            area: 1.0,
+            x1: X1::new(100.0).unwrap(),
+            x2: X2::new(0.5).unwrap(),
+            x3: X3::new(50.0).unwrap(),
+            x4: X4::new(1.5).unwrap(),
+            x5: X5::new(0.2).unwrap(),
+            x6: X6::new(5.0).unwrap(),
+            store_levels: Some(StoreLevels::absolute(0.0, 10.0, 0.0)),
+            uh_exponent: None,
+        }];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment,
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let mut model = GR6JModel::new(inputs).unwrap();
+        let balance = model.run().unwrap().water_balance();
+
+        assert_eq!(balance.precipitation, precipitation[1..].iter().sum::<f64>());
+        assert!(balance.run_off > 0.0);
+        assert!(balance.actual_evapotranspiration > 0.0);
+        assert!(balance.exchange != 0.0);
+        assert!(balance.residual.abs() < 1e-6, "residual is {}", balance.residual);
+    }
+
+    #[test]
+    fn test_weighted_store_levels() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 5) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let build_model = |catchment: Vec<CatchmentData>, output_precision: Option<OutputPrecision>| {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment,
+                run_period: ModelPeriod::new(t[100], t[365]).unwrap(),
+                warmup_period: Some(ModelPeriod::new(t[0], t[99]).unwrap()),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                output_precision,
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap()
+        };
+
+        // one unit
+        let mut model = build_model(default_catchment_data(), None);
+        let results = model.run().unwrap();
+        let (production_store, routing_store, exponential_store) = model.weighted_store_levels(&results);
+        assert_eq!(production_store, results.catchment_outputs[0].production_store());
+        assert_eq!(routing_store, results.catchment_outputs[0].routing_store());
+        assert_eq!(exponential_store, results.catchment_outputs[0].exponential_store());
+
+        // two units with different parameters
+        let mut catchment = default_catchment_data();
+        catchment[0].area = 2.0;
+        catchment.push(CatchmentData {
+            area: 0.5,
+            x1: X1::new(250.0).unwrap(),
+            x4: X4::new(2.5).unwrap(),
+            ..default_catchment_data().remove(0)
+        });
+        let mut model = build_model(catchment.clone(), None);
+        let results = model.run().unwrap();
+        let (production_store, routing_store, exponential_store) = model.weighted_store_levels(&results);
+        let weight = |values: fn(&ModelStepDataVector) -> Vec<f64>| -> Vec<f64> {
+            let unit1 = values(&results.catchment_outputs[0]);
+            let unit2 = values(&results.catchment_outputs[1]);
+            unit1
+                .iter()
+                .zip(unit2)
+                .map(|(a, b)| (2.0 * a + 0.5 * b) / 2.5)
+                .collect()
+        };
+        assert_eq!(production_store.len(), results.time.len());
+        assert_approx_array_eq(&production_store, &weight(ModelStepDataVector::production_store));
+        assert_approx_array_eq(&routing_store, &weight(ModelStepDataVector::routing_store));
+        assert_approx_array_eq(&exponential_store, &weight(ModelStepDataVector::exponential_store));
+
+        // single precision results
+        let mut model = build_model(catchment, Some(OutputPrecision::F32));
+        let results = model.run().unwrap();
+        let (production_store_f32, _, _) = model.weighted_store_levels(&results);
+        assert_eq!(production_store_f32.len(), production_store.len());
+        assert!(production_store_f32
+            .iter()
+            .zip(&production_store)
+            .all(|(a, b)| (a - b).abs() <= 1e-4 * b.abs().max(1.0)));
+    }
+
+    #[test]
+    fn test_run_iter() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 5) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let build_model = || {
+            let mut catchment = default_catchment_data();
+            catchment[0].area = 2.0;
+            catchment.push(CatchmentData {
+                area: 0.5,
+                ..default_catchment_data().remove(0)
+            });
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment,
+                run_period: ModelPeriod::new(t[100], t[365]).unwrap(),
+                warmup_period: Some(ModelPeriod::new(t[0], t[99]).unwrap()),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap()
+        };
+
+        let expected = build_model().run().unwrap();
+        let mut model = build_model();
+        let days: Vec<DayOutput> = model.run_iter().collect::<Result<_, _>>().unwrap();
+
+        // the warm-up period is skipped
+        assert_eq!(days.len(), expected.time.len());
+        assert_eq!(days[0].time, t[100]);
+        for (i, day) in days.iter().enumerate() {
+            assert_eq!(day.time, expected.time[i]);
+            assert_eq!(day.run_off, expected.run_off[i]);
+            assert_eq!(day.catchment_outputs.len(), 2);
+            assert_eq!(
+                day.catchment_outputs[1].run_off,
+                expected.catchment_outputs[1].0[i].run_off
+            );
+        }
+
+        // the simulation has ended
+        assert!(model.run_iter().next().is_none());
+    }
+
+    #[test]
+    fn test_warmup_gap() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 9) as f64 * 4.0).collect();
+        let evapotranspiration = vec![2.0; t.len()];
+        let load = |warmup_period: ModelPeriod| -> Result<GR6JModel, LoadModelError> {
+            GR6JModel::new(GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[200], t[365]).unwrap(),
+                warmup_period: Some(warmup_period),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            })
+        };
+
+        // the gap between the warm-up and run periods is added to the warm-up
+        let mut model = load(ModelPeriod::new(t[0], t[99]).unwrap()).unwrap();
+        assert_eq!(
+            model.describe().warmup_period,
+            Some(ModelPeriod::new(t[0], t[199]).unwrap())
+        );
+        let results = model.run().unwrap();
+        let expected = load(ModelPeriod::new(t[0], t[199]).unwrap()).unwrap().run().unwrap();
+        assert_eq!(results.time, expected.time);
+        assert_eq!(results.run_off, expected.run_off);
+
+        // the warm-up must end before the run period
+        let error = load(ModelPeriod::new(t[0], t[200]).unwrap()).err().unwrap();
+        assert!(matches!(error, LoadModelError::DateTooSmall(_)));
+    }
+
+    #[test]
+    fn test_runoff_components() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 9) as f64 * 4.0).collect();
+        let evapotranspiration = vec![2.0; t.len()];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let results = GR6JModel::new(inputs).unwrap().run().unwrap();
+
+        let margins = F64Margin {
+            epsilon: 1e-12,
+            ulps: 4,
+        };
+        for step_data in results.catchment_outputs[0].0.iter() {
+            let components = step_data.runoff_components();
+            assert!(
+                approx_eq!(f64, components.total(), step_data.run_off, margins),
+                "the run-off components do not add up to the run-off on {}",
+                step_data.time
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_warmup_dates() {
+        let date = |y: i32, m: u32, d: u32| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let days = |(start, end): (NaiveDate, NaiveDate)| (end - start).num_days() + 1;
+
+        // non-leap years
+        assert_eq!(
+            default_warmup_dates(date(1994, 1, 1)),
+            (date(1993, 1, 1), date(1993, 12, 31))
+        );
+        assert_eq!(days(default_warmup_dates(date(1994, 1, 1))), 365);
+        // the warm-up includes the 29th of February
+        assert_eq!(
+            default_warmup_dates(date(1997, 1, 1)),
+            (date(1996, 1, 1), date(1996, 12, 31))
+        );
+        assert_eq!(days(default_warmup_dates(date(1997, 1, 1))), 366);
+        assert_eq!(
+            default_warmup_dates(date(2000, 3, 1)),
+            (date(1999, 3, 1), date(2000, 2, 29))
+        );
+        assert_eq!(days(default_warmup_dates(date(2000, 3, 1))), 366);
+        assert_eq!(
+            default_warmup_dates(date(2001, 3, 1)),
+            (date(2000, 3, 1), date(2001, 2, 28))
+        );
+        assert_eq!(days(default_warmup_dates(date(2001, 3, 1))), 365);
+        // the run starts on a leap day
+        assert_eq!(
+            default_warmup_dates(date(2000, 2, 29)),
+            (date(1999, 2, 28), date(2000, 2, 28))
+        );
+    }
+
+    #[test]
+    fn test_set_parameters() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 7) as f64 * 3.0).collect();
+        let evapotranspiration = vec![1.5; t.len()];
+        let build_model = |catchment: Vec<CatchmentData>| {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment,
+                run_period: ModelPeriod::new(t[100], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap()
+        };
+
+        // sweep X1 on the same model
+        let mut model = build_model(default_catchment_data());
+        model.run().unwrap();
+        for x1 in [10.0, 250.0, 1500.0] {
+            model.set_parameters(0, [x1, 0.0, 0.4, 0.6, 0.0, 0.4]).unwrap();
+            let results = model.run().unwrap();
+
+            let mut catchment = default_catchment_data();
+            catchment[0].x1 = X1::new(x1).unwrap();
+            let expected = build_model(catchment).run().unwrap();
+            assert_eq!(results.time, expected.time);
+            assert_eq!(results.run_off, expected.run_off);
+        }
+
+        // reset without changing the parameters
+        let expected = model.run().unwrap();
+        assert!(expected.run_off.is_empty());
+        model.reset();
+        let results = model.run().unwrap();
+        assert_eq!(results.run_off.len(), 266);
+
+        // invalid inputs leave the model unchanged
+        assert!(model.set_parameters(1, [10.0, 0.0, 0.4, 0.6, 0.0, 0.4]).is_err());
+        assert!(model.set_parameters(0, [10.0, 0.0, 0.4, -1.0, 0.0, 0.4]).is_err());
+        model.reset();
+        assert_eq!(model.run().unwrap().run_off, results.run_off);
+    }
+
+    #[test]
+    fn test_uh_state() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 4) as f64 * 5.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let build_model = || {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap()
+        };
+
+        let mut model = build_model();
+        let state = model.uh_state();
+        assert_eq!(state.len(), 1);
+        assert_eq!(state[0].0, vec![0.0; 20]);
+        assert_eq!(state[0].1, vec![0.0; 40]);
+
+        // the state is copied to a new model
+        for _ in 0..10 {
+            model.step(0).unwrap();
+        }
+        let state = model.uh_state();
+        assert!(state[0].0.iter().any(|v| *v > 0.0));
+        let mut new_model = build_model();
+        new_model.set_uh_state(state.clone()).unwrap();
+        assert_eq!(new_model.uh_state(), state);
+
+        // wrong sizes
+        assert!(new_model.set_uh_state(vec![]).is_err());
+        let err = new_model
+            .set_uh_state(vec![(vec![0.0; 40], vec![0.0; 20])])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The unit hydrographs of unit #0 must have 20 and 40 values, but 40 and 20 were given"
+        );
+        assert_eq!(new_model.uh_state(), state);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 4) as f64 * 5.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let build_model = || {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap()
+        };
+
+        let mut model = build_model();
+        for _ in 0..100 {
+            model.step(0).unwrap();
+        }
+        let snapshot = model.state(0);
+        assert_eq!(snapshot.step, 100);
+        let expected: Vec<f64> = (0..100).map(|_| model.step(0).unwrap().run_off).collect();
+
+        // restore the state in a new model, after a round trip to JSON with the serde feature
+        #[cfg(feature = "serde")]
+        let restored: ModelState = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        #[cfg(not(feature = "serde"))]
+        let restored = snapshot.clone();
+        let mut new_model = build_model();
+        new_model.set_state(0, restored).unwrap();
+        assert_eq!(new_model.state(0), snapshot);
+        let run_off: Vec<f64> = (0..100).map(|_| new_model.step(0).unwrap().run_off).collect();
+        assert_eq!(run_off, expected);
+
+        // invalid states
+        assert!(new_model.set_state(1, snapshot.clone()).is_err());
+        let invalid = ModelState {
+            unit_hydrograph1: vec![0.0; 40],
+            ..snapshot.clone()
+        };
+        assert!(new_model.set_state(0, invalid).is_err());
+        let invalid = ModelState {
+            step: t.len() + 1,
+            ..snapshot
+        };
+        assert!(new_model.set_state(0, invalid).is_err());
+    }
+
+    #[test]
+    fn test_advance() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 5) as f64 * 4.0).collect();
+        let evapotranspiration: Vec<f64> = (0..t.len()).map(|i| 1.0 + (i % 3) as f64).collect();
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let mut model = GR6JModel::new(inputs).unwrap();
+        let expected = model.run().unwrap();
+
+        // feeding the loaded forcing reproduces the run
+        model.reset();
+        let days: Vec<ModelStepData> = precipitation
+            .iter()
+            .zip(&evapotranspiration)
+            .map(|(p, e)| model.advance(0, *p, *e).unwrap())
+            .collect();
+        let time: Vec<NaiveDate> = days.iter().map(|d| d.time).collect();
+        let run_off: Vec<f64> = days.iter().map(|d| d.run_off).collect();
+        assert_eq!(time, expected.time);
+        assert_eq!(
+            run_off,
+            expected.catchment_outputs[0]
+                .0
+                .iter()
+                .map(|d| d.run_off)
+                .collect::<Vec<_>>()
+        );
+
+        // the model can be advanced past the loaded data
+        let day = model.advance(0, 10.0, 1.0).unwrap();
+        assert_eq!(day.time, t[365] + TimeDelta::try_days(1).unwrap());
+    }
+
+    #[test]
+    fn test_forcing_correction() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 6) as f64 * 2.0).collect();
+        let evapotranspiration = vec![1.2; t.len()];
+        let build_model = |precipitation: &[f64],
+                           evapotranspiration: &[f64],
+                           precip_correction: Option<f64>,
+                           pet_correction: Option<f64>| {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation,
+                evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                precip_correction,
+                pet_correction,
+                ..Default::default()
+            };
+            GR6JModel::new(inputs)
+        };
+
+        let mut model = build_model(&precipitation, &evapotranspiration, Some(1.1), Some(0.9)).unwrap();
+        let description = model.describe();
+        assert_eq!(description.precip_correction, 1.1);
+        assert_eq!(description.pet_correction, 0.9);
+
+        // same as correcting the series beforehand
+        let corrected_precipitation: Vec<f64> = precipitation.iter().map(|p| p * 1.1).collect();
+        let corrected_evapotranspiration: Vec<f64> = evapotranspiration.iter().map(|e| e * 0.9).collect();
+        let mut expected_model =
+            build_model(&corrected_precipitation, &corrected_evapotranspiration, None, None).unwrap();
+        assert_eq!(model.run().unwrap().run_off, expected_model.run().unwrap().run_off);
+
+        let err = build_model(&precipitation, &evapotranspiration, None, Some(0.0)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The evapotranspiration correction factor must be larger than 0"
+        );
+    }
+
+    #[test]
+    fn test_destination_is_a_file() {
+        let t = build_t_vector();
+        let precipitation = vec![0.0; t.len()];
+        let evapotranspiration = vec![0.0; t.len()];
+        let file = env::temp_dir().join("gr6j_test_destination_is_a_file.txt");
+        fs::write(&file, "").unwrap();
+
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            destination: Some(file.clone()),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let model = GR6JModel::new(inputs);
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(
+            model.unwrap_err().to_string(),
+            format!("The destination {} is not a folder", file.to_str().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_charts() {
+        let t = build_t_vector();
+        let precipitation = vec![1.0; t.len()];
+        let evapotranspiration = vec![0.5; t.len()];
+        let observed = vec![0.2; t.len()];
+        let exported_files = |diagnostic_charts: Option<Vec<DiagnosticChart>>| -> Vec<String> {
+            let destination = env::temp_dir().join("gr6j_test_diagnostic_charts");
+            if destination.exists() {
+                fs::remove_dir_all(&destination).unwrap();
+            }
+            fs::create_dir(&destination).unwrap();
+
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[100]).unwrap(),
+                warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+                destination: Some(destination.clone()),
+                observed_runoff: Some(&observed),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                diagnostic_charts,
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap().run().unwrap();
+
+            // the results are in the sub-folder named with the run timestamp
+            let run_folder = fs::read_dir(&destination).unwrap().next().unwrap().unwrap().path();
+            let files = fs::read_dir(run_folder)
+                .unwrap()
+                .map(|file| file.unwrap().file_name().to_str().unwrap().to_string())
+                .collect();
+            fs::remove_dir_all(&destination).unwrap();
+            files
+        };
+
+        let files = exported_files(None);
+        assert!(files.contains(&"Summary.png".to_string()));
+        assert!(!files.contains(&"Cumulative_flow.png".to_string()));
+        assert!(!files.contains(&"Scatter.png".to_string()));
+        assert!(!files.contains(&"Monthly_regime.png".to_string()));
+
+        let files = exported_files(Some(vec![DiagnosticChart::CumulativeFlow]));
+        assert!(files.contains(&"Cumulative_flow.png".to_string()));
+        assert!(!files.contains(&"Scatter.png".to_string()));
+
+        let files = exported_files(Some(vec![DiagnosticChart::Scatter, DiagnosticChart::MonthlyRegime]));
+        assert!(files.contains(&"Scatter.png".to_string()));
+        assert!(files.contains(&"Monthly_regime.png".to_string()));
+    }
+
+    #[test]
+    fn test_initial_store_levels() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 3) as f64 * 4.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let run = |store_levels: Option<InitialStoreLevels>| {
+            let mut catchment = default_catchment_data();
+            catchment[0].x1 = X1::new(200.0).unwrap();
+            catchment[0].store_levels = store_levels;
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment,
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap().run().unwrap()
+        };
+
+        // the default levels are fractions of X1 and X3
+        let expected = run(None);
+        assert_eq!(
+            run(Some(CatchmentData::default_store_levels())).run_off,
+            expected.run_off
+        );
+        assert_eq!(
+            run(Some(StoreLevels::absolute(0.3 * 200.0, 0.5 * 0.4, 0.0))).run_off,
+            expected.run_off
+        );
+
+        // only the fractions are scaled
+        let results = run(Some(StoreLevels::fraction(0.5, 0.5, 0.0)));
+        assert_ne!(results.run_off, expected.run_off);
+        assert_eq!(
+            StoreLevels::absolute(100.0, 0.2, 1.0).to_absolute(200.0, 0.4),
+            StoreLevels {
+                production_store: 100.0,
+                routing_store: 0.2,
+                exponential_store: 1.0,
+            }
+        );
+        assert_eq!(
+            StoreLevels::fraction(0.5, 0.5, 1.0).to_absolute(200.0, 0.4),
+            StoreLevels {
+                production_store: 100.0,
+                routing_store: 0.2,
+                exponential_store: 1.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_impulse_response() {
+        let t = build_t_vector();
+        let precipitation = vec![3.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let mut model = GR6JModel::new(inputs).unwrap();
+
+        assert!(model.impulse_response(0).is_empty());
+        let response = model.impulse_response(60);
+        assert_eq!(response.len(), 60);
+        assert!(response.iter().all(|q| *q >= -1e-12));
+        // the pulse cannot generate more than 1 mm of run-off without the groundwater exchange
+        let total: f64 = response.iter().sum();
+        assert!(total > 0.0 && total <= 1.0 + 1e-12);
+
+        // the model is not changed
+        assert_eq!(model.run().unwrap().run_off.len(), 366);
+
+        // a longer time base of the unit hydrograph delays the peak
+        let peak_day = |r: &[f64]| r.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap().0;
+        model.set_parameters(0, [0.01, 0.0, 0.4, 5.0, 0.0, 0.4]).unwrap();
+        assert!(peak_day(&model.impulse_response(60)) > peak_day(&response));
+    }
+
+    #[test]
+    fn test_metadata() {
+        let t = build_t_vector();
+        let precipitation = vec![0.0; t.len()];
+        let evapotranspiration = vec![0.0; t.len()];
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[10], t[365]).unwrap(),
+            warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let model = GR6JModel::new(inputs).unwrap();
+        let metadata = model.metadata();
+
+        assert_eq!(metadata.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.forcing_length, 356);
+        assert_eq!(metadata.model.catchments[0].x1, 0.01);
+
+        #[cfg(feature = "serde")]
+        {
+            let json: serde_json::Value = serde_json::to_value(&metadata).unwrap();
+            assert_eq!(json["model"]["run_period"]["start"], "2000-01-11");
+            assert_eq!(json["model"]["warmup_strategy"], "NoWarmUp");
+            assert!(json["model"]["warmup_period"].is_null());
+        }
+    }
+
+    #[test]
+    /// The last day of the run period must be simulated.
+    fn test_run_period_end_is_included() {
+        let t = build_t_vector();
+        let precipitation = vec![1.0; t.len()];
+        let evapotranspiration = vec![0.5; t.len()];
+        let observed = vec![0.2; t.len()];
+        let run_period = ModelPeriod::new(t[0], t[365]).unwrap();
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period,
+            warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+            observed_runoff: Some(&observed),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let mut model = GR6JModel::new(inputs).unwrap();
+        let results = model.run().unwrap();
+
+        assert_eq!(results.time.last(), Some(&run_period.end));
+        assert_eq!(results.time.len(), t.len());
+        assert_eq!(results.run_off.len(), t.len());
+    }
+
+    #[test]
+    fn test_uh_exponent() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 3) as f64 * 4.0).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let run = |uh_exponent: Option<f64>| {
+            let mut catchment = default_catchment_data();
+            catchment[0].x4 = X4::new(3.5).unwrap();
+            catchment[0].uh_exponent = uh_exponent;
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment,
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).map(|mut model| model.run().unwrap())
+        };
+
+        // the default exponent is used in the comparison against airGR
+        let expected = run(None).unwrap();
+        assert_eq!(run(Some(UH_EXPONENT)).unwrap().run_off, expected.run_off);
+        assert_ne!(run(Some(4.0)).unwrap().run_off, expected.run_off);
+        assert!(run(Some(0.0)).is_err());
+        assert!(run(Some(f64::NAN)).is_err());
+
+        // a different exponent changes the shape of the unit hydrographs but not their volume
+        let levels = CatchmentData::default_store_levels();
+        let (x1, x3, x4) = (X1::new(200.0).unwrap(), X3::new(10.0).unwrap(), X4::new(3.5).unwrap());
+        let default_state = InternalState::new(levels, &x1, &x3, &x4, UH_EXPONENT);
+        let state = InternalState::new(levels, &x1, &x3, &x4, 4.0);
+        assert_ne!(
+            state.unit_hydrograph1.ordinates,
+            default_state.unit_hydrograph1.ordinates
+        );
+        assert_ne!(
+            state.unit_hydrograph2.ordinates,
+            default_state.unit_hydrograph2.ordinates
+        );
+        for uh in [&state.unit_hydrograph1, &state.unit_hydrograph2] {
+            assert!(approx_eq!(f64, uh.ordinates.iter().sum(), 1.0, epsilon = 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_unit_hydrograph_ordinates() {
+        let t = build_t_vector();
+        let precipitation = vec![1.0; t.len()];
+        let evapotranspiration = vec![1.0; t.len()];
+        let mut catchment = default_catchment_data();
+        catchment.push(CatchmentData {
+            x4: X4::new(2.5).unwrap(),
+            ..default_catchment_data().remove(0)
+        });
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment,
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            ..Default::default()
+        };
+        let model = GR6JModel::new(inputs).unwrap();
+
+        for model_index in 0..2 {
+            let (uh1, uh2) = model.unit_hydrograph_ordinates(model_index);
+            assert_eq!((uh1.len(), uh2.len()), (20, 40));
+            assert!(approx_eq!(f64, uh1.iter().sum(), 1.0, epsilon = 1e-12));
+            assert!(approx_eq!(f64, uh2.iter().sum(), 1.0, epsilon = 1e-12));
+        }
+
+        // with X4 = 2.5 the UH1 S-curve is (t / 2.5)^2.5 for the first two days
+        let (uh1, _) = model.unit_hydrograph_ordinates(1);
+        let s1 = 0.4_f64.powf(2.5);
+        let s2 = 0.8_f64.powf(2.5);
+        for (value, expected) in uh1.iter().zip([s1, s2 - s1, 1.0 - s2, 0.0]) {
+            assert!(approx_eq!(f64, *value, expected, epsilon = 1e-12));
+        }
+    }
+
+    /// Check that the buffer is a valid PNG image and return its width and height.
+    fn decode_png(buffer: &[u8]) -> (u32, u32) {
+        let mut reader = png::Decoder::new(buffer).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        (info.width, info.height)
+    }
+
+    #[test]
+    fn test_chart_bytes() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 7) as f64).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let observed: Vec<f64> = (0..t.len()).map(|i| 0.5 + (i % 5) as f64 / 10.0).collect();
+        let inputs = GR6JModelInputs {
+            time: &t,
+            precipitation: &precipitation,
+            evapotranspiration: &evapotranspiration,
+            catchment: default_catchment_data(),
+            run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+            warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+            observed_runoff: Some(&observed),
+            run_off_unit: RunOffUnit::NoConversion,
+            logging: Some(false),
+            fdc_log_panel: Some(false),
+            ..Default::default()
+        };
+        let mut model = GR6JModel::new(inputs).unwrap();
+        let results = model.run().unwrap();
+
+        let summary = model.summary_chart_bytes(&results).unwrap();
+        assert_eq!(decode_png(&summary), (1050, 1485));
+
+        let fdc = model.fdc_chart_bytes(&results).unwrap();
+        assert_eq!(decode_png(&fdc), (750, 375));
+    }
+
+    #[test]
+    fn test_optional_metrics() {
+        let t = build_t_vector();
+        let precipitation: Vec<f64> = (0..t.len()).map(|i| (i % 7) as f64).collect();
+        let evapotranspiration = vec![1.0; t.len()];
+        let observed: Vec<f64> = (0..t.len()).map(|i| 0.5 + (i % 5) as f64 / 10.0).collect();
+        let run = |optional_metrics: Option<OptionalMetrics>| {
+            let inputs = GR6JModelInputs {
+                time: &t,
+                precipitation: &precipitation,
+                evapotranspiration: &evapotranspiration,
+                catchment: default_catchment_data(),
+                run_period: ModelPeriod::new(t[0], t[365]).unwrap(),
+                warmup_strategy: Some(WarmupStrategy::NoWarmUp),
+                observed_runoff: Some(&observed),
+                run_off_unit: RunOffUnit::NoConversion,
+                logging: Some(false),
+                optional_metrics,
+                ..Default::default()
+            };
+            GR6JModel::new(inputs).unwrap().run().unwrap().metrics.unwrap()
+        };
+
+        let metrics = run(None);
+        assert!(metrics.kling_gupta2009.value.is_nan());
+        assert!(metrics.kling_gupta2012.value.is_nan());
+
+        let metrics = run(Some(OptionalMetrics {
+            kling_gupta2009: true,
+            kling_gupta2012: true,
+            ..OptionalMetrics::default()
+        }));
+        assert!(metrics.kling_gupta2009.value.is_finite());
+        assert!(metrics.kling_gupta2012.value.is_finite());
+        assert!(metrics.is_calculated(MetricType::KlingGupta2009));
+    }
 }
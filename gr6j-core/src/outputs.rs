@@ -1,10 +1,24 @@
-use crate::inputs::{CatchmentData, StoreLevels};
-use crate::metric::CalibrationMetric;
-use crate::parameter::Parameter;
-use chrono::NaiveDate;
+use crate::inputs::{
+    CalibrationCatchmentData, CatchmentData, ModelPeriod, OutputPrecision, RunOffUnit, StoreLevels, WarmupStrategy,
+};
+use crate::metric::{CalibrationMetric, MetricType};
+use crate::parameter::{
+    Parameter, ParameterRange, X1Range, X2Range, X3Range, X4Range, X5Range, X6Range, X1, X2, X3, X4, X5, X6,
+};
+use crate::utils::{format_float, Fdc, CSV_PRECISION};
+use chrono::{Datelike, NaiveDate};
+use csv::Writer;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+#[cfg(feature = "serde")]
+use std::io::Write;
+use std::path::Path;
 
 /// Outputs from a model time-step (one day)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ModelStepData {
     /// The time
     pub time: NaiveDate,
@@ -42,12 +56,131 @@ pub struct ModelStepData {
     pub run_off: f64,
 }
 
+/// The three additive components of the run-off at the catchment outlet.
+#[derive(Debug, Clone, Copy)]
+pub struct RunoffComponents {
+    /// Outflow from routing store (mm)
+    pub routing: f64,
+    /// Outflow from `UH2` branch after exchange (mm)
+    pub direct: f64,
+    /// Outflow from exponential store (mm)
+    pub exponential: f64,
+}
+
+impl RunoffComponents {
+    /// The sum of the components. This equals [`ModelStepData::run_off`].
+    pub fn total(&self) -> f64 {
+        self.routing + self.direct + self.exponential
+    }
+}
+
+/// The water balance of a simulation. All the quantities are totals in mm summed across the
+/// hydrological units. See [`GR6JOutputs::water_balance`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WaterBalance {
+    /// The total precipitation (mm)
+    pub precipitation: f64,
+    /// The total actual evapotranspiration (mm)
+    pub actual_evapotranspiration: f64,
+    /// The total run-off (mm)
+    pub run_off: f64,
+    /// The net actual exchange between catchments (mm). This is positive when water is gained.
+    pub exchange: f64,
+    /// The change of the production, routing and exponential store levels (mm)
+    pub storage_change: f64,
+    /// The closure residual: precipitation - actual evapotranspiration - run-off + exchange -
+    /// storage change (mm)
+    pub residual: f64,
+}
+
+/// The frequency used to aggregate a time series. See [`GR6JOutputs::resample`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    /// Aggregate the values in each calendar month.
+    Monthly,
+    /// Aggregate the values in each calendar year.
+    Annual,
+}
+
+impl Frequency {
+    /// Get the first date of the period containing a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `date`: The date.
+    ///
+    /// returns: NaiveDate
+    pub fn period_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Frequency::Monthly => NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap(),
+            Frequency::Annual => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+        }
+    }
+}
+
+impl ModelStepData {
+    /// Get the components that add up to the run-off.
+    ///
+    /// returns: `RunoffComponents`
+    pub fn runoff_components(&self) -> RunoffComponents {
+        RunoffComponents {
+            routing: self.routing_store_outflow,
+            direct: self.outflow_from_uh2_branch,
+            exponential: self.exponential_store_outflow,
+        }
+    }
+}
+
+/// The outputs of all hydrological units for one day. See [`crate::model::GR6JModel::run_iter`].
+#[derive(Debug, Clone)]
+pub struct DayOutput {
+    /// The day.
+    pub time: NaiveDate,
+    /// The step data of each hydrological unit.
+    pub catchment_outputs: Vec<ModelStepData>,
+    /// The run-off of all hydrological units in the unit of measurement set in
+    /// [`crate::inputs::GR6JModelInputs::run_off_unit`]. The run off of each unit is scaled by
+    /// its area.
+    pub run_off: f64,
+}
+
 /// A vector containing the results ([`ModelStepData`]) for each time step.
-#[derive(Debug)]
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ModelStepDataVector(pub Vec<ModelStepData>);
 
-/// The model outputs
-#[derive(Debug)]
+/// The store levels and run-off from a model time-step stored in single precision.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelStepDataF32 {
+    /// The time
+    pub time: NaiveDate,
+    /// The production store level (mm)
+    pub production_store: f32,
+    /// The routing store level (mm)
+    pub routing_store: f32,
+    /// The exponential store level (mm)
+    pub exponential_store: f32,
+    /// Simulated outflow at catchment outlet (mm)
+    pub run_off: f32,
+}
+
+impl From<&ModelStepData> for ModelStepDataF32 {
+    fn from(value: &ModelStepData) -> Self {
+        ModelStepDataF32 {
+            time: value.time,
+            production_store: value.store_levels.production_store as f32,
+            routing_store: value.store_levels.routing_store as f32,
+            exponential_store: value.store_levels.exponential_store as f32,
+            run_off: value.run_off as f32,
+        }
+    }
+}
+
+/// The model outputs. With the `serde` feature, these can be exported to JSON with
+/// `GR6JOutputs::to_json_writer`.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GR6JOutputs {
     /// The results for each catchment model and time step.
     pub catchment_outputs: Vec<ModelStepDataVector>,
@@ -56,9 +189,259 @@ pub struct GR6JOutputs {
     /// The run-off for the catchment or the combined sub-catchment run-off in the unit of
     /// measurements specified in [`crate::inputs::RunOffUnit`].
     pub run_off: Vec<f64>,
+    /// The run-off of each hydrological unit, scaled by the unit area, in the unit of measurements
+    /// specified in [`crate::inputs::RunOffUnit`]. The series are in the same order as
+    /// [`GR6JOutputs::catchment_outputs`] and their sum is [`GR6JOutputs::run_off`].
+    pub run_off_by_catchment: Vec<Vec<f64>>,
+    /// The combined run-off before the unit conversion (mm*km2/day). Use
+    /// [`GR6JOutputs::run_off_in`] to get the run-off in a different unit.
+    pub base_run_off: Vec<f64>,
+    /// The observed run-off aligned to [`GR6JOutputs::time`]. This is available only when
+    /// [`crate::inputs::GR6JModelInputs::observed_runoff`] is provided.
+    pub observed: Option<Vec<f64>>,
     /// The calibration metrics. This is available only when [`crate::inputs::GR6JModelInputs::observed_runoff`]
-    /// is provided.
+    /// is provided. The metrics are exported to JSON but are not restored when the outputs are
+    /// deserialised.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
     pub metrics: Option<CalibrationMetric>,
+    /// The strategy that was applied to initialise the model stores. This is
+    /// [`WarmupStrategy::NoWarmUp`] when the input data is too short to define a warm-up period.
+    pub warmup_strategy: WarmupStrategy,
+    /// The precision of the results. With [`OutputPrecision::F32`], [`GR6JOutputs::catchment_outputs`],
+    /// [`GR6JOutputs::run_off`], [`GR6JOutputs::run_off_by_catchment`] and
+    /// [`GR6JOutputs::base_run_off`] are empty and the results are stored in
+    /// [`GR6JOutputs::catchment_outputs_f32`], [`GR6JOutputs::run_off_f32`],
+    /// [`GR6JOutputs::run_off_by_catchment_f32`] and [`GR6JOutputs::base_run_off_f32`] instead.
+    pub precision: OutputPrecision,
+    /// The store levels and run-off for each catchment model and time step in single precision.
+    pub catchment_outputs_f32: Vec<Vec<ModelStepDataF32>>,
+    /// The run-off for the catchment or the combined sub-catchment run-off in single precision.
+    pub run_off_f32: Vec<f32>,
+    /// The run-off of each hydrological unit, scaled by the unit area, in single precision.
+    pub run_off_by_catchment_f32: Vec<Vec<f32>>,
+    /// The combined run-off before the unit conversion (mm*km2/day) in single precision.
+    pub base_run_off_f32: Vec<f32>,
+}
+
+impl GR6JOutputs {
+    /// Down-cast the run-off and the store levels to single precision and drop the double-precision
+    /// results. This does nothing if the results are already in single precision.
+    pub fn to_single_precision(&mut self) {
+        if self.precision == OutputPrecision::F32 {
+            return;
+        }
+        self.catchment_outputs_f32 = self
+            .catchment_outputs
+            .iter()
+            .map(|outputs| outputs.0.iter().map(ModelStepDataF32::from).collect())
+            .collect();
+        self.run_off_f32 = self.run_off.iter().map(|q| *q as f32).collect();
+        self.run_off_by_catchment_f32 = self
+            .run_off_by_catchment
+            .iter()
+            .map(|unit_run_off| unit_run_off.iter().map(|q| *q as f32).collect())
+            .collect();
+        self.catchment_outputs = vec![];
+        self.run_off = vec![];
+        self.base_run_off_f32 = self.base_run_off.iter().map(|q| *q as f32).collect();
+        self.run_off_by_catchment = vec![];
+        self.base_run_off = vec![];
+        self.precision = OutputPrecision::F32;
+    }
+
+    /// Convert the run-off to any unit of measurement, regardless of the unit used to run the model.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit`: The unit of measurement.
+    ///
+    /// returns: Vec<f64>
+    pub fn run_off_in(&self, unit: RunOffUnit) -> Vec<f64> {
+        let conv_factor = unit.conv_factor();
+        match self.precision {
+            OutputPrecision::F64 => self.base_run_off.iter().map(|q| q * conv_factor).collect(),
+            OutputPrecision::F32 => self.base_run_off_f32.iter().map(|q| *q as f64 * conv_factor).collect(),
+        }
+    }
+
+    /// Get the date and value of the maximum simulated run-off. Missing values are ignored.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the run-off vector is empty or only contains missing values.
+    ///
+    /// returns: (NaiveDate, f64)
+    pub fn peak_flow(&self) -> (NaiveDate, f64) {
+        self.time
+            .iter()
+            .zip(self.run_off_f64())
+            .filter(|(_, q)| !q.is_nan())
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(t, q)| (*t, q))
+            .expect("The run-off vector is empty")
+    }
+
+    /// Get the minimum of the 7-day rolling-mean run-off and the date of the last day of the
+    /// window. Windows containing missing values are ignored.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the run-off vector has less than 7 values or each window contains a missing
+    /// value.
+    ///
+    /// returns: (NaiveDate, f64)
+    pub fn low_flow_7day_min(&self) -> (NaiveDate, f64) {
+        self.run_off_f64()
+            .windows(7)
+            .zip(self.time.iter().skip(6))
+            .map(|(window, t)| (*t, window.iter().sum::<f64>() / 7.0))
+            .filter(|(_, q)| !q.is_nan())
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .expect("The run-off vector must contain at least 7 values")
+    }
+
+    /// Get the probability (0-100) that the simulated run-off is equal to or larger than a flow,
+    /// using the flow duration curve. See [`Fdc::exceedance_of_flow`].
+    ///
+    /// # Arguments
+    ///
+    /// * `q`: The flow in the unit of [`GR6JOutputs::run_off`].
+    ///
+    /// returns: f64
+    pub fn exceedance_of_flow(&self, q: f64) -> f64 {
+        Fdc::new(&self.run_off_f64()).exceedance_of_flow(q)
+    }
+
+    /// Get the residuals (observed minus simulated run-off) for each time step. The residual is
+    /// NaN when the observed run-off is missing.
+    ///
+    /// returns: Option<Vec<f64>>
+    pub fn residuals(&self) -> Option<Vec<f64>> {
+        self.observed
+            .as_ref()
+            .map(|observed| observed.iter().zip(self.run_off_f64()).map(|(o, s)| o - s).collect())
+    }
+
+    /// Get the total run-off in each calendar month or year. The periods at the beginning and end
+    /// of the simulation are included even when they are not complete and only contain the
+    /// available days. The total is NaN when the period contains a missing value.
+    ///
+    /// # Arguments
+    ///
+    /// * `freq`: The aggregation frequency.
+    ///
+    /// returns: Vec<(NaiveDate, f64)>. The first date of each period and the run-off total in
+    /// the unit of [`GR6JOutputs::run_off`].
+    pub fn resample(&self, freq: Frequency) -> Vec<(NaiveDate, f64)> {
+        let mut totals: Vec<(NaiveDate, f64)> = vec![];
+        for (t, q) in self.time.iter().zip(self.run_off_f64()) {
+            let period_start = freq.period_start(*t);
+            match totals.last_mut() {
+                Some((start, total)) if *start == period_start => *total += q,
+                _ => totals.push((period_start, q)),
+            }
+        }
+        totals
+    }
+
+    /// Export the outputs to JSON. The dates are written in the ISO-8601 format (YYYY-MM-DD) and
+    /// the missing values (NaN) are written as `null`.
+    ///
+    /// # Arguments
+    ///
+    /// * `w`: The writer (for example a file or a buffer).
+    ///
+    /// returns: serde_json::Result<()>
+    #[cfg(feature = "serde")]
+    pub fn to_json_writer<W: Write>(&self, w: W) -> serde_json::Result<()> {
+        serde_json::to_writer(w, self)
+    }
+
+    /// Get the water balance over the simulation, to check that the mass is conserved. Because
+    /// the store levels are recorded at the end of each day, the fluxes are summed from the second
+    /// day and the storage change is the difference between the levels on the last and first
+    /// day. The water in transit in the unit hydrographs is not part of the storage, therefore
+    /// the residual is close to zero only when the unit hydrographs hold a similar amount of water
+    /// on the first and last day. The balance is calculated from
+    /// [`GR6JOutputs::catchment_outputs`] and is empty with single-precision results.
+    ///
+    /// returns: WaterBalance
+    pub fn water_balance(&self) -> WaterBalance {
+        let mut balance = WaterBalance::default();
+        for outputs in self.catchment_outputs.iter() {
+            let (Some(first), Some(last)) = (outputs.0.first(), outputs.0.last()) else {
+                continue;
+            };
+            for step_data in outputs.0.iter().skip(1) {
+                balance.precipitation += step_data.precipitation;
+                balance.actual_evapotranspiration += step_data.actual_evapotranspiration;
+                balance.run_off += step_data.run_off;
+                balance.exchange += step_data.actual_exchange;
+            }
+            let total_storage =
+                |levels: &StoreLevels| levels.production_store + levels.routing_store + levels.exponential_store;
+            balance.storage_change += total_storage(&last.store_levels) - total_storage(&first.store_levels);
+        }
+        balance.residual = balance.precipitation - balance.actual_evapotranspiration - balance.run_off
+            + balance.exchange
+            - balance.storage_change;
+        balance
+    }
+
+    /// Convert the results to a polars DataFrame. The DataFrame contains the dates ("Time"), the
+    /// run-off ("Run off") and the production, routing and exponential store levels of each
+    /// hydrological unit (for example "Production store HU1"). This is only available with the
+    /// `polars` feature.
+    ///
+    /// returns: DataFrame
+    #[cfg(feature = "polars")]
+    pub fn to_dataframe(&self) -> polars::prelude::DataFrame {
+        use polars::prelude::{DataFrame, NamedFrom, Series};
+
+        let mut columns = vec![
+            Series::new("Time", &self.time),
+            Series::new("Run off", self.run_off_f64()),
+        ];
+        let store_levels: Vec<[Vec<f64>; 3]> = match self.precision {
+            OutputPrecision::F64 => self
+                .catchment_outputs
+                .iter()
+                .map(|outputs| {
+                    [
+                        outputs.production_store(),
+                        outputs.routing_store(),
+                        outputs.exponential_store(),
+                    ]
+                })
+                .collect(),
+            OutputPrecision::F32 => self
+                .catchment_outputs_f32
+                .iter()
+                .map(|outputs| {
+                    [
+                        outputs.iter().map(|d| d.production_store as f64).collect(),
+                        outputs.iter().map(|d| d.routing_store as f64).collect(),
+                        outputs.iter().map(|d| d.exponential_store as f64).collect(),
+                    ]
+                })
+                .collect(),
+        };
+        for (uh, levels) in store_levels.into_iter().enumerate() {
+            for (name, values) in ["Production", "Routing", "Exponential"].iter().zip(levels) {
+                columns.push(Series::new(&format!("{} store HU{}", name, uh + 1), values));
+            }
+        }
+
+        DataFrame::new(columns).expect("The output vectors must have the same length")
+    }
+
+    /// Get the run-off in double precision regardless of the precision of the results.
+    fn run_off_f64(&self) -> Vec<f64> {
+        match self.precision {
+            OutputPrecision::F64 => self.run_off.clone(),
+            OutputPrecision::F32 => self.run_off_f32.iter().map(|q| *q as f64).collect(),
+        }
+    }
 }
 
 impl ModelStepDataVector {
@@ -98,6 +481,73 @@ impl ModelStepDataVector {
             .map(|step_data| step_data.store_levels.exponential_store)
             .collect()
     }
+
+    /// Export all the fields of [`ModelStepData`] to a CSV file with one row per time step. The
+    /// columns are in the same order as the results exported from airGR.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination`: The destination CSV file.
+    /// * `precision`: The number of decimal places of the values. When None this defaults to
+    ///   [`CSV_PRECISION`].
+    ///
+    /// returns: Result<(), csv::Error>
+    pub fn to_csv(&self, destination: &Path, precision: Option<usize>) -> Result<(), csv::Error> {
+        let precision = precision.unwrap_or(CSV_PRECISION);
+        let mut wtr = Writer::from_path(destination)?;
+        wtr.write_record([
+            "Date",
+            "precipitation",
+            "evapotranspiration",
+            "net_rainfall",
+            "storage_p",
+            "pr",
+            "percolation",
+            "exchange",
+            "exchange_from_routing_store",
+            "exchange_from_direct_branch",
+            "routing_store_outflow",
+            "store_levels.production_store",
+            "store_levels.routing_store",
+            "store_levels.exponential_store",
+            "outflow_from_uh2_branch",
+            "actual_exchange",
+            "actual_evapotranspiration",
+            "exponential_store_outflow",
+            "run_off",
+        ])?;
+        for step_data in self.0.iter() {
+            let mut record = vec![step_data.time.to_string()];
+            record.extend(
+                [
+                    step_data.precipitation,
+                    step_data.evapotranspiration,
+                    step_data.net_rainfall,
+                    step_data.storage_p,
+                    step_data.pr,
+                    step_data.percolation,
+                    step_data.exchange,
+                    step_data.exchange_from_routing_store,
+                    step_data.exchange_from_direct_branch,
+                    step_data.routing_store_outflow,
+                    step_data.store_levels.production_store,
+                    step_data.store_levels.routing_store,
+                    step_data.store_levels.exponential_store,
+                    step_data.outflow_from_uh2_branch,
+                    step_data.actual_exchange,
+                    step_data.actual_evapotranspiration,
+                    step_data.exponential_store_outflow,
+                    step_data.run_off,
+                ]
+                .iter()
+                .map(|v| format_float(*v, precision)),
+            );
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+
+        Ok(())
+    }
 }
 
 /// This structure contains a vector with the metric values.
@@ -105,6 +555,24 @@ impl ModelStepDataVector {
 pub struct CalibrationMetricVector(pub(crate) Vec<CalibrationMetric>);
 
 impl CalibrationMetricVector {
+    /// Get all the metrics calculated for one model.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: The model index.
+    ///
+    /// returns: `Option<&CalibrationMetric>`
+    pub fn get(&self, index: usize) -> Option<&CalibrationMetric> {
+        self.0.get(index)
+    }
+    /// Get the number of models the metrics were calculated for.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Whether the vector does not contain any metric.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
     /// Get the vector of the Nash-Sutcliffe coefficients for all models.
     pub fn nash_sutcliffe(&self) -> Vec<f64> {
         self.0.iter().map(|m| m.nash_sutcliffe.value).collect()
@@ -113,6 +581,16 @@ impl CalibrationMetricVector {
     pub fn log_nash_sutcliffe(&self) -> Vec<f64> {
         self.0.iter().map(|m| m.log_nash_sutcliffe.value).collect()
     }
+    /// Get the vector of the 2009 Kling-Gupta coefficients for all models. The values are NaN when
+    /// the metric is not enabled (see [`crate::metric::OptionalMetrics`]).
+    pub fn kling_gupta2009(&self) -> Vec<f64> {
+        self.0.iter().map(|m| m.kling_gupta2009.value).collect()
+    }
+    /// Get the vector of the 2012 Kling-Gupta coefficients for all models. The values are NaN when
+    /// the metric is not enabled (see [`crate::metric::OptionalMetrics`]).
+    pub fn kling_gupta2012(&self) -> Vec<f64> {
+        self.0.iter().map(|m| m.kling_gupta2012.value).collect()
+    }
     /// Get the vector of the non-parametric Kling-Gupta coefficients for all models.
     pub fn non_parametric_kling_gupta(&self) -> Vec<f64> {
         self.0.iter().map(|m| m.non_parametric_kling_gupta.value).collect()
@@ -160,6 +638,22 @@ impl From<CatchmentData> for CalibrationParameterValues {
     }
 }
 
+/// The outputs of the differential-evolution optimiser.
+#[derive(Debug)]
+pub struct OptimiserOutputs {
+    /// The best parameter values for each sub-catchment.
+    pub parameters: Vec<CalibrationParameterValues>,
+    /// The calibration metrics of the best parameter set.
+    pub metrics: CalibrationMetric,
+    /// The value of the objective metric of the best parameter set after each generation. The
+    /// first value is the best value of the initial random population.
+    pub history: Vec<f64>,
+    /// The vector with the dates.
+    pub time: Vec<NaiveDate>,
+    /// The run-off simulated with the best parameter set.
+    pub run_off: Vec<f64>,
+}
+
 /// This structure contains a vector with the parameter values generated by the Latin Hypercube
 /// method for a model.
 #[derive(Debug)]
@@ -221,4 +715,610 @@ pub struct CalibrationOutputs {
     /// The list of calibration metrics for each simulated model. Use this to assess the calibration
     /// accuracy. The size of this vector is [`crate::inputs::CalibrationInputs::sample_size`].
     pub metrics: CalibrationMetricVector,
+    /// The area of each sub-catchment (km2).
+    pub areas: Vec<f64>,
+    /// The index and error message of the samples that failed to run when
+    /// [`crate::inputs::CalibrationInputs::skip_failures`] is enabled. The failed samples are
+    /// excluded from the other vectors.
+    pub failed_samples: Vec<(usize, String)>,
+    /// The composite score of each simulated model when
+    /// [`crate::inputs::CalibrationInputs::objective`] or
+    /// [`crate::inputs::CalibrationInputs::objective_fn`] is provided. The smaller the score, the
+    /// better the model.
+    pub objective_scores: Option<Vec<f64>>,
+    /// The index of the model with the smallest score. This is `None` when no objective is
+    /// provided or all scores are missing.
+    pub best_sample: Option<usize>,
+}
+
+impl CalibrationOutputs {
+    /// Get the number of samples that ran successfully and produced the calibration metrics.
+    ///
+    /// returns: usize
+    pub fn valid_sample_count(&self) -> usize {
+        self.metrics.0.len()
+    }
+
+    /// Suggest narrower parameter ranges to refine the calibration. The samples are ranked by how
+    /// close the `objective` metric is to its ideal value and the parameter ranges are set to the
+    /// minimum and maximum values of the best samples. Samples with a missing metric are ranked last.
+    ///
+    /// # Arguments
+    ///
+    /// * `objective`: The metric to rank the samples with.
+    /// * `top_fraction`: The fraction of the best samples to use (0-1). At least one sample is
+    ///   always used.
+    ///
+    /// returns: `Vec<CalibrationCatchmentData>`
+    pub fn suggest_ranges(&self, objective: MetricType, top_fraction: f64) -> Vec<CalibrationCatchmentData> {
+        let mut ranking: Vec<(usize, f64)> = self
+            .metrics
+            .0
+            .iter()
+            .map(|m| {
+                let metric = m.get(objective);
+                (metric.value - metric.ideal_value).abs()
+            })
+            .enumerate()
+            .collect();
+        ranking.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let total = ((ranking.len() as f64 * top_fraction.min(1.0)).ceil() as usize).max(1);
+        let best: Vec<usize> = ranking.iter().take(total).map(|(index, _)| *index).collect();
+
+        self.parameters
+            .iter()
+            .zip(&self.areas)
+            .map(|(parameters, area)| {
+                let values = parameters.to_vec();
+                let range = |p: usize, min: f64, max: f64| {
+                    let best_values = best.iter().map(|i| values[p][*i]);
+                    let lower = best_values.clone().fold(f64::INFINITY, f64::min).max(min);
+                    let upper = best_values.fold(f64::NEG_INFINITY, f64::max).min(max);
+                    (lower, upper)
+                };
+
+                // the sampled values are always within the parameter bounds
+                let (l, u) = range(0, X1::min_value(), X1::max_value());
+                let x1 = X1Range::new(l, u).unwrap();
+                let (l, u) = range(1, X2::min_value(), X2::max_value());
+                let x2 = X2Range::new(l, u).unwrap();
+                let (l, u) = range(2, X3::min_value(), X3::max_value());
+                let x3 = X3Range::new(l, u).unwrap();
+                let (l, u) = range(3, X4::min_value(), X4::max_value());
+                let x4 = X4Range::new(l, u).unwrap();
+                let (l, u) = range(4, X5::min_value(), X5::max_value());
+                let x5 = X5Range::new(l, u).unwrap();
+                let (l, u) = range(5, X6::min_value(), X6::max_value());
+                let x6 = X6Range::new(l, u).unwrap();
+
+                CalibrationCatchmentData {
+                    area: *area,
+                    x1,
+                    x2,
+                    x3,
+                    x4,
+                    x5,
+                    x6,
+                }
+            })
+            .collect()
+    }
+
+    /// Get the simulations on the Pareto front of two or more metrics (for example NSE and log-NSE
+    /// to balance the fit of high and low flows). A simulation is on the front when no other
+    /// simulation is at least as close to the ideal value for all `objectives` and closer for at
+    /// least one of them. Simulations with a missing metric are never on the front.
+    ///
+    /// # Arguments
+    ///
+    /// * `objectives`: The metrics to compare the simulations with.
+    ///
+    /// returns: `Vec<usize>`
+    pub fn pareto_front(&self, objectives: &[MetricType]) -> Vec<usize> {
+        let distances: Vec<Option<Vec<f64>>> = self
+            .metrics
+            .0
+            .iter()
+            .map(|m| {
+                let distances: Vec<f64> = objectives
+                    .iter()
+                    .map(|objective| {
+                        let metric = m.get(*objective);
+                        (metric.value - metric.ideal_value).abs()
+                    })
+                    .collect();
+                match distances.iter().any(|d| d.is_nan()) {
+                    true => None,
+                    false => Some(distances),
+                }
+            })
+            .collect();
+
+        let dominates =
+            |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(x, y)| x <= y) && a.iter().zip(b).any(|(x, y)| x < y);
+        distances
+            .iter()
+            .enumerate()
+            .filter_map(|(index, candidate)| {
+                let candidate = candidate.as_ref()?;
+                let dominated = distances.iter().flatten().any(|other| dominates(other, candidate));
+                (!dominated).then_some(index)
+            })
+            .collect()
+    }
+}
+
+/// The area and parameters of a hydrological unit.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CatchmentDescription {
+    /// The catchment os sub-catchment area (km2).
+    pub area: f64,
+    /// Maximum capacity of the production store (mm/day).
+    pub x1: f64,
+    /// Inter-catchment (or groundwater) exchange coefficient (mm/day).
+    pub x2: f64,
+    /// One-day-ahead maximum capacity of the routing store (mm/day).
+    pub x3: f64,
+    /// Time base of unit hydrograph `UH1` (days).
+    pub x4: f64,
+    /// Inter-catchment exchange threshold.
+    pub x5: f64,
+    /// Time constant of exponential store (mm)
+    pub x6: f64,
+}
+
+/// A summary of how a [`crate::model::GR6JModel`] was configured.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ModelDescription {
+    /// The area and parameters of each hydrological unit.
+    pub catchments: Vec<CatchmentDescription>,
+    /// The model run period.
+    pub run_period: ModelPeriod,
+    /// The warm-up period used to initialise the stores. This is `None` when the model runs
+    /// without a warm-up period.
+    pub warmup_period: Option<ModelPeriod>,
+    /// The strategy used to initialise the stores.
+    pub warmup_strategy: WarmupStrategy,
+    /// The unit of measurement of the simulated run-off.
+    pub run_off_unit: RunOffUnit,
+    /// Whether the observed run-off was provided.
+    pub has_observed_runoff: bool,
+    /// The factor applied to the precipitation series.
+    pub precip_correction: f64,
+    /// The factor applied to the potential evapotranspiration series.
+    pub pet_correction: f64,
+}
+
+/// A snapshot of the internal state of one hydrological unit. This can be saved to resume the
+/// simulation from the same day later or in another process (see
+/// [`crate::model::GR6JModel::state`] and [`crate::model::GR6JModel::set_state`]).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelState {
+    /// The index of the next time step to run.
+    pub step: usize,
+    /// The absolute store levels (mm).
+    pub store_levels: StoreLevels,
+    /// The values of the first unit hydrograph.
+    pub unit_hydrograph1: Vec<f64>,
+    /// The values of the second unit hydrograph.
+    pub unit_hydrograph2: Vec<f64>,
+}
+
+/// The metadata of a model run written to `run_metadata.json` in the destination folder, so that
+/// the run can be reproduced.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RunMetadata {
+    /// The version of the crate used to run the model.
+    pub crate_version: String,
+    /// The date and time when the model was run (RFC 3339).
+    pub timestamp: String,
+    /// The model configuration.
+    pub model: ModelDescription,
+    /// The number of days of forcing data used by the model, including the warm-up period.
+    pub forcing_length: usize,
+}
+
+impl fmt::Display for ModelDescription {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "GR6J model with {} hydrological unit(s)", self.catchments.len())?;
+        writeln!(f, "Run period: {}", self.run_period)?;
+        match &self.warmup_period {
+            None => writeln!(f, "Warm-up period: none")?,
+            Some(period) => writeln!(f, "Warm-up period: {}", period)?,
+        };
+        writeln!(f, "Warm-up strategy: {:?}", self.warmup_strategy)?;
+        writeln!(f, "Run-off unit: {}", self.run_off_unit.unit_label())?;
+        writeln!(f, "Observed run-off: {}", self.has_observed_runoff)?;
+        writeln!(
+            f,
+            "Correction factors: precipitation x{}, evapotranspiration x{}",
+            self.precip_correction, self.pet_correction
+        )?;
+        for (index, c) in self.catchments.iter().enumerate() {
+            write!(
+                f,
+                "Unit #{}: area={} km2, X1={}, X2={}, X3={}, X4={}, X5={}, X6={}",
+                index + 1,
+                c.area,
+                c.x1,
+                c.x2,
+                c.x3,
+                c.x4,
+                c.x5,
+                c.x6
+            )?;
+            if index + 1 < self.catchments.len() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    use crate::inputs::OutputPrecision;
+    use crate::inputs::RunOffUnit;
+    #[cfg(any(feature = "polars", feature = "serde"))]
+    use crate::inputs::StoreLevels;
+    use crate::metric::{CalibrationMetric, MetricType, OptionalMetrics};
+    use crate::outputs::{
+        CalibrationMetricVector, CalibrationOutputs, CalibrationParameterValueVector, CalibrationParameterValues,
+        Frequency, GR6JOutputs,
+    };
+    #[cfg(any(feature = "polars", feature = "serde"))]
+    use crate::outputs::{ModelStepData, ModelStepDataVector};
+    use chrono::{Datelike, Months, NaiveDate, TimeDelta};
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_to_dataframe() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..3).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let unit_outputs = || {
+            let data = time
+                .iter()
+                .map(|t| ModelStepData {
+                    time: *t,
+                    evapotranspiration: 0.0,
+                    precipitation: 0.0,
+                    net_rainfall: 0.0,
+                    store_levels: StoreLevels {
+                        production_store: 1.0,
+                        routing_store: 2.0,
+                        exponential_store: 3.0,
+                    },
+                    storage_p: 0.0,
+                    actual_evapotranspiration: 0.0,
+                    percolation: 0.0,
+                    pr: 0.0,
+                    exchange: 0.0,
+                    exchange_from_routing_store: 0.0,
+                    exchange_from_direct_branch: 0.0,
+                    actual_exchange: 0.0,
+                    routing_store_outflow: 0.0,
+                    exponential_store_outflow: 0.0,
+                    outflow_from_uh2_branch: 0.0,
+                    run_off: 1.0,
+                })
+                .collect();
+            ModelStepDataVector(data)
+        };
+        let outputs = GR6JOutputs {
+            catchment_outputs: vec![unit_outputs(), unit_outputs()],
+            time: time.clone(),
+            run_off: vec![2.0; 3],
+            ..GR6JOutputs::default()
+        };
+
+        let df = outputs.to_dataframe();
+        assert_eq!(df.height(), 3);
+        assert_eq!(
+            df.get_column_names(),
+            [
+                "Time",
+                "Run off",
+                "Production store HU1",
+                "Routing store HU1",
+                "Exponential store HU1",
+                "Production store HU2",
+                "Routing store HU2",
+                "Exponential store HU2"
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_json() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let step_data = ModelStepData {
+            time: start,
+            evapotranspiration: 1.0,
+            precipitation: 2.0,
+            net_rainfall: 1.0,
+            store_levels: StoreLevels {
+                production_store: 10.0,
+                routing_store: 20.0,
+                exponential_store: -3.0,
+            },
+            storage_p: 0.5,
+            actual_evapotranspiration: 1.0,
+            percolation: 0.01,
+            pr: 0.51,
+            exchange: 0.0,
+            exchange_from_routing_store: 0.0,
+            exchange_from_direct_branch: 0.0,
+            actual_exchange: 0.0,
+            routing_store_outflow: 0.1,
+            exponential_store_outflow: 0.2,
+            outflow_from_uh2_branch: 0.3,
+            run_off: 0.6,
+        };
+        let outputs = GR6JOutputs {
+            catchment_outputs: vec![ModelStepDataVector(vec![step_data.clone()])],
+            time: vec![start],
+            run_off: vec![0.6],
+            base_run_off: vec![0.6],
+            ..GR6JOutputs::default()
+        };
+
+        let mut buffer: Vec<u8> = vec![];
+        outputs.to_json_writer(&mut buffer).unwrap();
+        let json = String::from_utf8(buffer).unwrap();
+        assert!(json.contains(r#""time":["2000-01-01"]"#));
+
+        let parsed: GR6JOutputs = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.run_off, outputs.run_off);
+        assert_eq!(parsed.time, outputs.time);
+        assert_eq!(parsed.catchment_outputs[0].0[0].store_levels, step_data.store_levels);
+        assert_eq!(parsed.precision, OutputPrecision::F64);
+    }
+
+    #[test]
+    fn test_resample() {
+        // from 2000-12-30 to 2002-02-02
+        let start = NaiveDate::from_ymd_opt(2000, 12, 30).unwrap();
+        let time: Vec<NaiveDate> = (0..400).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let run_off: Vec<f64> = time.iter().map(|t| t.month() as f64).collect();
+        let outputs = GR6JOutputs {
+            time,
+            run_off,
+            ..GR6JOutputs::default()
+        };
+
+        let date = |y: i32, m: u32| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+        let monthly = outputs.resample(Frequency::Monthly);
+        assert_eq!(monthly.len(), 15);
+        // partial months at the edges
+        assert_eq!(monthly[0], (date(2000, 12), 2.0 * 12.0));
+        assert_eq!(monthly[14], (date(2002, 2), 2.0 * 2.0));
+        assert_eq!(monthly[1], (date(2001, 1), 31.0));
+        assert_eq!(monthly[2], (date(2001, 2), 28.0 * 2.0));
+        assert_eq!(monthly[13], (date(2002, 1), 31.0));
+
+        let annual = outputs.resample(Frequency::Annual);
+        let total_2001: f64 = (1..=12)
+            .map(|m| m as f64 * (date(2001, m) + Months::new(1) - date(2001, m)).num_days() as f64)
+            .sum();
+        assert_eq!(
+            annual,
+            vec![
+                (date(2000, 1), 24.0),
+                (date(2001, 1), total_2001),
+                (date(2002, 1), 31.0 + 4.0)
+            ]
+        );
+
+        // missing values
+        let mut outputs = outputs;
+        outputs.run_off[40] = f64::NAN;
+        let monthly = outputs.resample(Frequency::Monthly);
+        assert!(monthly[2].1.is_nan());
+        assert_eq!(monthly[3].1, 31.0 * 3.0);
+    }
+
+    #[test]
+    fn test_peak_and_low_flow() {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = (0..10).map(|d| start + TimeDelta::try_days(d).unwrap()).collect();
+        let mut outputs = GR6JOutputs {
+            time,
+            run_off: vec![5.0, 4.0, 3.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 10.0],
+            ..GR6JOutputs::default()
+        };
+
+        assert_eq!(
+            outputs.peak_flow(),
+            (NaiveDate::from_ymd_opt(2000, 1, 10).unwrap(), 10.0)
+        );
+        // the window from the 3rd to the 9th of January has the lowest mean
+        let (date, value) = outputs.low_flow_7day_min();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2000, 1, 9).unwrap());
+        assert_eq!(value, 9.0 / 7.0);
+
+        // missing values are skipped
+        outputs.run_off[9] = f64::NAN;
+        assert_eq!(outputs.peak_flow(), (start, 5.0));
+        outputs.run_off[8] = f64::NAN;
+        assert_eq!(
+            outputs.low_flow_7day_min(),
+            (NaiveDate::from_ymd_opt(2000, 1, 8).unwrap(), 12.0 / 7.0)
+        );
+
+        // single precision
+        outputs.to_single_precision();
+        assert_eq!(outputs.peak_flow(), (start, 5.0));
+    }
+
+    #[test]
+    fn test_run_off_in() {
+        let mut outputs = GR6JOutputs {
+            base_run_off: vec![1000.0, 2000.0],
+            ..GR6JOutputs::default()
+        };
+        assert_eq!(outputs.run_off_in(RunOffUnit::NoConversion), vec![1000.0, 2000.0]);
+        assert_eq!(
+            outputs.run_off_in(RunOffUnit::CubicMetrePerDay),
+            vec![
+                1000.0 * RunOffUnit::CubicMetrePerDay.conv_factor(),
+                2000.0 * RunOffUnit::CubicMetrePerDay.conv_factor()
+            ]
+        );
+
+        // single precision
+        outputs.to_single_precision();
+        assert!(outputs.base_run_off.is_empty());
+        assert_eq!(outputs.base_run_off_f32, vec![1000.0, 2000.0]);
+        assert_eq!(outputs.run_off_in(RunOffUnit::NoConversion), vec![1000.0, 2000.0]);
+    }
+
+    #[test]
+    fn test_residuals() {
+        let mut outputs = GR6JOutputs {
+            run_off: vec![1.0, 2.0, 3.0],
+            ..GR6JOutputs::default()
+        };
+        assert!(outputs.residuals().is_none());
+
+        outputs.observed = Some(vec![1.5, f64::NAN, 2.0]);
+        let residuals = outputs.residuals().unwrap();
+        assert_eq!(residuals[0], 0.5);
+        assert!(residuals[1].is_nan());
+        assert_eq!(residuals[2], -1.0);
+    }
+
+    #[test]
+    fn test_suggest_ranges() {
+        let observed = [1.0, 2.0, 3.0, 4.0];
+        let simulated = [
+            [1.0, 2.0, 3.0, 4.0],
+            [8.0, 1.0, 0.5, 9.0],
+            [1.1, 2.0, 3.0, 4.1],
+            [1.0, 2.0, 3.0, 4.0],
+        ];
+        let mut metrics: Vec<CalibrationMetric> = simulated
+            .iter()
+            .map(|sim| CalibrationMetric::new(&observed, sim, None).unwrap())
+            .collect();
+        // the last sample has a missing metric
+        metrics[3].nash_sutcliffe.value = f64::NAN;
+        let parameters = (0..4)
+            .map(|i| CalibrationParameterValues {
+                x1: 100.0 * (i + 1) as f64,
+                x2: 0.0,
+                x3: 10.0 * (i + 1) as f64,
+                x4: 1.0,
+                x5: 0.1 * i as f64,
+                x6: 5.0,
+            })
+            .collect();
+
+        let outputs = CalibrationOutputs {
+            time: vec![],
+            run_off: vec![],
+            parameters: vec![CalibrationParameterValueVector(parameters)],
+            metrics: CalibrationMetricVector(metrics),
+            areas: vec![2.0],
+            failed_samples: vec![],
+            objective_scores: None,
+            best_sample: None,
+        };
+
+        assert_eq!(outputs.valid_sample_count(), 4);
+
+        // the first and third samples are the best ones
+        let ranges = outputs.suggest_ranges(MetricType::NashSutcliffe, 0.5);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].area, 2.0);
+        assert_eq!((ranges[0].x1.lower_bound, ranges[0].x1.upper_bound), (100.0, 300.0));
+        assert_eq!((ranges[0].x3.lower_bound, ranges[0].x3.upper_bound), (10.0, 30.0));
+        assert_eq!((ranges[0].x5.lower_bound, ranges[0].x5.upper_bound), (0.0, 0.2));
+
+        // at least one sample is used
+        let ranges = outputs.suggest_ranges(MetricType::Rmse, 0.0);
+        assert_eq!((ranges[0].x1.lower_bound, ranges[0].x1.upper_bound), (100.0, 100.0));
+    }
+
+    #[test]
+    fn test_pareto_front() {
+        let observed = [1.0, 2.0, 3.0, 4.0];
+        // the NSE and log-NSE of each simulation
+        let values = [
+            (0.9, 0.5),
+            (0.8, 0.8),
+            (0.5, 0.9),
+            (0.7, 0.7),
+            (0.9, 0.4),
+            (f64::NAN, 1.0),
+            (0.8, 0.8),
+        ];
+        let metrics = values
+            .iter()
+            .map(|(nse, log_nse)| {
+                let mut metric = CalibrationMetric::new(&observed, &observed, None).unwrap();
+                metric.nash_sutcliffe.value = *nse;
+                metric.log_nash_sutcliffe.value = *log_nse;
+                metric
+            })
+            .collect();
+
+        let outputs = CalibrationOutputs {
+            time: vec![],
+            run_off: vec![],
+            parameters: vec![],
+            metrics: CalibrationMetricVector(metrics),
+            areas: vec![],
+            failed_samples: vec![],
+            objective_scores: None,
+            best_sample: None,
+        };
+
+        // the 4th and 5th simulations are dominated, the 6th has a missing metric and identical
+        // simulations do not dominate each other
+        assert_eq!(
+            outputs.pareto_front(&[MetricType::NashSutcliffe, MetricType::LogNashSutcliffe]),
+            vec![0, 1, 2, 6]
+        );
+        assert_eq!(outputs.pareto_front(&[MetricType::NashSutcliffe]), vec![0, 4]);
+    }
+
+    #[test]
+    fn test_kling_gupta_accessors() {
+        let observed = [1.0, 2.0, 3.0, 4.0];
+        let optional_metrics = OptionalMetrics {
+            kling_gupta2009: true,
+            kling_gupta2012: true,
+            ..OptionalMetrics::default()
+        };
+        let metrics = CalibrationMetricVector(vec![
+            CalibrationMetric::new(&observed, &observed, Some(optional_metrics.clone())).unwrap(),
+            CalibrationMetric::new(&observed, &[2.0, 3.0, 5.0, 6.0], Some(optional_metrics)).unwrap(),
+        ]);
+
+        let kge2009 = metrics.kling_gupta2009();
+        let kge2012 = metrics.kling_gupta2012();
+        assert_eq!(kge2009.len(), 2);
+        assert_eq!(kge2012.len(), 2);
+        for (index, metric) in metrics.0.iter().enumerate() {
+            assert_eq!(kge2009[index], metric.kling_gupta2009.value);
+            assert_eq!(kge2012[index], metric.kling_gupta2012.value);
+        }
+        // a perfect simulation
+        assert_eq!(kge2009[0], 1.0);
+        assert_eq!(kge2012[0], 1.0);
+        assert!(kge2009[1] < 1.0);
+        assert!(kge2012[1] < 1.0);
+
+        // the metrics are NaN when not enabled
+        let metrics = CalibrationMetricVector(vec![CalibrationMetric::new(&observed, &observed, None).unwrap(); 2]);
+        assert!(metrics.kling_gupta2009().iter().all(|v| v.is_nan()));
+        assert!(metrics.kling_gupta2012().iter().all(|v| v.is_nan()));
+    }
 }
@@ -1,4 +1,6 @@
 use crate::error::LoadModelError;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 
@@ -43,6 +45,7 @@ pub trait Parameter<'a>: fmt::Display {
 
 /// Maximum capacity of the production store (mm/day)
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(try_from = "f64"))]
 pub struct X1(f64);
 
 impl<'a> Parameter<'a> for X1 {
@@ -72,6 +75,14 @@ impl<'a> Parameter<'a> for X1 {
     }
 }
 
+impl TryFrom<f64> for X1 {
+    type Error = LoadModelError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        X1::new(value).map(|p| *p)
+    }
+}
+
 impl fmt::Display for X1 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{} (X1={})", X1::description(), self.0)
@@ -82,6 +93,7 @@ impl fmt::Display for X1 {
 /// or negative to simulate imports or exports of water with deep aquifers or
 /// surrounding catchments.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(try_from = "f64"))]
 pub struct X2(f64);
 
 impl<'a> Parameter<'a> for X2 {
@@ -111,6 +123,14 @@ impl<'a> Parameter<'a> for X2 {
     }
 }
 
+impl TryFrom<f64> for X2 {
+    type Error = LoadModelError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        X2::new(value).map(|p| *p)
+    }
+}
+
 impl fmt::Display for X2 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{} (X2={})", X2::description(), self.0)
@@ -119,6 +139,7 @@ impl fmt::Display for X2 {
 
 /// One-day-ahead maximum capacity of the routing store (mm/day)
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(try_from = "f64"))]
 pub struct X3(f64);
 
 impl<'a> Parameter<'a> for X3 {
@@ -148,6 +169,14 @@ impl<'a> Parameter<'a> for X3 {
     }
 }
 
+impl TryFrom<f64> for X3 {
+    type Error = LoadModelError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        X3::new(value).map(|p| *p)
+    }
+}
+
 impl fmt::Display for X3 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{} (X3={})", X3::description(), self.0)
@@ -156,6 +185,7 @@ impl fmt::Display for X3 {
 
 /// Time base of unit hydrograph `UH1` (days)
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(try_from = "f64"))]
 pub struct X4(f64);
 
 impl<'a> Parameter<'a> for X4 {
@@ -185,6 +215,14 @@ impl<'a> Parameter<'a> for X4 {
     }
 }
 
+impl TryFrom<f64> for X4 {
+    type Error = LoadModelError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        X4::new(value).map(|p| *p)
+    }
+}
+
 impl fmt::Display for X4 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{} (X4={})", X4::description(), self.0)
@@ -195,6 +233,7 @@ impl fmt::Display for X4 {
 /// allows a change in the direction of the groundwater exchange depending on the capacity
 /// of the routing store level `R`.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(try_from = "f64"))]
 pub struct X5(f64);
 
 impl<'a> Parameter<'a> for X5 {
@@ -224,6 +263,14 @@ impl<'a> Parameter<'a> for X5 {
     }
 }
 
+impl TryFrom<f64> for X5 {
+    type Error = LoadModelError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        X5::new(value).map(|p| *p)
+    }
+}
+
 impl fmt::Display for X5 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{} (X5={})", X5::description(), self.0)
@@ -232,6 +279,7 @@ impl fmt::Display for X5 {
 
 /// Time constant of exponential store (mm)
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(try_from = "f64"))]
 pub struct X6(f64);
 
 impl<'a> Parameter<'a> for X6 {
@@ -261,6 +309,14 @@ impl<'a> Parameter<'a> for X6 {
     }
 }
 
+impl TryFrom<f64> for X6 {
+    type Error = LoadModelError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        X6::new(value).map(|p| *p)
+    }
+}
+
 impl fmt::Display for X6 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{} (X6={})", X6::description(), self.0)
@@ -0,0 +1,151 @@
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use pdf_writer::{Content, Filter, Name, Pdf, Rect, Ref};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The resolution used to convert the size of the page images from pixels to points.
+const DPI: f64 = 150.0;
+
+/// A page of a PDF document containing one raster image.
+pub(crate) struct PdfPage {
+    /// The image width in pixels.
+    width: u32,
+    /// The image height in pixels.
+    height: u32,
+    /// The RGB pixels of the image compressed with the Deflate algorithm.
+    data: Vec<u8>,
+}
+
+impl PdfPage {
+    /// Create a new page from an RGB image.
+    ///
+    /// # Arguments
+    ///
+    /// * `width`: The image width in pixels.
+    /// * `height`: The image height in pixels.
+    /// * `rgb`: The image pixels with three bytes (red, green and blue) per pixel.
+    ///
+    /// returns: `Result<PdfPage, io::Error>`
+    pub(crate) fn new(width: u32, height: u32, rgb: &[u8]) -> io::Result<Self> {
+        if rgb.len() != (width * height * 3) as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the image has {} bytes instead of {}", rgb.len(), width * height * 3),
+            ));
+        }
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(rgb)?;
+        Ok(Self {
+            width,
+            height,
+            data: encoder.finish()?,
+        })
+    }
+}
+
+/// A minimal PDF document where each page contains an image filling the whole page.
+#[derive(Default)]
+pub(crate) struct PdfDocument {
+    /// The document pages.
+    pages: Vec<PdfPage>,
+}
+
+impl PdfDocument {
+    /// Append a page to the document.
+    ///
+    /// # Arguments
+    ///
+    /// * `page`: The page to add.
+    ///
+    /// returns: `()`
+    pub(crate) fn add_page(&mut self, page: PdfPage) {
+        self.pages.push(page);
+    }
+
+    /// Get the PDF document as bytes.
+    ///
+    /// returns: `Vec<u8>`
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut pdf = Pdf::new();
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        // each page uses three objects: the page, its content stream and the image
+        let page_ids: Vec<Ref> = (0..self.pages.len()).map(|i| Ref::new(3 + 3 * i as i32)).collect();
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().copied())
+            .count(self.pages.len() as i32);
+
+        let image_name = Name(b"Im0");
+        for (page, page_id) in self.pages.iter().zip(page_ids) {
+            let content_id = Ref::new(page_id.get() + 1);
+            let image_id = Ref::new(page_id.get() + 2);
+            let width = (page.width as f64 * 72.0 / DPI) as f32;
+            let height = (page.height as f64 * 72.0 / DPI) as f32;
+
+            let mut pdf_page = pdf.page(page_id);
+            pdf_page
+                .parent(page_tree_id)
+                .media_box(Rect::new(0.0, 0.0, width, height))
+                .contents(content_id);
+            pdf_page.resources().x_objects().pair(image_name, image_id);
+            drop(pdf_page);
+
+            // scale the image, which has a unit size, to the whole page
+            let mut content = Content::new();
+            content
+                .save_state()
+                .transform([width, 0.0, 0.0, height, 0.0, 0.0])
+                .x_object(image_name)
+                .restore_state();
+            pdf.stream(content_id, &content.finish());
+
+            let mut image = pdf.image_xobject(image_id, &page.data);
+            image.filter(Filter::FlateDecode);
+            image.width(page.width as i32);
+            image.height(page.height as i32);
+            image.color_space().device_rgb();
+            image.bits_per_component(8);
+        }
+
+        pdf.finish()
+    }
+
+    /// Save the document to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination`: The path of the PDF file.
+    ///
+    /// returns: `Result<(), io::Error>`
+    pub(crate) fn save(&self, destination: &Path) -> io::Result<()> {
+        let mut file = File::create(destination)?;
+        file.write_all(&self.to_bytes())?;
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pdf::{PdfDocument, PdfPage};
+
+    #[test]
+    fn test_pdf_document() {
+        let mut document = PdfDocument::default();
+        document.add_page(PdfPage::new(2, 1, &[255, 0, 0, 0, 0, 255]).unwrap());
+        document.add_page(PdfPage::new(1, 1, &[0, 255, 0]).unwrap());
+        let bytes = document.to_bytes();
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert!(bytes.ends_with(b"%%EOF"));
+
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("/Count 2"));
+        assert_eq!(text.matches("/Subtype /Image").count(), 2);
+
+        // the image size must match the number of pixels
+        assert!(PdfPage::new(2, 2, &[0; 3]).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::utils::{separate_baseflow, NaNVec};
+
+/// The parameter of the digital filter used to separate the baseflow from the run-off.
+pub const BASEFLOW_FILTER_PARAMETER: f64 = 0.925;
+
+/// The flow signatures of a run-off time series. These summarise the hydrological behaviour of a
+/// catchment and can be used to compare the simulated and observed run-off beyond the flow
+/// duration curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlowSignatures {
+    /// The baseflow index (0-1). This is the fraction of the total run-off coming from the
+    /// baseflow, which is separated using the one-parameter digital filter by Lyne and Hollick
+    /// (1979).
+    pub baseflow_index: f64,
+    /// The flow exceeded 5% of the time (high flow).
+    pub q5: f64,
+    /// The flow exceeded 50% of the time (median flow).
+    pub q50: f64,
+    /// The flow exceeded 95% of the time (low flow).
+    pub q95: f64,
+    /// The Richards-Baker flashiness index. This is the sum of the absolute day-to-day changes in
+    /// run-off divided by the total run-off.
+    pub flashiness_index: f64,
+    /// The fraction of the precipitation that becomes run-off (0-1).
+    pub runoff_ratio: f64,
+}
+
+impl FlowSignatures {
+    /// Calculate the flow signatures. The missing values are removed before calculating the
+    /// signatures; for the run-off ratio, the time steps where either the run-off or the
+    /// precipitation is missing are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_off`: The run-off time series (mm/day).
+    /// * `precipitation`: The total precipitation time series (mm/day). This must have the same
+    ///   length as the run-off.
+    ///
+    /// returns: FlowSignatures
+    pub fn new(run_off: &[f64], precipitation: &[f64]) -> FlowSignatures {
+        let run_off_vec = NaNVec(run_off);
+        let q = run_off_vec.remove_nans();
+        let total: f64 = q.iter().sum();
+
+        let baseflow_index = baseflow(&q).iter().sum::<f64>() / total;
+        let flashiness_index = q.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>() / total;
+
+        let runoff_ratio = match run_off_vec.remove_nans_from_pair(precipitation) {
+            Ok((q, p)) => q.iter().sum::<f64>() / p.iter().sum::<f64>(),
+            Err(_) => f64::NAN,
+        };
+
+        FlowSignatures {
+            baseflow_index,
+            q5: run_off_vec.quantile(0.95),
+            q50: run_off_vec.quantile(0.5),
+            q95: run_off_vec.quantile(0.05),
+            flashiness_index,
+            runoff_ratio,
+        }
+    }
+}
+
+/// Separate the baseflow from the run-off using the one-parameter digital filter by Lyne and
+/// Hollick (1979) with [`BASEFLOW_FILTER_PARAMETER`] and one pass. See [`separate_baseflow`].
+///
+/// # Arguments
+///
+/// * `run_off`: The run-off time series without missing values.
+///
+/// returns: `Vec<f64>`
+pub fn baseflow(run_off: &[f64]) -> Vec<f64> {
+    separate_baseflow(run_off, BASEFLOW_FILTER_PARAMETER, 1).0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signatures::{baseflow, FlowSignatures};
+    use float_cmp::{assert_approx_eq, F64Margin};
+
+    const MARGINS: F64Margin = F64Margin {
+        epsilon: 1e-12,
+        ulps: 2,
+    };
+
+    /// A constant baseflow of 1 with a one-day peak of 10 every 10 days. Half of the run-off is
+    /// baseflow.
+    fn spiky_run_off() -> Vec<f64> {
+        (0..1000).map(|i| if i % 10 == 5 { 11.0 } else { 1.0 }).collect()
+    }
+
+    #[test]
+    fn test_constant_run_off() {
+        let run_off = [2.0; 100];
+        let signatures = FlowSignatures::new(&run_off, &[4.0; 100]);
+
+        assert_approx_eq!(f64, signatures.baseflow_index, 1.0, MARGINS);
+        assert_approx_eq!(f64, signatures.flashiness_index, 0.0, MARGINS);
+        assert_approx_eq!(f64, signatures.q5, 2.0, MARGINS);
+        assert_approx_eq!(f64, signatures.q95, 2.0, MARGINS);
+        assert_approx_eq!(f64, signatures.runoff_ratio, 0.5, MARGINS);
+    }
+
+    #[test]
+    fn test_baseflow() {
+        let run_off = spiky_run_off();
+        let baseflow = baseflow(&run_off);
+
+        // the filter only attributes part of the peak to the baseflow
+        assert_approx_eq!(f64, baseflow[5], 1.375, MARGINS);
+        assert_approx_eq!(f64, baseflow[6], 1.0, MARGINS);
+        assert!(baseflow.iter().zip(&run_off).all(|(b, q)| *b >= 0.0 && b <= q));
+    }
+
+    #[test]
+    fn test_signatures() {
+        let mut run_off = spiky_run_off();
+        let precipitation = vec![4.0; run_off.len()];
+        let signatures = FlowSignatures::new(&run_off, &precipitation);
+
+        assert!((signatures.baseflow_index - 0.5).abs() < 0.025);
+        assert_approx_eq!(f64, signatures.baseflow_index, 0.51875, MARGINS);
+        assert_approx_eq!(f64, signatures.q5, 11.0, MARGINS);
+        assert_approx_eq!(f64, signatures.q50, 1.0, MARGINS);
+        assert_approx_eq!(f64, signatures.q95, 1.0, MARGINS);
+        assert_approx_eq!(f64, signatures.flashiness_index, 1.0, MARGINS);
+        assert_approx_eq!(f64, signatures.runoff_ratio, 0.5, MARGINS);
+
+        // missing values are ignored
+        run_off.push(f64::NAN);
+        let with_nan = FlowSignatures::new(&run_off, &[precipitation, vec![4.0]].concat());
+        assert_eq!(with_nan, signatures);
+    }
+}
@@ -1,8 +1,31 @@
+use chrono::{Datelike, NaiveDate};
 use csv::Writer;
 use float_cmp::{approx_eq, F64Margin};
 use ndarray::Array;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// The default number of decimal places of the numbers exported to the CSV files.
+pub const CSV_PRECISION: usize = 6;
+
+/// Format a number with a fixed number of decimal places for the CSV files. Unlike
+/// `f64::to_string`, this gives the same representation on all platforms and does not export
+/// negative zeros.
+///
+/// # Arguments
+///
+/// * `value`: The number to format.
+/// * `precision`: The number of decimal places.
+///
+/// returns: String
+pub fn format_float(value: f64, precision: usize) -> String {
+    let formatted = format!("{:.*}", precision, value);
+    match formatted.strip_prefix('-') {
+        Some(abs_value) if abs_value.chars().all(|c| c == '0' || c == '.') => abs_value.to_string(),
+        _ => formatted,
+    }
+}
+
 /// Get the series max value
 pub(crate) fn series_max(series: &[f64]) -> f64 {
     *series
@@ -35,6 +58,169 @@ pub(crate) fn vector_nan_indices(data: &[f64]) -> Vec<String> {
         .collect::<Vec<_>>();
 }
 
+/// Check if a vector contains negative numbers and returns their indices. NaNs are ignored.
+///
+/// # Arguments
+///
+/// * `data`: The vector to check.
+///
+/// returns: Vec<String>
+pub(crate) fn vector_negative_indices(data: &[f64]) -> Vec<String> {
+    data.iter()
+        .enumerate()
+        .filter(|(_, &r)| r < 0.0)
+        .map(|(index, _)| index.to_string())
+        .collect()
+}
+
+/// Calculate the mean of a series for each day of the year and map it to the given dates. NaNs
+/// are ignored. The 29th of February falls back to the 28th when the series does not contain
+/// any leap day.
+///
+/// # Arguments
+///
+/// * `time`: The time vector of the series.
+/// * `series`: The series to average.
+/// * `dates`: The dates to get the day-of-year mean for.
+///
+/// returns: Vec<f64>
+pub(crate) fn day_of_year_mean(time: &[NaiveDate], series: &[f64], dates: &[NaiveDate]) -> Vec<f64> {
+    let mut totals: HashMap<(u32, u32), (f64, usize)> = HashMap::new();
+    for (t, value) in time.iter().zip(series) {
+        if value.is_nan() {
+            continue;
+        }
+        let total = totals.entry((t.month(), t.day())).or_insert((0.0, 0));
+        total.0 += value;
+        total.1 += 1;
+    }
+
+    dates
+        .iter()
+        .map(|d| {
+            let key = (d.month(), d.day());
+            let total = match totals.get(&key) {
+                None if key == (2, 29) => totals.get(&(2, 28)),
+                total => total,
+            };
+            match total {
+                Some((sum, count)) => sum / *count as f64,
+                None => f64::NAN,
+            }
+        })
+        .collect()
+}
+
+/// Smooth a series using a centred moving average. NaNs in the window are skipped and the mean is
+/// NaN when the window has no valid values. At the start and end of the series, the window only
+/// contains the available values. For an even window, the window contains one more value after
+/// the current one than before it.
+///
+/// # Arguments
+///
+/// * `series`: The series to smooth.
+/// * `window`: The number of values in the window (at least 1).
+///
+/// # Panics
+///
+/// This panics if `window` is zero.
+///
+/// returns: `Vec<f64>`
+pub fn rolling_mean(series: &[f64], window: usize) -> Vec<f64> {
+    assert!(window >= 1, "The window must contain at least one value");
+
+    let before = (window - 1) / 2;
+    let after = window / 2;
+    (0..series.len())
+        .map(|i| {
+            let start = i.saturating_sub(before);
+            let end = (i + after + 1).min(series.len());
+            NaNVec(&series[start..end]).mean()
+        })
+        .collect()
+}
+
+/// Separate the run-off into baseflow and quick flow using the Lyne and Hollick (1979) recursive
+/// digital filter. When more than one pass is requested, the filter is applied again to the
+/// baseflow of the previous pass, alternating the forward and backward direction. The quick flow
+/// is constrained so that the baseflow is never negative or larger than the run-off. Missing
+/// values are returned as NaN and are skipped by the filter.
+///
+/// # Arguments
+///
+/// * `run_off`: The run-off time series.
+/// * `alpha`: The filter parameter (0-1, exclusive). Values around 0.925 are common for daily
+///   data.
+/// * `passes`: The number of times the filter is applied (at least 1).
+///
+/// # Panics
+///
+/// This panics if `alpha` is not between 0 and 1 or `passes` is zero.
+///
+/// returns: (`Vec<f64>`, `Vec<f64>`) with the baseflow and the quick flow.
+pub fn separate_baseflow(run_off: &[f64], alpha: f64, passes: usize) -> (Vec<f64>, Vec<f64>) {
+    assert!(
+        alpha > 0.0 && alpha < 1.0,
+        "The filter parameter must be between 0 and 1"
+    );
+    assert!(passes >= 1, "The filter must be applied at least once");
+
+    let mut baseflow = run_off.to_vec();
+    for pass in 0..passes {
+        let mut indices: Vec<usize> = (0..baseflow.len()).filter(|i| !baseflow[*i].is_nan()).collect();
+        if pass % 2 == 1 {
+            indices.reverse();
+        }
+
+        let mut quick_flow = 0.0;
+        let mut previous: Option<f64> = None;
+        for i in indices {
+            let q = baseflow[i];
+            if let Some(previous) = previous {
+                quick_flow = alpha * quick_flow + (1.0 + alpha) / 2.0 * (q - previous);
+            }
+            quick_flow = quick_flow.clamp(0.0, q.max(0.0));
+            previous = Some(q);
+            baseflow[i] = q - quick_flow;
+        }
+    }
+
+    let quick_flow = run_off.iter().zip(&baseflow).map(|(q, b)| q - b).collect();
+    (baseflow, quick_flow)
+}
+
+/// The formula used to calculate the probability of exceedence of the i-th largest run-off value
+/// out of n values in the flow duration curve.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PlottingPosition {
+    /// The rank divided by the number of values (i / n).
+    #[default]
+    RankOverN,
+    /// The Weibull plotting position (i / (n + 1)). This is unbiased and never returns 100%.
+    Weibull,
+    /// The Cunnane plotting position ((i - 0.4) / (n + 0.2)).
+    Cunnane,
+}
+
+impl PlottingPosition {
+    /// Get the probability of exceedence (0-100) of the run-off value with a rank.
+    ///
+    /// # Arguments
+    ///
+    /// * `rank`: The rank of the run-off value (1 for the largest value).
+    /// * `total`: The number of values.
+    ///
+    /// returns: f64
+    pub fn exceedence(&self, rank: f64, total: f64) -> f64 {
+        let probability = match self {
+            PlottingPosition::RankOverN => rank / total,
+            PlottingPosition::Weibull => rank / (total + 1.0),
+            PlottingPosition::Cunnane => (rank - 0.4) / (total + 0.2),
+        };
+        probability * 100.0
+    }
+}
+
 /// Calculate the flow duration curve
 #[derive(Clone)]
 pub struct Fdc {
@@ -45,7 +231,8 @@ pub struct Fdc {
 }
 
 impl Fdc {
-    /// Calculate the flow duration curve.
+    /// Calculate the flow duration curve using the [`PlottingPosition::RankOverN`] plotting
+    /// position. This is the curve used in the charts.
     ///
     /// # Arguments
     ///
@@ -53,7 +240,22 @@ impl Fdc {
     ///
     /// returns: Fdc
     pub fn new(run_off: &[f64]) -> Self {
-        let exceedence = Array::range(1., run_off.len() as f64 + 1.0, 1.0) / run_off.len() as f64 * 100.0;
+        Self::with_plotting_position(run_off, PlottingPosition::RankOverN)
+    }
+
+    /// Calculate the flow duration curve with a plotting position. This only changes the
+    /// probability of exceedence; the sorted run-off, which the non-parametric Kling-Gupta
+    /// efficiency is calculated from, does not depend on the plotting position.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_off`: The run-off time series.
+    /// * `position`: The formula to calculate the probability of exceedence.
+    ///
+    /// returns: Fdc
+    pub fn with_plotting_position(run_off: &[f64], position: PlottingPosition) -> Self {
+        let total = run_off.len() as f64;
+        let exceedence = Array::range(1., total + 1.0, 1.0).mapv(|rank| position.exceedence(rank, total));
         let sorted_run_off = NaNVec(run_off).sort(SortType::Asc);
 
         Self {
@@ -62,20 +264,66 @@ impl Fdc {
         }
     }
 
+    /// Whether the run-off contains zero or negative values. These cannot be shown on a log scale.
+    ///
+    /// returns: bool
+    pub fn has_non_positive_values(&self) -> bool {
+        self.sorted_run_off.iter().any(|q| *q <= 0.0)
+    }
+
+    /// Get the probability (0-100) that the run-off is equal to or larger than a flow. The
+    /// probability is linearly interpolated between the points of the curve. This is 0 for flows
+    /// larger than the maximum run-off and 100 for flows smaller than the minimum run-off. Missing
+    /// values are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `q`: The flow.
+    ///
+    /// returns: f64
+    pub fn exceedance_of_flow(&self, q: f64) -> f64 {
+        let points: Vec<(f64, f64)> = self
+            .exceedence
+            .iter()
+            .zip(&self.sorted_run_off)
+            .filter(|(_, flow)| !flow.is_nan())
+            .map(|(pct, flow)| (*pct, *flow))
+            .collect();
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return f64::NAN;
+        };
+        if q > first.1 {
+            return 0.0;
+        }
+        if q < last.1 {
+            return 100.0;
+        }
+
+        // the run-off is sorted in descending order
+        for w in points.windows(2) {
+            let ((p0, q0), (p1, q1)) = (w[0], w[1]);
+            if q > q1 {
+                return p0 + (q0 - q) / (q0 - q1) * (p1 - p0);
+            }
+        }
+        last.0
+    }
+
     /// Export theflow duration curve and to a CSV file.
     ///
     /// # Arguments
     ///
     /// * `destination`: The destination CSV file.
     /// * `run_off_unit`: The run-off unit of measurement.
+    /// * `precision`: The number of decimal places of the exported numbers.
     ///
     /// returns: Result<(), csv::Error>
-    pub fn to_csv(&self, destination: &PathBuf, run_off_unit: &str) -> Result<(), csv::Error> {
+    pub fn to_csv(&self, destination: &PathBuf, run_off_unit: &str, precision: usize) -> Result<(), csv::Error> {
         let mut wtr = Writer::from_path(destination)?;
         wtr.write_record(["Percentage exceedance", format!("Run-off ({})", run_off_unit).as_str()])?;
 
         for (pct, q) in self.exceedence.iter().zip(&self.sorted_run_off) {
-            wtr.write_record([pct.to_string(), q.to_string()])?;
+            wtr.write_record([format_float(*pct, precision), format_float(*q, precision)])?;
             wtr.flush()?;
         }
         Ok(())
@@ -140,6 +388,48 @@ impl NaNVec<'_> {
         (delta_sum / total).powf(0.5)
     }
 
+    /// Calculate a percentile of a vector using the linear interpolation between the closest
+    /// ranks. NaN values are excluded.
+    ///
+    /// # Arguments
+    ///
+    /// * `percentile`: The percentile to calculate (0-100).
+    ///
+    /// returns: f64
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        let mut sorted = self.remove_nans();
+        if sorted.is_empty() {
+            return f64::NAN;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let position = percentile / 100.0 * (sorted.len() - 1) as f64;
+        let lower = position.floor() as usize;
+        let upper = position.ceil() as usize;
+        sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+    }
+
+    /// Calculate a quantile of a vector using the linear interpolation between the order
+    /// statistics. NaN values are excluded and NaN is returned when the vector has no values. For
+    /// example use 0.95 to get the Q5 low-flow index of a run-off series.
+    ///
+    /// # Arguments
+    ///
+    /// * `p`: The probability of the quantile (0-1).
+    ///
+    /// # Panics
+    ///
+    /// This panics if `p` is not between 0 and 1.
+    ///
+    /// returns: f64
+    pub fn quantile(&self, p: f64) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&p),
+            "The quantile probability must be between 0 and 1"
+        );
+        self.percentile(p * 100.0)
+    }
+
     /// Removed NaNs from a vector.
     ///
     /// # Arguments
@@ -268,10 +558,11 @@ pub(crate) fn assert_approx_array_eq(calculated_values: &[f64], expected_values:
 }
 
 pub mod example {
-    use chrono::NaiveDate;
+    use crate::io::{load_forcing_csv, ForcingCsvConfig};
+    use chrono::{Datelike, NaiveDate};
     use std::error::Error;
-    use std::fs::File;
-    use std::path::PathBuf;
+    use std::f64::consts::PI;
+    use std::path::{Path, PathBuf};
 
     pub struct HydrologicalData {
         /// Vector of time.
@@ -284,33 +575,105 @@ pub mod example {
         pub observed_runoff: Vec<f64>,
     }
 
-    pub fn load_data() -> Result<HydrologicalData, Box<dyn Error>> {
-        let mut data_folder = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        data_folder.push(r"src\test_data\airGR_L0123001_dataset.csv");
+    /// A small pseudo-random number generator (SplitMix64). This is not suitable for
+    /// cryptography but gives the same sequence on all platforms for a given seed.
+    struct Rng(u64);
+
+    impl Rng {
+        /// Get a random number in the (0, 1) interval.
+        fn uniform(&mut self) -> f64 {
+            self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^= z >> 31;
+            ((z >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+        }
 
-        // Collect hydrological data
-        let file = File::open(r"gr6j-core\src\test_data\airGR_L0123001_dataset.csv")?;
-        let mut rdr = csv::Reader::from_reader(file);
-
-        let mut time: Vec<NaiveDate> = vec![];
-        let mut precipitation: Vec<f64> = vec![];
-        let mut evapotranspiration: Vec<f64> = vec![];
-        let mut observed_runoff: Vec<f64> = vec![];
-        for result in rdr.records() {
-            let record = result.unwrap();
-            let t = NaiveDate::parse_from_str(record.get(0).unwrap(), "%d/%m/%Y")?;
-            time.push(t);
-            precipitation.push(record.get(1).unwrap().parse::<f64>()?);
-            evapotranspiration.push(record.get(2).unwrap().parse::<f64>()?);
-            let obs = record.get(3).unwrap();
-            let obs = if obs == "NA" { "0.0" } else { obs };
-            observed_runoff.push(obs.parse::<f64>()?);
+        /// Get a random number from the standard normal distribution (Box-Muller transform).
+        fn normal(&mut self) -> f64 {
+            (-2.0 * self.uniform().ln()).sqrt() * (2.0 * PI * self.uniform()).cos()
         }
 
-        Ok(HydrologicalData {
+        /// Get a random number from the gamma distribution using the Marsaglia and Tsang method.
+        fn gamma(&mut self, shape: f64, scale: f64) -> f64 {
+            if shape < 1.0 {
+                let u = self.uniform();
+                return self.gamma(shape + 1.0, scale) * u.powf(1.0 / shape);
+            }
+            let d = shape - 1.0 / 3.0;
+            let c = 1.0 / (9.0 * d).sqrt();
+            loop {
+                let x = self.normal();
+                let v = (1.0 + c * x).powi(3);
+                if v > 0.0 && self.uniform().ln() < 0.5 * x.powi(2) + d - d * v + d * v.ln() {
+                    return d * v * scale;
+                }
+            }
+        }
+    }
+
+    /// Generate a synthetic daily forcing dataset starting on the 1st January 2000. This is meant
+    /// for reproducible tests and does not require any data file.
+    ///
+    /// The wet days are generated with a two-state Markov chain (rain is more likely after a wet
+    /// day and in winter) and the rainfall depth of a wet day is gamma-distributed with a mean of
+    /// 6.4 mm. The potential evapotranspiration follows a seasonal cycle between 0.5 mm/day in
+    /// January and 4.5 mm/day in July. The observed run-off is not available and is set to NaN.
+    ///
+    /// # Arguments
+    ///
+    /// * `years`: The number of calendar years to generate.
+    /// * `seed`: The seed of the random number generator. The same seed always gives the same data.
+    ///
+    /// returns: HydrologicalData
+    pub fn synthetic_forcing(years: usize, seed: u64) -> HydrologicalData {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2000 + years as i32, 1, 1).unwrap();
+        let time: Vec<NaiveDate> = start.iter_days().take_while(|t| *t < end).collect();
+
+        let mut rng = Rng(seed);
+        let mut is_wet = false;
+        let mut precipitation = Vec::with_capacity(time.len());
+        let mut evapotranspiration = Vec::with_capacity(time.len());
+        for t in time.iter() {
+            // 1 in winter and -1 in summer
+            let season = (2.0 * PI * t.ordinal0() as f64 / 365.25).cos();
+
+            let wet_probability = if is_wet { 0.6 } else { 0.25 } + 0.1 * season;
+            is_wet = rng.uniform() < wet_probability;
+            precipitation.push(if is_wet { rng.gamma(0.8, 8.0) } else { 0.0 });
+            evapotranspiration.push(2.5 - 2.0 * season);
+        }
+
+        HydrologicalData {
+            observed_runoff: vec![f64::NAN; time.len()],
             time,
             precipitation,
             evapotranspiration,
+        }
+    }
+
+    pub fn load_data() -> Result<HydrologicalData, Box<dyn Error>> {
+        let mut data_folder = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        data_folder.push(r"src\test_data\airGR_L0123001_dataset.csv");
+
+        // Collect hydrological data. The missing observed run-off is replaced with 0
+        let forcing = load_forcing_csv(
+            Path::new(r"gr6j-core\src\test_data\airGR_L0123001_dataset.csv"),
+            ForcingCsvConfig::airgr(),
+        )?;
+        let observed_runoff = forcing
+            .observed_runoff
+            .unwrap_or_default()
+            .iter()
+            .map(|q| if q.is_nan() { 0.0 } else { *q })
+            .collect();
+
+        Ok(HydrologicalData {
+            time: forcing.time,
+            precipitation: forcing.precipitation,
+            evapotranspiration: forcing.evapotranspiration,
             observed_runoff,
         })
     }
@@ -318,13 +681,201 @@ pub mod example {
 
 #[cfg(test)]
 mod test {
-    use crate::utils::{assert_approx_array_eq, NaNVec};
+    use crate::utils::example::synthetic_forcing;
+    use crate::utils::{
+        assert_approx_array_eq, day_of_year_mean, format_float, rolling_mean, separate_baseflow, Fdc, NaNVec,
+        PlottingPosition,
+    };
+    use chrono::NaiveDate;
     use float_cmp::{assert_approx_eq, F64Margin};
 
     const X: [f64; 10] = [106.0, 100.0, 86.0, 101.0, 99.0, 103.0, 97.0, 113.0, 112.0, 110.0];
     const Y: [f64; 10] = [7.0, 27.0, 2.0, 50.0, 28.0, 29.0, 20.0, 12.0, 6.0, 17.0];
     const MARGINS: F64Margin = F64Margin { epsilon: 0.0, ulps: 2 };
 
+    #[test]
+    fn test_synthetic_forcing() {
+        let data = synthetic_forcing(2, 42);
+        assert_eq!(data.time.len(), 731);
+        assert_eq!(data.precipitation.len(), 731);
+        assert_eq!(data.evapotranspiration.len(), 731);
+        assert_eq!(
+            *data.time.last().unwrap(),
+            NaiveDate::from_ymd_opt(2001, 12, 31).unwrap()
+        );
+
+        // plausible values
+        assert!(data.precipitation.iter().all(|p| *p >= 0.0 && p.is_finite()));
+        let wet_days = data.precipitation.iter().filter(|p| **p > 0.0).count();
+        assert!(wet_days > 100 && wet_days < 500);
+        assert!(data.evapotranspiration.iter().all(|e| *e >= 0.5 && *e <= 4.5));
+        assert!(data.evapotranspiration[0] < data.evapotranspiration[190]);
+
+        // the data only depends on the seed
+        assert_eq!(synthetic_forcing(2, 42).precipitation, data.precipitation);
+        assert_ne!(synthetic_forcing(2, 43).precipitation, data.precipitation);
+    }
+
+    #[test]
+    fn test_rolling_mean() {
+        // a window of one returns the series
+        let series = [1.0, f64::NAN, 3.0, 5.0];
+        let smoothed = rolling_mean(&series, 1);
+        assert_eq!(smoothed[0], 1.0);
+        assert!(smoothed[1].is_nan());
+        assert_eq!(smoothed[2..], [3.0, 5.0]);
+
+        // the window spans the NaN
+        assert_eq!(rolling_mean(&series, 3), vec![1.0, 2.0, 4.0, 4.0]);
+        assert_eq!(rolling_mean(&series, 4), vec![2.0, 3.0, 4.0, 4.0]);
+
+        // windows without valid values
+        let smoothed = rolling_mean(&[f64::NAN, f64::NAN, f64::NAN, f64::NAN, 1.0], 3);
+        assert!(smoothed[..3].iter().all(|v| v.is_nan()));
+        assert_eq!(smoothed[3..], [1.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rolling_mean_zero_window() {
+        rolling_mean(&[1.0, 2.0], 0);
+    }
+
+    #[test]
+    fn test_separate_baseflow() {
+        let run_off: Vec<f64> = (0..365)
+            .map(|i| 1.0 + 10.0 * (-((i % 30) as f64) / 3.0).exp())
+            .collect();
+
+        for passes in 1..=3 {
+            let (baseflow, quick_flow) = separate_baseflow(&run_off, 0.925, passes);
+            assert_eq!(baseflow.len(), run_off.len());
+            for ((b, qf), q) in baseflow.iter().zip(&quick_flow).zip(&run_off) {
+                assert!(*b >= 0.0 && b <= q);
+                assert!(*qf >= 0.0);
+                assert_approx_eq!(f64, b + qf, *q, epsilon = 1e-12);
+            }
+        }
+
+        // more passes give a smoother and smaller baseflow
+        let one_pass: f64 = separate_baseflow(&run_off, 0.925, 1).0.iter().sum();
+        let three_passes: f64 = separate_baseflow(&run_off, 0.925, 3).0.iter().sum();
+        assert!(three_passes < one_pass);
+
+        // missing values
+        let (baseflow, quick_flow) = separate_baseflow(&[1.0, f64::NAN, 3.0, 1.0], 0.925, 2);
+        assert!(baseflow[1].is_nan() && quick_flow[1].is_nan());
+        assert!(baseflow.iter().filter(|b| !b.is_nan()).all(|b| *b >= 0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_separate_baseflow_invalid_alpha() {
+        separate_baseflow(&[1.0, 2.0], 1.0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_separate_baseflow_no_passes() {
+        separate_baseflow(&[1.0, 2.0], 0.925, 0);
+    }
+
+    #[test]
+    fn test_plotting_position() {
+        let run_off = [3.0, 1.0, 4.0, 2.0];
+        let expected = [
+            (PlottingPosition::RankOverN, 25.0, 100.0),
+            (PlottingPosition::Weibull, 20.0, 80.0),
+            (PlottingPosition::Cunnane, 0.6 / 4.2 * 100.0, 3.6 / 4.2 * 100.0),
+        ];
+        for (position, first, last) in expected {
+            let fdc = Fdc::with_plotting_position(&run_off, position);
+            assert_eq!(fdc.exceedence.len(), run_off.len());
+            assert_approx_eq!(f64, fdc.exceedence[0], first, MARGINS);
+            assert_approx_eq!(f64, *fdc.exceedence.last().unwrap(), last, MARGINS);
+            assert_eq!(fdc.sorted_run_off, vec![4.0, 3.0, 2.0, 1.0]);
+        }
+        assert_eq!(Fdc::new(&run_off).exceedence, vec![25.0, 50.0, 75.0, 100.0]);
+    }
+
+    #[test]
+    fn test_exceedance_of_flow() {
+        let fdc = Fdc::new(&[3.0, 1.0, 4.0, 2.0]);
+        assert_eq!(fdc.exceedance_of_flow(5.0), 0.0);
+        assert_eq!(fdc.exceedance_of_flow(0.5), 100.0);
+        assert_eq!(fdc.exceedance_of_flow(4.0), 25.0);
+        assert_eq!(fdc.exceedance_of_flow(3.5), 37.5);
+        assert_eq!(fdc.exceedance_of_flow(2.5), 62.5);
+        assert_eq!(fdc.exceedance_of_flow(1.0), 100.0);
+
+        // repeated flows
+        let fdc = Fdc::new(&[2.0, 1.0, 2.0, 1.0]);
+        assert_eq!(fdc.exceedance_of_flow(2.0), 50.0);
+        assert!(Fdc::new(&[f64::NAN]).exceedance_of_flow(1.0).is_nan());
+    }
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(0.1 + 0.2, 6), "0.300000");
+        assert_eq!(format_float(1234.56789, 2), "1234.57");
+        assert_eq!(format_float(-2.5, 3), "-2.500");
+        assert_eq!(format_float(-0.0000001, 6), "0.000000");
+        assert_eq!(format_float(-0.0, 0), "0");
+        assert_eq!(format_float(f64::NAN, 6), "NaN");
+    }
+
+    #[test]
+    fn test_fdc_non_positive_values() {
+        assert!(!Fdc::new(&[1.0, 0.5, f64::NAN]).has_non_positive_values());
+        assert!(Fdc::new(&[1.0, 0.0, 2.0]).has_non_positive_values());
+    }
+
+    #[test]
+    fn test_day_of_year_mean() {
+        let time = [
+            NaiveDate::from_ymd_opt(2001, 2, 28).unwrap(),
+            NaiveDate::from_ymd_opt(2001, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2002, 2, 28).unwrap(),
+            NaiveDate::from_ymd_opt(2002, 3, 1).unwrap(),
+        ];
+        let series = [2.0, 1.0, 4.0, f64::NAN];
+        let dates = [
+            NaiveDate::from_ymd_opt(2004, 2, 28).unwrap(),
+            NaiveDate::from_ymd_opt(2004, 2, 29).unwrap(),
+            NaiveDate::from_ymd_opt(2004, 3, 1).unwrap(),
+        ];
+        assert_eq!(day_of_year_mean(&time, &series, &dates), vec![3.0, 3.0, 1.0]);
+
+        let missing = day_of_year_mean(&time, &series, &[NaiveDate::from_ymd_opt(2004, 3, 2).unwrap()]);
+        assert!(missing[0].is_nan());
+    }
+
+    #[test]
+    fn test_quantile() {
+        let vec = NaNVec(&X);
+        assert_approx_eq!(f64, vec.quantile(0.0), 86.0, MARGINS);
+        assert_approx_eq!(f64, vec.quantile(0.05), 90.95, MARGINS);
+        assert_approx_eq!(f64, vec.quantile(0.5), 102.0, MARGINS);
+        assert_approx_eq!(f64, vec.quantile(0.95), 112.55, MARGINS);
+        assert_approx_eq!(f64, vec.quantile(1.0), 113.0, MARGINS);
+    }
+
+    #[test]
+    fn test_quantile_with_nans() {
+        let vec = [3.0, f64::NAN, 1.0, 2.0, f64::NAN];
+        assert_approx_eq!(f64, NaNVec(&vec).quantile(0.25), 1.5, MARGINS);
+        assert_approx_eq!(f64, NaNVec(&vec).quantile(0.5), 2.0, MARGINS);
+
+        assert!(NaNVec(&[f64::NAN]).quantile(0.5).is_nan());
+        assert!(NaNVec(&[]).quantile(0.5).is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_quantile_invalid_probability() {
+        NaNVec(&X).quantile(1.5);
+    }
+
     #[test]
     fn test_rank_1() {
         let expected: Vec<f64> = vec![7.0, 4.0, 1.0, 5.0, 3.0, 6.0, 2.0, 10.0, 9.0, 8.0];
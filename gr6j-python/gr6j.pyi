@@ -1,6 +1,8 @@
 from datetime import date
 from enum import Enum
+from pathlib import Path
 
+import numpy as np
 import pandas as pd
 
 
@@ -24,17 +26,23 @@ class X1:
         """
 
     @staticmethod
-    def min() -> str:
+    def min() -> float:
         """
         The parameter minimum value.
         """
 
     @staticmethod
-    def max() -> str:
+    def max() -> float:
         """
         The parameter maximum value.
         """
 
+    @staticmethod
+    def bounds() -> tuple[float, float]:
+        """
+        The parameter minimum and maximum values.
+        """
+
 
 class X2:
     def __init__(self, value: float):
@@ -56,17 +64,23 @@ class X2:
         """
 
     @staticmethod
-    def min() -> str:
+    def min() -> float:
         """
         The parameter minimum value.
         """
 
     @staticmethod
-    def max() -> str:
+    def max() -> float:
         """
         The parameter maximum value.
         """
 
+    @staticmethod
+    def bounds() -> tuple[float, float]:
+        """
+        The parameter minimum and maximum values.
+        """
+
 
 class X3:
     def __init__(self, value: float):
@@ -88,17 +102,23 @@ class X3:
         """
 
     @staticmethod
-    def min() -> str:
+    def min() -> float:
         """
         The parameter minimum value.
         """
 
     @staticmethod
-    def max() -> str:
+    def max() -> float:
         """
         The parameter maximum value.
         """
 
+    @staticmethod
+    def bounds() -> tuple[float, float]:
+        """
+        The parameter minimum and maximum values.
+        """
+
 
 class X4:
     def __init__(self, value: float):
@@ -120,17 +140,23 @@ class X4:
         """
 
     @staticmethod
-    def min() -> str:
+    def min() -> float:
         """
         The parameter minimum value.
         """
 
     @staticmethod
-    def max() -> str:
+    def max() -> float:
         """
         The parameter maximum value.
         """
 
+    @staticmethod
+    def bounds() -> tuple[float, float]:
+        """
+        The parameter minimum and maximum values.
+        """
+
 
 class X5:
     def __init__(self, value: float):
@@ -152,17 +178,23 @@ class X5:
         """
 
     @staticmethod
-    def min() -> str:
+    def min() -> float:
         """
         The parameter minimum value.
         """
 
     @staticmethod
-    def max() -> str:
+    def max() -> float:
         """
         The parameter maximum value.
         """
 
+    @staticmethod
+    def bounds() -> tuple[float, float]:
+        """
+        The parameter minimum and maximum values.
+        """
+
 
 class X6:
     def __init__(self, value: float):
@@ -184,17 +216,23 @@ class X6:
         """
 
     @staticmethod
-    def min() -> str:
+    def min() -> float:
         """
         The parameter minimum value.
         """
 
     @staticmethod
-    def max() -> str:
+    def max() -> float:
         """
         The parameter maximum value.
         """
 
+    @staticmethod
+    def bounds() -> tuple[float, float]:
+        """
+        The parameter minimum and maximum values.
+        """
+
 
 class StoreLevels:
     """
@@ -212,8 +250,8 @@ class StoreLevels:
         ```
             levels = StoreLevels(production_store=0.4, routing_store=0.4, exponential_store=0.1)
         ```
-        :param production_store: The production store level (mm)
-        :param routing_store: The routing store level (mm)
+        :param production_store: The production store level as fraction of X1 (0-1)
+        :param routing_store: The routing store level as fraction of X3 (0-1)
         :param exponential_store: The exponential store level (mm)
         """
 
@@ -305,12 +343,14 @@ class RunOffUnit(Enum):
      - CUBIC_METRE_PER_DAY: convert the run-off to m³/d
      - ML_PER_DAY: convert the run-off to Ml/d
      - CUBIC_METRE_PER_SECOND: convert the run-off to m³/s
+     - LITRE_PER_SECOND: convert the run-off to L/s
     """
 
     NO_CONVERSION = None
     CUBIC_METRE_PER_DAY = None
     ML_PER_DAY = None
     CUBIC_METRE_PER_SECOND = None
+    LITRE_PER_SECOND = None
 
 
 class GR6JModelInputs:
@@ -376,17 +416,18 @@ class GR6JModelInputs:
     def __init__(
             self,
             time: list[date],
-            precipitation: list[float],
-            evapotranspiration: list[float],
+            precipitation: list[float] | np.ndarray,
+            evapotranspiration: list[float] | np.ndarray,
             catchment: list[CatchmentData] | CatchmentData,
             run_period: ModelPeriod,
             warmup_period: ModelPeriod | None = None,
             destination: str | None = None,
-            observed_runoff: list[float] | None = None,
+            observed_runoff: list[float] | np.ndarray | None = None,
             run_off_unit: RunOffUnit | None = None,
     ):
         """
-        Initialise the inputs to the GR6J model.
+        Initialise the inputs to the GR6J model. The forcing and the observed run-off
+        can be given as lists or as one-dimensional numpy arrays of floats.
         :param time: The time vector as a list of `date` objects.
         :param precipitation: List of total precipitation values (mm/day)
         :param evapotranspiration: List of potential evapotranspiration (PE) values
@@ -400,7 +441,9 @@ class GR6JModelInputs:
         on a shorter time period compared to `time`.
         :param warmup_period: The start and end date of the warm-up period. If `None`
         and `run_period.start` allows, the one-year period preceding the
-        `run_period.start` is used. Default to None.
+        `run_period.start` is used. The warm-up period must end before the run
+        period; when there is a gap, the warm-up is extended to the day before the
+        run start date. Default to None.
         :param destination: Whether to export charts, the simulated run-off and other
         diagnostic file into a sub-folder inside the given destination folder. The
         sub-folder will be named with the run timestamp. Default to None.
@@ -439,12 +482,31 @@ class CalibrationMetric:
     """  The 2012 Kling-Gupta efficiency metric. An efficiency of 1 gives a perfect match
      of simulated to observed data. To calculate the alpha component the ratio of the 
      standard deviation and the mean is used. """
-    non_paramettric_kling_gupta: Metric
+    non_parametric_kling_gupta: Metric
     """  The non-parametric Kling-Gupta efficiency metric. An efficiency of 1 gives a 
      perfect match of simulated to observed data. This differs from kling_gupta2012 and
      kling_gupta2012 because the alpha component is calculated using the flow percentile
      from the flow duration curve instead of using the standard deviation.
      See <https://www.tandfonline.com/doi/full/10.1080/02626667.2018.1552002> """
+    error_sign_fractions: tuple[float, float, float]
+    """ The fraction of days (excluding days with missing values) when the simulated
+     run-off is larger than, equal to or smaller than the observed run-off. """
+    anomaly_nash_sutcliffe: Metric
+    """ The Nash-Sutcliffe efficiency calculated on the flow anomalies, after subtracting
+     the day-of-year mean of the observed flow from both series. """
+    pearson: Metric
+    """ The Pearson correlation coefficient between the observed and simulated flow. """
+    r_squared: Metric
+    """ The coefficient of determination (the square of the Pearson correlation
+     coefficient). """
+    percent_bias: Metric
+    """ The percent bias (PBIAS) as `100 * sum(sim - obs) / sum(obs)`. A positive value
+     indicates that the model overestimates the flow. """
+
+    @property
+    def non_paramettric_kling_gupta(self) -> Metric:
+        """ Deprecated alias of `non_parametric_kling_gupta`. This will be removed in
+         the next release. """
 
 
 class ModelStepData:
@@ -516,6 +578,32 @@ class GR6JModelOutputs:
         :return: The DataFrame with the run-off.
         """
 
+    def fdc(self) -> tuple[list[float], list[float]]:
+        """
+        Calculate the flow duration curve of the simulated run-off.
+        :return: A tuple with the probability of exceedence (0-100) and the sorted
+        run-off.
+        """
+
+    def write_metrics_csv(self, path: str | Path) -> None:
+        """
+        Export the calibration metrics to a CSV file with the metric name, its value
+        and its ideal value. This is the same file exported by `run` when the
+        `destination` is set.
+        :param path: The destination CSV file.
+        :raises ValueError: When the model has no observed run-off and no metric is
+        available.
+        """
+
+    def write_step_data_csv(self, path: str | Path, model_index: int = 0) -> None:
+        """
+        Export all the data of `ModelStepData` for one hydrological unit to a CSV
+        file with one row per time step.
+        :param path: The destination CSV file.
+        :param model_index: The index of the hydrological unit. Default to 0.
+        :raises IndexError: When the hydrological unit does not exist.
+        """
+
 
 class GR6JModel:
     """
@@ -528,6 +616,15 @@ class GR6JModel:
         :param input: The input data. See `GR6JModelInputs` how to build this class.
         """
 
+    def describe(self) -> dict:
+        """
+        Get a summary of the model configuration. The dictionary contains the
+        "catchments" key with the area and parameters of each hydrological unit, the
+        "run_period", the "warmup_period" (None if the model has no warm-up period), the
+        "run_off_unit" and "has_observed_runoff".
+        :return: The dictionary with the model configuration.
+        """
+
     def run(self) -> GR6JModelOutputs:
         """
         Run the model and export charts and outputs.
@@ -535,6 +632,23 @@ class GR6JModel:
         simulated time and run off values.
         """
 
+    def reset(self) -> None:
+        """
+        Restore the initial state of the model so that it can be run again. Without
+        resetting the model, a second `run` continues from the end of the previous
+        simulation.
+        """
+
+    def step(self, model_index: int = 0) -> ModelStepData:
+        """
+        Advance the model by one day for one hydrological unit, so that the model can be
+        driven day by day. The days of the warm-up period are returned as well. Use
+        `reset` to restart the simulation.
+        :param model_index: The index of the hydrological unit. Default to 0.
+        :return: The data of the simulated day.
+        :raises StopIteration: When the end of the simulation is reached.
+        """
+
 
 class X1Range:
     lower_bound: float
@@ -819,3 +933,10 @@ class Calibration:
         :param catchment_index: The index (0 based) of the catchment.
         :return: The parameter values.
         """
+
+    def metric(self, index: int) -> CalibrationMetric:
+        """
+        Get all the calibration metrics calculated for one model.
+        :param index: The index (0 based) of the model.
+        :return: The calibration metrics.
+        """
@@ -1,4 +1,5 @@
 use crate::inputs::{ModelPeriod, RunOffUnit};
+use crate::outputs::CalibrationMetric;
 use crate::parameter::{X1Range, X2Range, X3Range, X4Range, X5Range, X6Range};
 use ::gr6j::inputs::CalibrationCatchmentData as RsCalibrationCatchmentData;
 use chrono::NaiveDate;
@@ -241,10 +242,22 @@ impl Calibration {
             observed_runoff: &inputs.observed_runoff,
             catchment: inputs.rs_catchment,
             calibration_period: inputs.calibration_period.0,
+            warmup_period: None,
             destination: inputs.destination,
             run_off_unit: inputs.run_off_unit.into(),
             sample_size: inputs.sample_size,
             generate_comparison_charts: inputs.generate_comparison_charts.unwrap_or(true),
+            csv_precision: None,
+            skip_failures: None,
+            objective: None,
+            objective_fn: None,
+            output_format: None,
+            seed: None,
+            sampling_strategy: None,
+            progress: None,
+            max_threads: None,
+            chart_theme: None,
+            optional_metrics: None,
         };
         let mut rs_calibration = RsCalibration::new(inputs).map_err(|e| PyValueError::new_err(e.to_string()))?;
 
@@ -322,4 +335,11 @@ impl Calibration {
     pub fn volume_error(&self) -> Vec<f64> {
         self.0.metrics.volume_error()
     }
+
+    pub fn metric(&self, index: usize) -> PyResult<CalibrationMetric> {
+        match self.0.metrics.get(index) {
+            None => Err(PyIndexError::new_err("Out of bounds")),
+            Some(m) => Ok(m.clone().into()),
+        }
+    }
 }
@@ -1,10 +1,11 @@
 use crate::parameter::{X1, X2, X3, X4, X5, X6};
 use ::gr6j::inputs::{
-    CatchmentData as RsCatchmentData, ModelPeriod as RsModelPeriod, RunOffUnit as RsRunOffUnit,
+    CatchmentData as RsCatchmentData, InitialStoreLevels, ModelPeriod as RsModelPeriod, RunOffUnit as RsRunOffUnit,
     StoreLevels as RsStoreLevels,
 };
 use chrono::NaiveDate;
 use gr6j::parameter::Parameter;
+use numpy::PyReadonlyArray1;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyList;
@@ -96,7 +97,8 @@ impl CatchmentData {
             x4: Box::from(x4.0),
             x5: Box::from(x5.0),
             x6: Box::from(x6.0),
-            store_levels: store_levels.map(Into::into),
+            store_levels: store_levels.map(|levels| InitialStoreLevels::Fraction(levels.into())),
+            uh_exponent: None,
         };
         Ok(CatchmentData {
             area,
@@ -178,6 +180,8 @@ pub enum RunOffUnit {
     MlPerDay,
     #[pyo3(name = "CUBIC_METRE_PER_SECOND")]
     CubicMetrePerSecond,
+    #[pyo3(name = "LITRE_PER_SECOND")]
+    LitrePerSecond,
 }
 
 impl RunOffUnit {
@@ -193,6 +197,7 @@ impl From<RunOffUnit> for RsRunOffUnit {
             RunOffUnit::CubicMetrePerDay => RsRunOffUnit::CubicMetrePerDay,
             RunOffUnit::MlPerDay => RsRunOffUnit::MlPerDay,
             RunOffUnit::CubicMetrePerSecond => RsRunOffUnit::CubicMetrePerSecond,
+            RunOffUnit::LitrePerSecond => RsRunOffUnit::LitrePerSecond,
         }
     }
 }
@@ -286,6 +291,25 @@ pub struct GR6JModelInputs {
     pub run_off_unit: Option<RunOffUnit>,
 }
 
+/// A series of numbers given either as a numpy array or as a list of floats.
+#[derive(FromPyObject)]
+pub enum FloatSeries<'py> {
+    Array(PyReadonlyArray1<'py, f64>),
+    List(Vec<f64>),
+}
+
+impl FloatSeries<'_> {
+    /// Convert the series to a vector.
+    ///
+    /// returns: Vec<f64>
+    pub fn into_vec(self) -> Vec<f64> {
+        match self {
+            FloatSeries::Array(a) => a.as_array().to_vec(),
+            FloatSeries::List(v) => v,
+        }
+    }
+}
+
 #[pymethods]
 impl GR6JModelInputs {
     #[new]
@@ -293,13 +317,13 @@ impl GR6JModelInputs {
     #[allow(clippy::too_many_arguments)]
     fn new(
         time: Vec<NaiveDate>,
-        precipitation: Vec<f64>,
-        evapotranspiration: Vec<f64>,
+        precipitation: FloatSeries,
+        evapotranspiration: FloatSeries,
         catchment: PyObject,
         run_period: ModelPeriod,
         warmup_period: Option<ModelPeriod>,
         destination: Option<PathBuf>,
-        observed_runoff: Option<Vec<f64>>,
+        observed_runoff: Option<FloatSeries>,
         run_off_unit: Option<RunOffUnit>,
     ) -> PyResult<Self> {
         let catchment = CatchmentDataVec::try_from(catchment)?;
@@ -307,14 +331,14 @@ impl GR6JModelInputs {
 
         Ok(GR6JModelInputs {
             time,
-            precipitation,
-            evapotranspiration,
+            precipitation: precipitation.into_vec(),
+            evapotranspiration: evapotranspiration.into_vec(),
             catchment,
             rs_catchment,
             run_period,
             warmup_period,
             destination,
-            observed_runoff,
+            observed_runoff: observed_runoff.map(FloatSeries::into_vec),
             run_off_unit,
         })
     }
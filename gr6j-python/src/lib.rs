@@ -2,10 +2,12 @@ use crate::calibration::{Calibration, CalibrationCatchmentData, CalibrationInput
 use crate::inputs::{CatchmentData, GR6JModelInputs, ModelPeriod, RunOffUnit, StoreLevels};
 use crate::outputs::{CalibrationMetric, GR6JOutputs, Metric, ModelStepData};
 use crate::parameter::{X1Range, X2Range, X3Range, X4Range, X5Range, X6Range, X1, X2, X3, X4, X5, X6};
+use ::gr6j::error::RunModelError;
 use ::gr6j::inputs::{GR6JModelInputs as RsGR6JModelInputs, ModelPeriod as RsModelPeriod};
 use ::gr6j::model::GR6JModel as RsGR6JModel;
-use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyStopIteration, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
 
 mod calibration;
 mod inputs;
@@ -33,7 +35,7 @@ impl GR6JModel {
             destination: inputs.destination,
             observed_runoff: inputs.observed_runoff.as_deref(),
             run_off_unit: inputs.run_off_unit.unwrap_or_default().into(),
-            logging: None,
+            ..Default::default()
         };
         let model = GR6JModel {
             run_period,
@@ -53,6 +55,35 @@ impl GR6JModel {
         self.__repr__().unwrap()
     }
 
+    /// Get a summary of the model configuration
+    fn describe(&self) -> PyResult<PyObject> {
+        let description = self.rs_model.describe();
+        Python::with_gil(|py| {
+            let catchments = PyList::empty_bound(py);
+            for c in description.catchments.iter() {
+                let data = PyDict::new_bound(py);
+                data.set_item("area", c.area)?;
+                data.set_item("x1", c.x1)?;
+                data.set_item("x2", c.x2)?;
+                data.set_item("x3", c.x3)?;
+                data.set_item("x4", c.x4)?;
+                data.set_item("x5", c.x5)?;
+                data.set_item("x6", c.x6)?;
+                catchments.append(data)?;
+            }
+
+            let dict = PyDict::new_bound(py);
+            dict.set_item("catchments", catchments)?;
+            dict.set_item("run_period", ModelPeriod(description.run_period).into_py(py))?;
+            dict.set_item("warmup_period", description.warmup_period.map(ModelPeriod).into_py(py))?;
+            dict.set_item("run_off_unit", description.run_off_unit.unit_label())?;
+            dict.set_item("has_observed_runoff", description.has_observed_runoff)?;
+            dict.set_item("precip_correction", description.precip_correction)?;
+            dict.set_item("pet_correction", description.pet_correction)?;
+            Ok(dict.into_py(py))
+        })
+    }
+
     /// Run the model
     fn run(&mut self) -> PyResult<GR6JOutputs> {
         let results = self
@@ -74,9 +105,34 @@ impl GR6JModel {
             catchment_outputs: model_results,
             time: results.time,
             run_off: results.run_off,
-            metrics: results.metrics.map(Into::into),
+            metrics: results.metrics.clone().map(Into::into),
+            rs_metrics: results.metrics,
         })
     }
+
+    /// Restore the initial state of the model to run it again
+    fn reset(&mut self) {
+        self.rs_model.reset();
+    }
+
+    /// Advance the model by one day for one hydrological unit. This raises StopIteration when
+    /// the end of the simulation is reached
+    #[pyo3(signature = (model_index=0))]
+    fn step(&mut self, model_index: usize) -> PyResult<ModelStepData> {
+        let total_models = self.rs_model.describe().catchments.len();
+        if model_index >= total_models {
+            return Err(PyIndexError::new_err(format!(
+                "The model index {model_index} must be smaller than the number of hydrological units ({total_models})"
+            )));
+        }
+        match self.rs_model.step(model_index) {
+            Ok(step_data) => Ok(step_data.into()),
+            Err(RunModelError::ReachedSimulationEnd()) => Err(PyStopIteration::new_err(
+                RunModelError::ReachedSimulationEnd().to_string(),
+            )),
+            Err(e) => Err(PyRuntimeError::new_err(e.to_string())),
+        }
+    }
 }
 
 #[pymodule]
@@ -1,9 +1,12 @@
 use crate::inputs::StoreLevels;
 use ::gr6j::metric::{CalibrationMetric as RsCalibrationMetric, Metric as RsMetric};
-use ::gr6j::outputs::ModelStepData as RsModelStepData;
+use ::gr6j::outputs::{ModelStepData as RsModelStepData, ModelStepDataVector as RsModelStepDataVector};
+use ::gr6j::utils::Fdc;
 use chrono::NaiveDate;
+use pyo3::exceptions::{PyDeprecationWarning, PyIndexError, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
+use std::path::PathBuf;
 
 #[pyclass(get_all)]
 #[derive(Clone)]
@@ -72,7 +75,12 @@ pub struct CalibrationMetric {
     log_nash_sutcliffe: Metric,
     kling_gupta2009: Metric,
     kling_gupta2012: Metric,
-    non_paramettric_kling_gupta: Metric,
+    non_parametric_kling_gupta: Metric,
+    error_sign_fractions: (f64, f64, f64),
+    anomaly_nash_sutcliffe: Metric,
+    pearson: Metric,
+    r_squared: Metric,
+    percent_bias: Metric,
 }
 
 impl From<RsCalibrationMetric> for CalibrationMetric {
@@ -82,7 +90,12 @@ impl From<RsCalibrationMetric> for CalibrationMetric {
             log_nash_sutcliffe: m.log_nash_sutcliffe.into(),
             kling_gupta2009: m.kling_gupta2009.into(),
             kling_gupta2012: m.kling_gupta2012.into(),
-            non_paramettric_kling_gupta: m.non_parametric_kling_gupta.into(),
+            non_parametric_kling_gupta: m.non_parametric_kling_gupta.into(),
+            error_sign_fractions: m.error_sign_fractions,
+            anomaly_nash_sutcliffe: m.anomaly_nash_sutcliffe.into(),
+            pearson: m.pearson.into(),
+            r_squared: m.r_squared.into(),
+            percent_bias: m.percent_bias.into(),
         }
     }
 }
@@ -97,6 +110,21 @@ impl CalibrationMetric {
     }
 }
 
+#[pymethods]
+impl CalibrationMetric {
+    /// Deprecated alias of `non_parametric_kling_gupta`. This will be removed in the next release.
+    #[getter]
+    fn non_paramettric_kling_gupta(&self, py: Python<'_>) -> PyResult<Metric> {
+        PyErr::warn_bound(
+            py,
+            &py.get_type_bound::<PyDeprecationWarning>(),
+            "non_paramettric_kling_gupta is deprecated, use non_parametric_kling_gupta instead",
+            1,
+        )?;
+        Ok(self.non_parametric_kling_gupta.clone())
+    }
+}
+
 impl From<RsModelStepData> for ModelStepData {
     fn from(value: RsModelStepData) -> Self {
         ModelStepData {
@@ -106,8 +134,8 @@ impl From<RsModelStepData> for ModelStepData {
             net_rainfall: value.net_rainfall,
             store_levels: StoreLevels::new(
                 value.store_levels.production_store,
-                value.store_levels.exponential_store,
                 value.store_levels.routing_store,
+                value.store_levels.exponential_store,
             ),
             storage_p: value.storage_p,
             actual_evapotranspiration: value.actual_evapotranspiration,
@@ -125,12 +153,41 @@ impl From<RsModelStepData> for ModelStepData {
     }
 }
 
-#[pyclass(get_all)]
+impl From<&ModelStepData> for RsModelStepData {
+    fn from(value: &ModelStepData) -> Self {
+        RsModelStepData {
+            time: value.time,
+            evapotranspiration: value.evapotranspiration,
+            precipitation: value.precipitation,
+            net_rainfall: value.net_rainfall,
+            store_levels: value.store_levels.into(),
+            storage_p: value.storage_p,
+            actual_evapotranspiration: value.actual_evapotranspiration,
+            percolation: value.percolation,
+            pr: value.pr,
+            exchange: value.exchange,
+            exchange_from_routing_store: value.exchange_from_routing_store,
+            exchange_from_direct_branch: value.exchange_from_direct_branch,
+            actual_exchange: value.actual_exchange,
+            routing_store_outflow: value.routing_store_outflow,
+            exponential_store_outflow: value.exponential_store_outflow,
+            outflow_from_uh2_branch: value.outflow_from_uh2_branch,
+            run_off: value.run_off,
+        }
+    }
+}
+
+#[pyclass]
 pub struct GR6JOutputs {
+    #[pyo3(get)]
     pub catchment_outputs: Vec<Vec<ModelStepData>>,
+    #[pyo3(get)]
     pub time: Vec<NaiveDate>,
+    #[pyo3(get)]
     pub run_off: Vec<f64>,
+    #[pyo3(get)]
     pub metrics: Option<CalibrationMetric>,
+    pub rs_metrics: Option<RsCalibrationMetric>,
 }
 
 #[pymethods]
@@ -168,4 +225,36 @@ impl GR6JOutputs {
 
         Ok(df)
     }
+
+    /// Get the flow duration curve of the simulated run-off as a tuple with the percentage of
+    /// exceedence and the sorted run-off
+    pub fn fdc(&self) -> (Vec<f64>, Vec<f64>) {
+        let fdc = Fdc::new(&self.run_off);
+        (fdc.exceedence, fdc.sorted_run_off)
+    }
+
+    /// Export the calibration metrics to a CSV file. This raises an error when the model has no
+    /// observed run-off
+    pub fn write_metrics_csv(&self, path: PathBuf) -> PyResult<()> {
+        let metrics = self.rs_metrics.as_ref().ok_or_else(|| {
+            PyValueError::new_err("The metrics are only available when the observed run-off is given")
+        })?;
+        metrics
+            .to_csv(path, None)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    /// Export all the step data of a hydrological unit to a CSV file with one row per time step
+    #[pyo3(signature = (path, model_index=0))]
+    pub fn write_step_data_csv(&self, path: PathBuf, model_index: usize) -> PyResult<()> {
+        let total_models = self.catchment_outputs.len();
+        let outputs = self.catchment_outputs.get(model_index).ok_or_else(|| {
+            PyIndexError::new_err(format!(
+                "The model index {model_index} must be smaller than the number of hydrological units ({total_models})"
+            ))
+        })?;
+        RsModelStepDataVector(outputs.iter().map(Into::into).collect())
+            .to_csv(&path, None)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
 }
@@ -32,6 +32,10 @@ impl X1 {
     pub fn description() -> PyResult<String> {
         Ok(RsX1::description().to_string())
     }
+    #[staticmethod]
+    pub fn bounds() -> PyResult<(f64, f64)> {
+        Ok((RsX1::min_value(), RsX1::max_value()))
+    }
     pub fn __repr__(&self) -> PyResult<String> {
         Ok(self.0.value().to_string())
     }
@@ -65,7 +69,11 @@ impl X2 {
     }
     #[staticmethod]
     pub fn description() -> PyResult<String> {
-        Ok(RsX1::description().to_string())
+        Ok(RsX2::description().to_string())
+    }
+    #[staticmethod]
+    pub fn bounds() -> PyResult<(f64, f64)> {
+        Ok((RsX2::min_value(), RsX2::max_value()))
     }
     pub fn __repr__(&self) -> PyResult<String> {
         Ok(self.0.value().to_string())
@@ -100,7 +108,11 @@ impl X3 {
     }
     #[staticmethod]
     pub fn description() -> PyResult<String> {
-        Ok(RsX1::description().to_string())
+        Ok(RsX3::description().to_string())
+    }
+    #[staticmethod]
+    pub fn bounds() -> PyResult<(f64, f64)> {
+        Ok((RsX3::min_value(), RsX3::max_value()))
     }
     pub fn __repr__(&self) -> PyResult<String> {
         Ok(self.0.value().to_string())
@@ -135,7 +147,11 @@ impl X4 {
     }
     #[staticmethod]
     pub fn description() -> PyResult<String> {
-        Ok(RsX1::description().to_string())
+        Ok(RsX4::description().to_string())
+    }
+    #[staticmethod]
+    pub fn bounds() -> PyResult<(f64, f64)> {
+        Ok((RsX4::min_value(), RsX4::max_value()))
     }
     pub fn __repr__(&self) -> PyResult<String> {
         Ok(self.0.value().to_string())
@@ -170,7 +186,11 @@ impl X5 {
     }
     #[staticmethod]
     pub fn description() -> PyResult<String> {
-        Ok(RsX1::description().to_string())
+        Ok(RsX5::description().to_string())
+    }
+    #[staticmethod]
+    pub fn bounds() -> PyResult<(f64, f64)> {
+        Ok((RsX5::min_value(), RsX5::max_value()))
     }
     pub fn __repr__(&self) -> PyResult<String> {
         Ok(self.0.value().to_string())
@@ -205,7 +225,11 @@ impl X6 {
     }
     #[staticmethod]
     pub fn description() -> PyResult<String> {
-        Ok(RsX1::description().to_string())
+        Ok(RsX6::description().to_string())
+    }
+    #[staticmethod]
+    pub fn bounds() -> PyResult<(f64, f64)> {
+        Ok((RsX6::min_value(), RsX6::max_value()))
     }
     pub fn __repr__(&self) -> PyResult<String> {
         Ok(self.0.value().to_string())
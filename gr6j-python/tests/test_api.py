@@ -61,6 +61,231 @@ def test_simple_model(data_path):
     model.run()
 
 
+def test_non_parametric_kling_gupta(data_path):
+    data = pd.read_csv(
+        data_path / "airGR_L0123001_dataset.csv",
+        index_col=[0],
+        parse_dates=True,
+        dayfirst=True,
+    )
+    inputs = GR6JModelInputs(
+        time=data.index.tolist(),
+        precipitation=data["P"].tolist(),
+        evapotranspiration=data["E"].tolist(),
+        catchment=CatchmentData(
+            area=1.0,
+            x1=X1(31),
+            x2=X2(3.47),
+            x3=X3(32),
+            x4=X4(2.1),
+            x5=X5(0.55),
+            x6=X6(5.3),
+        ),
+        run_period=ModelPeriod(start=date(1990, 1, 1), end=date(1990, 12, 31)),
+        observed_runoff=data["Qmm"].tolist(),
+        run_off_unit=RunOffUnit.NO_CONVERSION,
+    )
+    metrics = GR6JModel(inputs).run().metrics
+
+    metric = metrics.non_parametric_kling_gupta
+    assert metric.name == "Non-parametric Kling-Gupta"
+    assert metric.value <= 1.0
+
+    # the misspelled name is still available but deprecated
+    with pytest.warns(DeprecationWarning):
+        alias = metrics.non_paramettric_kling_gupta
+    assert alias.value == metric.value
+
+
+def test_numpy_inputs(data_path):
+    data = pd.read_csv(
+        data_path / "airGR_L0123001_dataset.csv",
+        index_col=[0],
+        parse_dates=True,
+        dayfirst=True,
+    )
+    data = data.iloc[0:730]
+    catchment = CatchmentData(
+        area=1.0,
+        x1=X1(31),
+        x2=X2(3.47),
+        x3=X3(32),
+        x4=X4(2.1),
+        x5=X5(0.55),
+        x6=X6(5.3),
+    )
+    time = data.index.date.tolist()
+    run_period = ModelPeriod(start=time[365], end=time[-1])
+
+    list_inputs = GR6JModelInputs(
+        time=time,
+        precipitation=data["P"].tolist(),
+        evapotranspiration=data["E"].tolist(),
+        catchment=catchment,
+        run_period=run_period,
+        observed_runoff=data["Qmm"].tolist(),
+    )
+    numpy_inputs = GR6JModelInputs(
+        time=time,
+        precipitation=data["P"].to_numpy(),
+        evapotranspiration=data["E"].to_numpy(),
+        catchment=catchment,
+        run_period=run_period,
+        observed_runoff=data["Qmm"].to_numpy(),
+    )
+    assert numpy_inputs.precipitation == list_inputs.precipitation
+    assert numpy_inputs.evapotranspiration == list_inputs.evapotranspiration
+    assert numpy_inputs.observed_runoff == list_inputs.observed_runoff
+
+    expected = GR6JModel(list_inputs).run()
+    results = GR6JModel(numpy_inputs).run()
+    assert results.run_off == expected.run_off
+
+
+def test_fdc_and_metrics_csv(data_path, tmp_path):
+    data = pd.read_csv(
+        data_path / "airGR_L0123001_dataset.csv",
+        index_col=[0],
+        parse_dates=True,
+        dayfirst=True,
+    )
+    inputs = GR6JModelInputs(
+        time=data.index.tolist(),
+        precipitation=data["P"].tolist(),
+        evapotranspiration=data["E"].tolist(),
+        catchment=CatchmentData(
+            area=1.0,
+            x1=X1(31),
+            x2=X2(3.47),
+            x3=X3(32),
+            x4=X4(2.1),
+            x5=X5(0.55),
+            x6=X6(5.3),
+        ),
+        run_period=ModelPeriod(start=date(1990, 1, 1), end=date(1994, 12, 31)),
+        destination=str(tmp_path),
+        observed_runoff=data["Qmm"].tolist(),
+        run_off_unit=RunOffUnit.NO_CONVERSION,
+    )
+    results = GR6JModel(inputs).run()
+
+    # flow duration curve
+    exceedence, sorted_run_off = results.fdc()
+    total = len(results.run_off)
+    assert len(exceedence) == total
+    assert exceedence[0] == pytest.approx(100 / total)
+    assert exceedence[-1] == pytest.approx(100)
+    assert sorted(sorted_run_off) == sorted(results.run_off)
+
+    # the metric file matches the one exported by the model
+    metric_file = tmp_path / "metrics.csv"
+    results.write_metrics_csv(metric_file)
+    exported_file = next(tmp_path.rglob("Metrics.csv"))
+    assert metric_file.read_text() == exported_file.read_text()
+    assert metric_file.read_text().startswith("Metric,Value,Ideal value")
+
+
+def test_write_metrics_csv_without_observed_runoff(tmp_path):
+    t = [date(2000, 1, 1 + d) for d in range(10)]
+    inputs = GR6JModelInputs(
+        time=t,
+        precipitation=[1.0] * 10,
+        evapotranspiration=[0.5] * 10,
+        catchment=CatchmentData(
+            area=1.0,
+            x1=X1(31),
+            x2=X2(3.47),
+            x3=X3(32),
+            x4=X4(2.1),
+            x5=X5(0.55),
+            x6=X6(5.3),
+        ),
+        run_period=ModelPeriod(start=t[0], end=t[-1]),
+    )
+    results = GR6JModel(inputs).run()
+    with pytest.raises(ValueError):
+        results.write_metrics_csv(tmp_path / "metrics.csv")
+
+
+def test_step():
+    t = [date(2000, 1, 1 + d) for d in range(20)]
+    inputs = GR6JModelInputs(
+        time=t,
+        precipitation=[float(d % 4) for d in range(20)],
+        evapotranspiration=[0.5] * 20,
+        catchment=CatchmentData(
+            area=1.0,
+            x1=X1(31),
+            x2=X2(3.47),
+            x3=X3(32),
+            x4=X4(2.1),
+            x5=X5(0.55),
+            x6=X6(5.3),
+        ),
+        run_period=ModelPeriod(start=t[5], end=t[-1]),
+        warmup_period=ModelPeriod(start=t[0], end=t[4]),
+        run_off_unit=RunOffUnit.NO_CONVERSION,
+    )
+    model = GR6JModel(inputs)
+    expected = model.run().catchment_outputs[0]
+
+    # step through the warm-up and run periods until the end of the simulation
+    model.reset()
+    steps = []
+    with pytest.raises(StopIteration):
+        while True:
+            steps.append(model.step())
+    steps = [s for s in steps if s.time >= t[5]]
+
+    assert [s.time for s in steps] == [s.time for s in expected]
+    assert sum(s.run_off for s in steps) == pytest.approx(sum(s.run_off for s in expected))
+
+    with pytest.raises(IndexError):
+        model.step(model_index=1)
+
+
+def test_step_data_store_levels(data_path, tmp_path):
+    data = pd.read_csv(
+        data_path / "airGR_L0123001_dataset.csv",
+        index_col=[0],
+        parse_dates=True,
+        dayfirst=True,
+    )
+    start = date(1990, 1, 1)
+    end = date(1990, 12, 31)
+
+    inputs = GR6JModelInputs(
+        time=data.index.tolist(),
+        precipitation=data["P"].tolist(),
+        evapotranspiration=data["E"].tolist(),
+        catchment=CatchmentData(
+            area=1.0,
+            x1=X1(31),
+            x2=X2(3.47),
+            x3=X3(32),
+            x4=X4(2.1),
+            x5=X5(0.55),
+            x6=X6(5.3),
+        ),
+        run_period=ModelPeriod(start=start, end=end),
+        run_off_unit=RunOffUnit.NO_CONVERSION,
+        destination=str(tmp_path),
+    )
+    results = GR6JModel(inputs).run()
+
+    # the store levels must match the ones exported by the Rust library
+    step_data = results.catchment_outputs[0]
+    step_data_file = tmp_path / "step_data.csv"
+    results.write_step_data_csv(step_data_file)
+    exported = pd.read_csv(step_data_file)
+    assert len(step_data) == len(exported)
+    for step, (_, row) in zip(step_data, exported.iterrows()):
+        levels = step.store_levels
+        assert levels.production_store == pytest.approx(row["store_levels.production_store"], abs=1e-5)
+        assert levels.routing_store == pytest.approx(row["store_levels.routing_store"], abs=1e-5)
+        assert levels.exponential_store == pytest.approx(row["store_levels.exponential_store"], abs=1e-5)
+
+
 def test_destination_exception():
     t = [date(1999, 1, 1), date(1999, 1, 2)]
     inputs = GR6JModelInputs(
@@ -96,6 +321,16 @@ def test_parameter_exception():
         )
 
 
+def test_parameter_metadata():
+    descriptions = set()
+    for parameter in [X1, X2, X3, X4, X5, X6]:
+        assert parameter.bounds() == (parameter.min(), parameter.max())
+        assert parameter.min() < parameter.max()
+        descriptions.add(parameter.description())
+    assert len(descriptions) == 6
+    assert "X1" in X1.description()
+
+
 def test_model_period_exception():
     with pytest.raises(ValueError):
         ModelPeriod(start=date(1999, 12, 1), end=date(1999, 1, 1))